@@ -1,4 +1,6 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use snowflake_rs_impl::clock::{CachedClock, CoarseClock};
+use snowflake_rs_impl::const_layout::DefaultConstSnowflake;
 use snowflake_rs_impl::snowflake::Snowflake;
 use std::sync::Arc;
 use std::thread;
@@ -12,6 +14,51 @@ fn benchmark_single_thread(c: &mut Criterion) {
     });
 }
 
+fn benchmark_single_thread_unchecked(c: &mut Criterion) {
+    let snowflake = Snowflake::new(1, None).unwrap();
+    c.bench_function("single thread unchecked generation", |b| {
+        b.iter(|| {
+            black_box(snowflake.generate_unchecked());
+        })
+    });
+}
+
+fn benchmark_single_thread_const_layout(c: &mut Criterion) {
+    let snowflake = DefaultConstSnowflake::new(1, None).unwrap();
+    c.bench_function("single thread const layout generation", |b| {
+        b.iter(|| {
+            black_box(snowflake.generate().unwrap());
+        })
+    });
+}
+
+fn benchmark_single_thread_coarse_clock(c: &mut Criterion) {
+    let snowflake = Snowflake::builder(1).clock(Arc::new(CoarseClock)).build().unwrap();
+    c.bench_function("single thread coarse clock generation", |b| {
+        b.iter(|| {
+            black_box(snowflake.generate().unwrap());
+        })
+    });
+}
+
+fn benchmark_single_thread_cached_clock(c: &mut Criterion) {
+    let snowflake = Snowflake::builder(1).clock(Arc::new(CachedClock::new())).build().unwrap();
+    c.bench_function("single thread cached clock generation", |b| {
+        b.iter(|| {
+            black_box(snowflake.generate().unwrap());
+        })
+    });
+}
+
+fn benchmark_batch(c: &mut Criterion) {
+    let snowflake = Snowflake::new(1, None).unwrap();
+    c.bench_function("batch generation of 4096", |b| {
+        b.iter(|| {
+            black_box(snowflake.generate_batch(4096).unwrap());
+        })
+    });
+}
+
 fn benchmark_multi_thread(c: &mut Criterion) {
     c.bench_function("multi thread generation", |b| {
         b.iter(|| {
@@ -32,5 +79,5 @@ fn benchmark_multi_thread(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, benchmark_single_thread, benchmark_multi_thread);
+criterion_group!(benches, benchmark_single_thread, benchmark_single_thread_unchecked, benchmark_single_thread_const_layout, benchmark_single_thread_coarse_clock, benchmark_single_thread_cached_clock, benchmark_batch, benchmark_multi_thread);
 criterion_main!(benches);
\ No newline at end of file
@@ -0,0 +1,201 @@
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use tokio::sync::{mpsc, oneshot};
+
+use crate::id::SnowflakeId;
+use crate::snowflake::{Snowflake, SnowflakeError};
+
+/// Default number of requests that may queue up before `IdHandle::next` waits
+pub const DEFAULT_CHANNEL_CAPACITY: usize = 1024;
+
+/// Most requests the service takes off the channel at a time
+const MAX_COALESCED: usize = 256;
+
+// A request sent by a handle to the service task
+enum Request {
+    One(oneshot::Sender<Result<SnowflakeId, SnowflakeError>>),
+    Batch(usize, oneshot::Sender<Result<Vec<SnowflakeId>, SnowflakeError>>),
+}
+
+/// Cheap, cloneable handle to a generator owned by a background task
+///
+/// `channel` (or `IdHandle::spawn` on tokio) moves a `Snowflake` into a task and returns a
+/// handle to it. Every
+/// `next` or `next_batch` call sends a request over a bounded channel and waits for the
+/// reply, so thousands of tasks can draw IDs without sharing the generator: they only
+/// clone the handle. Once the channel is full, callers wait for room, which applies
+/// backpressure instead of queueing without bound.
+///
+/// The task takes all queued requests off the channel at once and serves the single IDs
+/// among them with one `generate_batch` call. It runs until every handle has been dropped.
+///
+/// The task calls the generator directly, so a generator whose rate limit uses
+/// `ThrottleMode::Wait` blocks a runtime worker thread while it waits; prefer
+/// `ThrottleMode::Error` here.
+///
+/// # Example
+/// ```
+/// # #[cfg(feature = "tokio")]
+/// # {
+/// use snowflake_rs_impl::actor::IdHandle;
+/// use snowflake_rs_impl::snowflake::Snowflake;
+///
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// let handle = IdHandle::spawn(Snowflake::new(1, None).unwrap());
+/// let id = handle.next().await.unwrap();
+/// let batch = handle.clone().next_batch(10).await.unwrap();
+/// assert!(batch.iter().all(|next| *next > id));
+/// # });
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct IdHandle {
+    requests: mpsc::Sender<Request>,
+}
+
+impl IdHandle {
+    /// Spawns a task owning `generator` with room for DEFAULT_CHANNEL_CAPACITY queued
+    /// requests, and returns a handle to it
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a tokio runtime
+    #[cfg(feature = "tokio")]
+    pub fn spawn(generator: Snowflake) -> Self {
+        IdHandle::spawn_with_capacity(generator, DEFAULT_CHANNEL_CAPACITY)
+    }
+
+    /// Spawns a task owning `generator` with room for `capacity` queued requests, and
+    /// returns a handle to it
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a tokio runtime, or if `capacity` is 0
+    #[cfg(feature = "tokio")]
+    pub fn spawn_with_capacity(generator: Snowflake, capacity: usize) -> Self {
+        let (handle, service) = channel(generator, capacity);
+        tokio::spawn(service);
+        handle
+    }
+
+    /// Generates a new Snowflake ID
+    ///
+    /// Waits for room in the channel if the service is busy.
+    ///
+    /// # Errors
+    ///
+    /// - SnowflakeError::ServiceStopped if the service task is no longer running (e.g. the
+    ///   runtime is shutting down)
+    /// - Otherwise, same as `Snowflake::generate`
+    pub async fn next(&self) -> Result<SnowflakeId, SnowflakeError> {
+        let (reply, response) = oneshot::channel();
+        self.send(Request::One(reply)).await?;
+        response.await.map_err(|_| SnowflakeError::ServiceStopped)?
+    }
+
+    /// Generates `n` Snowflake IDs in ascending order
+    ///
+    /// # Errors
+    ///
+    /// Same as `next`
+    pub async fn next_batch(&self, n: usize) -> Result<Vec<SnowflakeId>, SnowflakeError> {
+        let (reply, response) = oneshot::channel();
+        self.send(Request::Batch(n, reply)).await?;
+        response.await.map_err(|_| SnowflakeError::ServiceStopped)?
+    }
+
+    /// Returns true if the service task is no longer running
+    pub fn is_stopped(&self) -> bool {
+        self.requests.is_closed()
+    }
+
+    // Queues a request, waiting for room in the channel
+    async fn send(&self, request: Request) -> Result<(), SnowflakeError> {
+        self.requests.send(request).await.map_err(|_| SnowflakeError::ServiceStopped)
+    }
+}
+
+/// The task serving requests from `IdHandle`s, created by `channel`
+///
+/// A future that runs until every handle has been dropped. It uses no timers or I/O,
+/// so it can be spawned on any executor.
+pub struct IdService {
+    inner: Pin<Box<dyn Future<Output = ()> + Send>>,
+}
+
+impl fmt::Debug for IdService {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("IdService").finish_non_exhaustive()
+    }
+}
+
+impl Future for IdService {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        self.inner.as_mut().poll(cx)
+    }
+}
+
+/// Moves `generator` into a service with room for `capacity` queued requests, returning
+/// a handle to it and the service to spawn
+///
+/// Nothing is generated until the service is spawned (or otherwise polled), so this works
+/// with any runtime, e.g. `smol::spawn(service).detach()` or
+/// `async_std::task::spawn(service)`.
+///
+/// # Panics
+///
+/// Panics if `capacity` is 0
+///
+/// # Example
+/// ```
+/// use snowflake_rs_impl::actor;
+/// use snowflake_rs_impl::snowflake::Snowflake;
+///
+/// let (handle, service) = actor::channel(Snowflake::new(1, None).unwrap(), 64);
+/// smol::spawn(service).detach();
+/// let id = smol::block_on(handle.next()).unwrap();
+/// assert_eq!(id.node(), 1);
+/// ```
+pub fn channel(generator: Snowflake, capacity: usize) -> (IdHandle, IdService) {
+    let (requests, receiver) = mpsc::channel(capacity);
+    let service = IdService {
+        inner: Box::pin(run(generator, receiver)),
+    };
+    (IdHandle { requests }, service)
+}
+
+// Serves requests until every handle is dropped
+async fn run(generator: Snowflake, mut receiver: mpsc::Receiver<Request>) {
+    let mut requests = Vec::with_capacity(MAX_COALESCED);
+    while receiver.recv_many(&mut requests, MAX_COALESCED).await > 0 {
+        serve(&generator, &mut requests);
+    }
+}
+
+// Replies to every request in `requests`, generating the single IDs as one batch.
+// Replies to callers that have given up are dropped.
+fn serve(generator: &Snowflake, requests: &mut Vec<Request>) {
+    let singles = requests.iter().filter(|request| matches!(request, Request::One(_))).count();
+    // The error is not cloneable, so on failure every single request is retried on its
+    // own and gets its own result. A failed batch gives its rate limit tokens back.
+    let mut ids = match singles {
+        0 | 1 => None,
+        _ => generator.generate_batch(singles).ok().map(Vec::into_iter),
+    };
+    for request in requests.drain(..) {
+        match request {
+            Request::One(reply) => {
+                let id = ids.as_mut().and_then(Iterator::next).map_or_else(|| generator.generate_id(), Ok);
+                let _ = reply.send(id);
+            }
+            Request::Batch(n, reply) => {
+                let _ = reply.send(generator.generate_batch(n));
+            }
+        }
+    }
+}
@@ -0,0 +1,290 @@
+use arrow_array::builder::{ArrayBuilder, UInt64Builder};
+use arrow_array::cast::AsArray;
+use arrow_array::types::{ArrowPrimitiveType, Int64Type, TimestampMillisecondType, UInt16Type, UInt64Type};
+use arrow_array::{Array, PrimitiveArray, TimestampMillisecondArray, UInt16Array, UInt64Array};
+use arrow_schema::extension::ExtensionType;
+use arrow_schema::{ArrowError, DataType, Field};
+use serde::{Deserialize, Serialize};
+
+use crate::exhaustion::{era, ERA_BIT};
+use crate::id::SnowflakeId;
+use crate::layout::Layout;
+use crate::snowflake::{SnowflakeError, DEFAULT_EPOCH};
+
+/// Name of the Arrow extension type for Snowflake IDs
+pub const EXTENSION_NAME: &str = "snowflake.id";
+
+/// Arrow extension type for Snowflake IDs
+///
+/// Annotates a `UInt64` (or `Int64`, as written by systems without unsigned integers)
+/// field with the epoch and layout the IDs were generated with, so readers can decompose
+/// them. The metadata is JSON with the same keys as the Avro logical type:
+///
+/// ```json
+/// {"epoch": 1609459200000, "timestampBits": 41, "nodeBits": 10, "sequenceBits": 12}
+/// ```
+///
+/// A `checkBits` key is added for layouts with a check field. Readers that do not know
+/// the extension see a plain integer column.
+///
+/// # Example
+/// ```
+/// use snowflake_rs_impl::arrow::SnowflakeIdType;
+/// use snowflake_rs_impl::layout::Layout;
+///
+/// let id_type = SnowflakeIdType::new(None, Layout::DEFAULT);
+/// let field = id_type.field("id", false);
+/// assert_eq!(field.extension_type_name(), Some("snowflake.id"));
+/// assert_eq!(field.extension_type::<SnowflakeIdType>(), id_type);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SnowflakeIdType {
+    epoch: i64,
+    layout: Layout,
+}
+
+// Serialized form of the extension metadata
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Metadata {
+    epoch: i64,
+    timestamp_bits: u8,
+    node_bits: u8,
+    sequence_bits: u8,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    check_bits: u8,
+}
+
+impl SnowflakeIdType {
+    /// Creates the extension type for IDs generated with `epoch` and `layout`
+    ///
+    /// # Arguments
+    ///
+    /// * `epoch` - The epoch in milliseconds the IDs were generated with. If None, DEFAULT_EPOCH is used.
+    /// * `layout` - The bit layout the IDs were generated with
+    pub fn new(epoch: Option<i64>, layout: Layout) -> Self {
+        SnowflakeIdType {
+            epoch: epoch.unwrap_or(DEFAULT_EPOCH),
+            layout,
+        }
+    }
+
+    /// Returns the epoch in milliseconds since Unix epoch
+    pub fn epoch(&self) -> i64 {
+        self.epoch
+    }
+
+    /// Returns the bit layout of the IDs
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    /// Returns a `UInt64` field named `name` annotated with this extension type
+    pub fn field(&self, name: &str, nullable: bool) -> Field {
+        Field::new(name, DataType::UInt64, nullable).with_extension_type(*self)
+    }
+}
+
+impl Default for SnowflakeIdType {
+    fn default() -> Self {
+        SnowflakeIdType::new(None, Layout::DEFAULT)
+    }
+}
+
+impl ExtensionType for SnowflakeIdType {
+    const NAME: &'static str = EXTENSION_NAME;
+
+    type Metadata = Self;
+
+    fn metadata(&self) -> &Self::Metadata {
+        self
+    }
+
+    fn serialize_metadata(&self) -> Option<String> {
+        let metadata = Metadata {
+            epoch: self.epoch,
+            timestamp_bits: self.layout.timestamp_bits(),
+            node_bits: self.layout.node_bits(),
+            sequence_bits: self.layout.step_bits(),
+            check_bits: self.layout.check_bits(),
+        };
+        Some(serde_json::to_string(&metadata).expect("extension metadata serializes"))
+    }
+
+    fn deserialize_metadata(metadata: Option<&str>) -> Result<Self::Metadata, ArrowError> {
+        let invalid =
+            || ArrowError::InvalidArgumentError(format!("{} requires valid epoch and layout metadata", EXTENSION_NAME));
+        let metadata: Metadata = serde_json::from_str(metadata.ok_or_else(invalid)?).map_err(|_| invalid())?;
+        let layout = Layout::new(metadata.node_bits, metadata.sequence_bits)
+            .and_then(|layout| layout.with_check_bits(metadata.check_bits))
+            .map_err(|_| invalid())?;
+        if layout.timestamp_bits() != metadata.timestamp_bits {
+            return Err(invalid());
+        }
+        Ok(SnowflakeIdType::new(Some(metadata.epoch), layout))
+    }
+
+    fn supports_data_type(&self, data_type: &DataType) -> Result<(), ArrowError> {
+        match data_type {
+            DataType::UInt64 | DataType::Int64 => Ok(()),
+            other => Err(ArrowError::InvalidArgumentError(format!(
+                "{} requires a UInt64 or Int64 field, found {}",
+                EXTENSION_NAME, other
+            ))),
+        }
+    }
+
+    fn try_new(data_type: &DataType, metadata: Self::Metadata) -> Result<Self, ArrowError> {
+        metadata.supports_data_type(data_type)?;
+        Ok(metadata)
+    }
+}
+
+/// Builder for a `UInt64Array` of Snowflake IDs
+///
+/// # Example
+/// ```
+/// use snowflake_rs_impl::arrow::SnowflakeIdBuilder;
+/// use snowflake_rs_impl::snowflake::Snowflake;
+///
+/// let snowflake = Snowflake::new(1, None).unwrap();
+/// let mut builder = SnowflakeIdBuilder::with_capacity(100);
+/// builder.append_slice(&snowflake.generate_batch(100).unwrap());
+/// builder.append_null();
+/// let ids = builder.finish();
+/// assert_eq!(ids.len(), 101);
+/// ```
+#[derive(Debug, Default)]
+pub struct SnowflakeIdBuilder {
+    inner: UInt64Builder,
+}
+
+impl SnowflakeIdBuilder {
+    /// Creates an empty builder
+    pub fn new() -> Self {
+        SnowflakeIdBuilder::default()
+    }
+
+    /// Creates an empty builder with room for `capacity` IDs
+    pub fn with_capacity(capacity: usize) -> Self {
+        SnowflakeIdBuilder {
+            inner: UInt64Builder::with_capacity(capacity),
+        }
+    }
+
+    /// Appends an ID
+    #[inline]
+    pub fn append(&mut self, id: SnowflakeId) {
+        self.inner.append_value(id.as_u64());
+    }
+
+    /// Appends an ID, or a null if `id` is None
+    #[inline]
+    pub fn append_option(&mut self, id: Option<SnowflakeId>) {
+        self.inner.append_option(id.map(|id| id.as_u64()));
+    }
+
+    /// Appends a null
+    #[inline]
+    pub fn append_null(&mut self) {
+        self.inner.append_null();
+    }
+
+    /// Appends every ID in `ids`
+    pub fn append_slice(&mut self, ids: &[SnowflakeId]) {
+        self.inner.extend(ids.iter().map(|id| Some(id.as_u64())));
+    }
+
+    /// Returns the number of IDs (and nulls) appended so far
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns true if nothing has been appended
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Returns the array of appended IDs and resets the builder
+    pub fn finish(&mut self) -> UInt64Array {
+        self.inner.finish()
+    }
+}
+
+impl Extend<SnowflakeId> for SnowflakeIdBuilder {
+    fn extend<I: IntoIterator<Item = SnowflakeId>>(&mut self, ids: I) {
+        for id in ids {
+            self.append(id);
+        }
+    }
+}
+
+impl Extend<Option<SnowflakeId>> for SnowflakeIdBuilder {
+    fn extend<I: IntoIterator<Item = Option<SnowflakeId>>>(&mut self, ids: I) {
+        for id in ids {
+            self.append_option(id);
+        }
+    }
+}
+
+/// Returns the time each ID was generated, as a UTC millisecond timestamp column
+///
+/// Nulls stay null. Second-era IDs (with `ERA_BIT` set) map to times after the end of the
+/// first era.
+///
+/// # Errors
+///
+/// Returns SnowflakeError::InvalidArrowArray if `ids` is not a `UInt64` or `Int64`
+/// array, or an `Int64` array holds negative values
+pub fn timestamp_millis(ids: &dyn Array, id_type: &SnowflakeIdType) -> Result<TimestampMillisecondArray, SnowflakeError> {
+    let (epoch, layout) = (id_type.epoch, id_type.layout);
+    let timestamps: TimestampMillisecondArray =
+        map_ids::<TimestampMillisecondType>(ids, |id| {
+            let era_offset = era(id) as i64 * (layout.max_timestamp() as i64 + 1);
+            epoch + era_offset + layout.decompose(id & !ERA_BIT).0 as i64
+        })?;
+    Ok(timestamps.with_timezone_utc())
+}
+
+/// Returns the node ID of each ID
+///
+/// # Errors
+///
+/// Same as `timestamp_millis`
+pub fn node_ids(ids: &dyn Array, id_type: &SnowflakeIdType) -> Result<UInt16Array, SnowflakeError> {
+    let layout = id_type.layout;
+    map_ids::<UInt16Type>(ids, |id| layout.decompose(id).1)
+}
+
+/// Returns the sequence number of each ID
+///
+/// # Errors
+///
+/// Same as `timestamp_millis`
+pub fn sequences(ids: &dyn Array, id_type: &SnowflakeIdType) -> Result<UInt16Array, SnowflakeError> {
+    let layout = id_type.layout;
+    map_ids::<UInt16Type>(ids, |id| layout.decompose(id).2)
+}
+
+// Leaves `checkBits` out of the metadata of layouts without a check field
+fn is_zero(bits: &u8) -> bool {
+    *bits == 0
+}
+
+// Applies `op` to every non-null ID of a UInt64 or non-negative Int64 array
+fn map_ids<O>(ids: &dyn Array, op: impl Fn(u64) -> O::Native) -> Result<PrimitiveArray<O>, SnowflakeError>
+where
+    O: ArrowPrimitiveType,
+{
+    match ids.data_type() {
+        DataType::UInt64 => Ok(ids.as_primitive::<UInt64Type>().unary(op)),
+        DataType::Int64 => {
+            let ids = ids.as_primitive::<Int64Type>();
+            if ids.iter().flatten().any(|id| id < 0) {
+                return Err(SnowflakeError::InvalidArrowArray);
+            }
+            Ok(ids.unary(|id| op(id as u64)))
+        }
+        _ => Err(SnowflakeError::InvalidArrowArray),
+    }
+}
@@ -0,0 +1,246 @@
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use log::error;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+/// Default width of the time windows recorded by an `AuditLog`
+pub const DEFAULT_AUDIT_WINDOW: Duration = Duration::from_secs(1);
+
+/// IDs issued by one node within one time window, as recorded by an `AuditLog`
+///
+/// Covers every ID of `node` with a timestamp from `first_timestamp` to `last_timestamp`
+/// and a sequence number up to `max_sequence`. The range is coarse: not every ID it
+/// covers was necessarily issued, but no ID outside it was.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IssuedRange {
+    /// Node ID carried by the IDs
+    pub node: u16,
+    /// Timestamp of the earliest ID in milliseconds since Unix epoch
+    pub first_timestamp: i64,
+    /// Timestamp of the latest ID in milliseconds since Unix epoch
+    pub last_timestamp: i64,
+    /// Highest sequence number issued in any millisecond of the window
+    pub max_sequence: u16,
+}
+
+impl IssuedRange {
+    /// Returns true if the range covers the ID with the given node ID, timestamp (in
+    /// milliseconds since Unix epoch) and sequence number
+    pub fn contains(&self, node: u16, timestamp_ms: i64, sequence: u16) -> bool {
+        node == self.node
+            && (self.first_timestamp..=self.last_timestamp).contains(&timestamp_ms)
+            && sequence <= self.max_sequence
+    }
+
+    // A range holding a single ID
+    fn single(node: u16, timestamp_ms: i64, sequence: u16) -> Self {
+        IssuedRange {
+            node,
+            first_timestamp: timestamp_ms,
+            last_timestamp: timestamp_ms,
+            max_sequence: sequence,
+        }
+    }
+}
+
+impl fmt::Display for IssuedRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "node={} from={} to={} max_sequence={}",
+            self.node, self.first_timestamp, self.last_timestamp, self.max_sequence
+        )
+    }
+}
+
+/// Append-only record of the ID ranges issued by one or more generators
+///
+/// Attach a log to generators with `SnowflakeBuilder::audit_log`. The log keeps one open
+/// `IssuedRange` per node and widens it as IDs are issued; once a node issues an ID in a
+/// later time window, the finished range is appended to the sink as one line:
+///
+/// ```text
+/// node=1 from=1700000000000 to=1700000000999 max_sequence=4095
+/// ```
+///
+/// Windows are aligned to multiples of the window width since Unix epoch. After a data
+/// loss incident, `read_ranges` returns every range a node ever minted from, e.g. to
+/// tell whether an ID found in the wild was really issued. Ranges still open are written
+/// by `flush` and when the log is dropped, so a process that dies without either loses
+/// at most one window per node.
+///
+/// Sink write errors are logged and do not fail ID generation.
+///
+/// # Example
+/// ```
+/// use std::sync::Arc;
+/// use snowflake_rs_impl::audit::AuditLog;
+/// use snowflake_rs_impl::snowflake::Snowflake;
+///
+/// # let path = std::env::temp_dir().join(format!("snowflake-doc-{}.audit", std::process::id()));
+/// let audit_log = Arc::new(AuditLog::open(&path).unwrap());
+/// let snowflake = Snowflake::builder(1).audit_log(audit_log.clone()).build().unwrap();
+/// snowflake.generate().unwrap();
+/// audit_log.flush().unwrap();
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+pub struct AuditLog {
+    window_ms: i64,
+    state: Mutex<AuditState>,
+}
+
+// The sink and the open range of every node seen so far
+struct AuditState {
+    sink: Box<dyn Write + Send>,
+    open: Vec<IssuedRange>,
+}
+
+impl AuditLog {
+    /// Creates a log appending to the file at `path`, creating it if needed
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be opened for appending
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(AuditLog::from_writer(file))
+    }
+
+    /// Creates a log appending to `writer`
+    ///
+    /// The writer is flushed after every record.
+    pub fn from_writer(writer: impl Write + Send + 'static) -> Self {
+        AuditLog {
+            window_ms: DEFAULT_AUDIT_WINDOW.as_millis() as i64,
+            state: Mutex::new(AuditState {
+                sink: Box::new(writer),
+                open: Vec::new(),
+            }),
+        }
+    }
+
+    /// Sets the width of the time windows, rounded down to whole milliseconds (at least
+    /// one). Defaults to `DEFAULT_AUDIT_WINDOW` (one second).
+    ///
+    /// Wider windows mean fewer records but coarser ranges.
+    pub fn window(mut self, window: Duration) -> Self {
+        self.window_ms = i64::try_from(window.as_millis()).unwrap_or(i64::MAX).max(1);
+        self
+    }
+
+    /// Returns the width of the time windows
+    pub fn window_width(&self) -> Duration {
+        Duration::from_millis(self.window_ms as u64)
+    }
+
+    /// Records an issued ID by its node ID, timestamp (in milliseconds since Unix epoch)
+    /// and sequence number
+    ///
+    /// Generators with this log attached call this for every ID they issue; call it
+    /// directly for IDs issued elsewhere.
+    pub fn record(&self, node: u16, timestamp_ms: i64, sequence: u16) {
+        let window = timestamp_ms.div_euclid(self.window_ms);
+        let mut state = self.state.lock();
+        let finished = match state.open.iter_mut().find(|range| range.node == node) {
+            Some(range) if range.first_timestamp.div_euclid(self.window_ms) == window => {
+                range.first_timestamp = range.first_timestamp.min(timestamp_ms);
+                range.last_timestamp = range.last_timestamp.max(timestamp_ms);
+                range.max_sequence = range.max_sequence.max(sequence);
+                None
+            }
+            Some(range) => Some(std::mem::replace(range, IssuedRange::single(node, timestamp_ms, sequence))),
+            None => {
+                state.open.push(IssuedRange::single(node, timestamp_ms, sequence));
+                None
+            }
+        };
+        if let Some(finished) = finished {
+            if let Err(err) = state.append(&finished) {
+                error!("Failed to write audit record {}: {}", finished, err);
+            }
+        }
+    }
+
+    /// Writes every open range to the sink and flushes it
+    ///
+    /// IDs issued afterwards in the same window start a new range, so the log may then
+    /// hold two overlapping ranges for a window.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the sink cannot be written; the ranges not yet written stay open
+    pub fn flush(&self) -> io::Result<()> {
+        let mut state = self.state.lock();
+        while let Some(range) = state.open.first().copied() {
+            state.append(&range)?;
+            state.open.remove(0);
+        }
+        state.sink.flush()
+    }
+}
+
+impl AuditState {
+    // Appends one record line and flushes the sink
+    fn append(&mut self, range: &IssuedRange) -> io::Result<()> {
+        writeln!(self.sink, "{}", range)?;
+        self.sink.flush()
+    }
+}
+
+impl fmt::Debug for AuditLog {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AuditLog")
+            .field("window_ms", &self.window_ms)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for AuditLog {
+    fn drop(&mut self) {
+        if let Err(err) = self.flush() {
+            error!("Failed to flush audit log on drop: {}", err);
+        }
+    }
+}
+
+/// Reads the ranges recorded by an `AuditLog`, in the order they were written
+///
+/// Blank lines are skipped.
+///
+/// # Errors
+///
+/// Returns an error if the reader fails or a line is not a valid record
+pub fn read_ranges(reader: impl BufRead) -> io::Result<Vec<IssuedRange>> {
+    let mut ranges = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        ranges.push(parse_range(&line).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("invalid audit record `{}`", line))
+        })?);
+    }
+    Ok(ranges)
+}
+
+// Parses one `node=.. from=.. to=.. max_sequence=..` record line
+fn parse_range(line: &str) -> Option<IssuedRange> {
+    let mut fields = line.split_whitespace().map(|field| field.split_once('='));
+    let mut field = |key: &str| match fields.next()? {
+        Some((name, value)) if name == key => Some(value),
+        _ => None,
+    };
+    let range = IssuedRange {
+        node: field("node")?.parse().ok()?,
+        first_timestamp: field("from")?.parse().ok()?,
+        last_timestamp: field("to")?.parse().ok()?,
+        max_sequence: field("max_sequence")?.parse().ok()?,
+    };
+    (fields.next().is_none() && range.first_timestamp <= range.last_timestamp).then_some(range)
+}
@@ -0,0 +1,106 @@
+use apache_avro::types::Value;
+use apache_avro::Schema;
+
+use crate::id::SnowflakeId;
+use crate::layout::Layout;
+use crate::snowflake::{SnowflakeError, DEFAULT_EPOCH};
+
+/// Name of the Avro logical type for Snowflake IDs
+///
+/// The logical type annotates a `long`. Readers that do not know it fall back to the
+/// plain `long`, as required by the Avro specification, so the IDs stay readable
+/// everywhere.
+pub const LOGICAL_TYPE: &str = "snowflake-id";
+
+/// Returns the Avro schema JSON for a Snowflake ID
+///
+/// The schema is a `long` with the `snowflake-id` logical type and metadata describing
+/// how to decode the ID:
+///
+/// ```json
+/// {
+///   "type": "long",
+///   "logicalType": "snowflake-id",
+///   "epoch": 1609459200000,
+///   "timestampBits": 41,
+///   "nodeBits": 10,
+///   "sequenceBits": 12
+/// }
+/// ```
+///
+/// Layouts with a check field add a `checkBits` key.
+///
+/// The snippet can be used as-is for a record field type, e.g.
+/// `{"name": "id", "type": <schema>}`.
+///
+/// # Arguments
+///
+/// * `epoch` - The epoch in milliseconds the IDs were generated with. If None, DEFAULT_EPOCH is used.
+/// * `layout` - The bit layout the IDs were generated with
+pub fn schema_json(epoch: Option<i64>, layout: &Layout) -> String {
+    let check_bits = match layout.check_bits() {
+        0 => String::new(),
+        bits => format!(r#", "checkBits": {}"#, bits),
+    };
+    format!(
+        r#"{{"type": "long", "logicalType": "{}", "epoch": {}, "timestampBits": {}, "nodeBits": {}, "sequenceBits": {}{}}}"#,
+        LOGICAL_TYPE,
+        epoch.unwrap_or(DEFAULT_EPOCH),
+        layout.timestamp_bits(),
+        layout.node_bits(),
+        layout.step_bits(),
+        check_bits,
+    )
+}
+
+/// Returns the parsed Avro schema for a Snowflake ID
+///
+/// Since `snowflake-id` is not a built-in Avro logical type, the parsed schema is
+/// `Schema::Long`; use `schema_json` to embed the full annotated schema in a larger one.
+pub fn schema(epoch: Option<i64>, layout: &Layout) -> Schema {
+    Schema::parse_str(&schema_json(epoch, layout)).expect("snowflake-id schema is valid Avro")
+}
+
+/// Converts a Snowflake ID to an Avro `long` value
+///
+/// # Errors
+///
+/// Returns SnowflakeError::InvalidAvroValue if the ID has the sign bit set and does not
+/// fit in an Avro `long`
+pub fn to_value(id: SnowflakeId) -> Result<Value, SnowflakeError> {
+    i64::try_from(id.as_u64())
+        .map(Value::Long)
+        .map_err(|_| SnowflakeError::InvalidAvroValue)
+}
+
+/// Converts an Avro value back to a Snowflake ID
+///
+/// Accepts a non-negative `long`, optionally wrapped in a union (for nullable fields
+/// the null branch is rejected).
+///
+/// # Errors
+///
+/// Returns SnowflakeError::InvalidAvroValue if the value is not a non-negative `long`
+pub fn from_value(value: &Value) -> Result<SnowflakeId, SnowflakeError> {
+    match value {
+        Value::Long(id) if *id >= 0 => Ok(SnowflakeId::from(*id as u64)),
+        Value::Union(_, inner) => from_value(inner),
+        _ => Err(SnowflakeError::InvalidAvroValue),
+    }
+}
+
+impl TryFrom<SnowflakeId> for Value {
+    type Error = SnowflakeError;
+
+    fn try_from(id: SnowflakeId) -> Result<Self, Self::Error> {
+        to_value(id)
+    }
+}
+
+impl TryFrom<Value> for SnowflakeId {
+    type Error = SnowflakeError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        from_value(&value)
+    }
+}
@@ -0,0 +1,36 @@
+use std::collections::HashMap;
+
+use parking_lot::Mutex;
+
+// Sequence space for backfilled IDs: the next free sequence number of every millisecond
+// backfilled so far. Backfill timestamps arrive in any order, so a single "last
+// timestamp" like the live generator's state is not enough.
+#[derive(Debug)]
+pub(crate) struct Backfill {
+    node: u16,
+    next_sequences: Mutex<HashMap<i64, u32>>,
+}
+
+impl Backfill {
+    pub(crate) fn new(node: u16) -> Self {
+        Backfill {
+            node,
+            next_sequences: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) fn node(&self) -> u16 {
+        self.node
+    }
+
+    // Claims the next sequence number of `timestamp`, or None once all of them are used
+    pub(crate) fn next_sequence(&self, timestamp: i64, max_sequence: u16) -> Option<u16> {
+        let mut next_sequences = self.next_sequences.lock();
+        let next = next_sequences.entry(timestamp).or_insert(0);
+        if *next > max_sequence as u32 {
+            return None;
+        }
+        *next += 1;
+        Some((*next - 1) as u16)
+    }
+}
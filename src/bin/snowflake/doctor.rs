@@ -0,0 +1,285 @@
+use std::env;
+use std::fs;
+use std::net::{IpAddr, UdpSocket};
+use std::path::Path;
+use std::process::Command;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use snowflake_rs_impl::layout::Layout;
+use snowflake_rs_impl::snowflake::Snowflake;
+
+/// Environment variable checked first for an explicitly assigned node ID
+const NODE_ID_VAR: &str = "SNOWFLAKE_NODE_ID";
+
+// Whether the host reports its clock as NTP-synchronized
+enum NtpStatus {
+    Synchronized(&'static str),
+    NotSynchronized(&'static str),
+    Unknown,
+}
+
+// A node ID derived from some property of the host
+struct NodeCandidate {
+    source: &'static str,
+    detail: String,
+    node: u16,
+    // False for sources that can collide between hosts, like a hostname hash
+    unique: bool,
+}
+
+/// Runs the `doctor` command
+pub(crate) fn run(args: &[String]) -> Result<(), String> {
+    let duration = Duration::from_millis(super::option(args, "--duration-ms", 1000)?);
+    let layout = Layout::DEFAULT;
+    let mut warnings = Vec::new();
+
+    println!("Clock");
+    let resolution = clock_resolution();
+    println!("  resolution:        {:?}", resolution);
+    if resolution > Duration::from_millis(1) {
+        warnings.push(format!("clock resolution is {:?}; IDs within one tick share a timestamp", resolution));
+    }
+    match ntp_status() {
+        NtpStatus::Synchronized(source) => println!("  NTP synchronized:  yes ({})", source),
+        NtpStatus::NotSynchronized(source) => {
+            println!("  NTP synchronized:  no ({})", source);
+            warnings.push("clock is not NTP-synchronized; expect clock steps and ClockMovedBackwards errors".to_string());
+        }
+        NtpStatus::Unknown => println!("  NTP synchronized:  unknown (no timedatectl, systemd-timesyncd or chronyc)"),
+    }
+
+    let threads = thread::available_parallelism().map(usize::from).unwrap_or(1);
+    println!("Throughput ({} ms per run)", duration.as_millis());
+    let single = measure_throughput(1, duration)?;
+    println!("  1 thread:          {:.0} IDs/sec", single);
+    let multi = if threads > 1 {
+        let multi = measure_throughput(threads, duration)?;
+        println!("  {:<18} {:.0} IDs/sec", format!("{} threads:", threads), multi);
+        multi
+    } else {
+        single
+    };
+
+    println!("Node ID candidates (default layout, 0-{})", layout.max_node());
+    let candidates = node_candidates(&layout);
+    if candidates.is_empty() {
+        println!("  none found");
+    }
+    for candidate in &candidates {
+        println!("  {:<18} {:<5} {}", format!("{}:", candidate.source), candidate.node, candidate.detail);
+    }
+
+    let peak_per_milli = (single.max(multi) / 1000.0).ceil() as u64;
+    let recommended_layout = recommend_layout(peak_per_milli);
+    if recommended_layout != layout {
+        warnings.push(format!(
+            "this host can issue about {} IDs/ms, more than the default layout's {} sequence numbers",
+            peak_per_milli,
+            layout.max_sequence() as u32 + 1
+        ));
+    }
+    let node = match candidates.first() {
+        Some(candidate) => {
+            if !candidate.unique {
+                warnings.push(format!("node ID from {} may collide with other hosts; assign one explicitly", candidate.source));
+            }
+            candidate.node & recommended_layout.max_node()
+        }
+        None => {
+            warnings.push(format!("no node ID could be detected; set {}", NODE_ID_VAR));
+            0
+        }
+    };
+
+    println!("Recommended configuration");
+    println!("  Snowflake::builder({})", node);
+    if recommended_layout != layout {
+        println!(
+            "      .layout(Layout::new({}, {}).unwrap())",
+            recommended_layout.node_bits(),
+            recommended_layout.step_bits()
+        );
+    }
+    println!("      .build()");
+
+    if !warnings.is_empty() {
+        println!("Warnings");
+        for warning in &warnings {
+            println!("  - {}", warning);
+        }
+    }
+    Ok(())
+}
+
+// Measures the smallest observable step of the system clock
+fn clock_resolution() -> Duration {
+    let deadline = Instant::now() + Duration::from_millis(100);
+    let mut smallest = Duration::MAX;
+    for _ in 0..100 {
+        let start = SystemTime::now();
+        let mut now = SystemTime::now();
+        while now == start && Instant::now() < deadline {
+            now = SystemTime::now();
+        }
+        if let Ok(step) = now.duration_since(start) {
+            if step > Duration::ZERO {
+                smallest = smallest.min(step);
+            }
+        }
+        if Instant::now() >= deadline {
+            break;
+        }
+    }
+    smallest
+}
+
+// Asks the usual time daemons whether the clock is synchronized
+fn ntp_status() -> NtpStatus {
+    if let Some(output) = command_output("timedatectl", &["show", "-p", "NTPSynchronized", "--value"]) {
+        match output.trim() {
+            "yes" => return NtpStatus::Synchronized("timedatectl"),
+            "no" => return NtpStatus::NotSynchronized("timedatectl"),
+            _ => {}
+        }
+    }
+    if Path::new("/run/systemd/timesync/synchronized").exists() {
+        return NtpStatus::Synchronized("systemd-timesyncd");
+    }
+    if let Some(output) = command_output("chronyc", &["tracking"]) {
+        return if output.lines().any(|line| line.starts_with("Leap status") && line.ends_with("Normal")) {
+            NtpStatus::Synchronized("chronyc")
+        } else {
+            NtpStatus::NotSynchronized("chronyc")
+        };
+    }
+    NtpStatus::Unknown
+}
+
+// Runs a command and returns its stdout, or None if it cannot be run or fails
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout).ok()
+}
+
+// Generates IDs on `threads` threads sharing one generator for `duration`. The generator
+// uses the widest sequence field, so the result is bounded by the host rather than by the
+// default layout's sequence numbers per millisecond.
+fn measure_throughput(threads: usize, duration: Duration) -> Result<f64, String> {
+    let layout = Layout::new(6, 16).map_err(|err| err.to_string())?;
+    let snowflake = Snowflake::builder(0).layout(layout).build().map_err(|err| err.to_string())?;
+    let snowflake = Arc::new(snowflake);
+    let start = Instant::now();
+    let deadline = start + duration;
+    let handles: Vec<_> = (0..threads)
+        .map(|_| {
+            let snowflake = Arc::clone(&snowflake);
+            thread::spawn(move || {
+                let mut count = 0u64;
+                while Instant::now() < deadline {
+                    for _ in 0..256 {
+                        if snowflake.generate().is_ok() {
+                            count += 1;
+                        }
+                    }
+                }
+                count
+            })
+        })
+        .collect();
+    let total: u64 = handles.into_iter().map(|handle| handle.join().unwrap_or(0)).sum();
+    Ok(total as f64 / start.elapsed().as_secs_f64())
+}
+
+// Collects node IDs derived from the environment, most trustworthy first
+fn node_candidates(layout: &Layout) -> Vec<NodeCandidate> {
+    let max_node = layout.max_node();
+    let mut candidates = Vec::new();
+
+    if let Some(node) = env::var(NODE_ID_VAR).ok().and_then(|value| value.trim().parse::<u16>().ok()) {
+        if node <= max_node {
+            candidates.push(NodeCandidate { source: NODE_ID_VAR, detail: String::new(), node, unique: true });
+        }
+    }
+
+    // StatefulSet pods are named `<set>-<ordinal>`, and the ordinal is unique within the set
+    if env::var_os("KUBERNETES_SERVICE_HOST").is_some() {
+        if let Ok(pod) = env::var("POD_NAME").or_else(|_| env::var("HOSTNAME")) {
+            if let Some(ordinal) = pod.rsplit_once('-').and_then(|(_, ordinal)| ordinal.parse::<u16>().ok()) {
+                if ordinal <= max_node {
+                    candidates.push(NodeCandidate { source: "k8s ordinal", detail: pod, node: ordinal, unique: true });
+                }
+            }
+        }
+    }
+
+    if let Some((interface, mac)) = mac_address() {
+        let node = u16::from_be_bytes([mac[4], mac[5]]) & max_node;
+        let detail = format!("{} {}", interface, mac.iter().map(|byte| format!("{:02x}", byte)).collect::<Vec<_>>().join(":"));
+        candidates.push(NodeCandidate { source: "MAC address", detail, node, unique: false });
+    }
+
+    if let Some(IpAddr::V4(ip)) = local_ip() {
+        let octets = ip.octets();
+        let node = u16::from_be_bytes([octets[2], octets[3]]) & max_node;
+        candidates.push(NodeCandidate { source: "IPv4 address", detail: ip.to_string(), node, unique: false });
+    }
+
+    if let Some(hostname) = hostname() {
+        let node = (fnv1a(hostname.as_bytes()) % (max_node as u64 + 1)) as u16;
+        candidates.push(NodeCandidate { source: "hostname hash", detail: hostname, node, unique: false });
+    }
+    candidates
+}
+
+// Returns the first non-loopback interface with a non-zero MAC address
+fn mac_address() -> Option<(String, [u8; 6])> {
+    let mut interfaces: Vec<_> = fs::read_dir("/sys/class/net").ok()?.filter_map(Result::ok).collect();
+    interfaces.sort_by_key(|entry| entry.file_name());
+    interfaces.into_iter().find_map(|entry| {
+        let interface = entry.file_name().into_string().ok()?;
+        let address = fs::read_to_string(entry.path().join("address")).ok()?;
+        let bytes: Vec<u8> = address.trim().split(':').filter_map(|byte| u8::from_str_radix(byte, 16).ok()).collect();
+        let mac: [u8; 6] = bytes.try_into().ok()?;
+        (interface != "lo" && mac != [0; 6]).then_some((interface, mac))
+    })
+}
+
+// Returns the address of the interface used for outbound traffic. Connecting a UDP
+// socket only selects a route; no packet is sent.
+fn local_ip() -> Option<IpAddr> {
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("192.0.2.1:9").ok()?;
+    let ip = socket.local_addr().ok()?.ip();
+    (!ip.is_loopback() && !ip.is_unspecified()).then_some(ip)
+}
+
+fn hostname() -> Option<String> {
+    env::var("HOSTNAME")
+        .ok()
+        .or_else(|| fs::read_to_string("/proc/sys/kernel/hostname").ok())
+        .or_else(|| fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+}
+
+// 64-bit FNV-1a, stable across Rust versions unlike `DefaultHasher`
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3))
+}
+
+// Widens the sequence field (taking bits from the node field) until one millisecond holds
+// `peak_per_milli` IDs, keeping the default 41 timestamp bits
+fn recommend_layout(peak_per_milli: u64) -> Layout {
+    let default = Layout::DEFAULT;
+    let mut step_bits = default.step_bits();
+    while step_bits < 16 && (1u64 << step_bits) < peak_per_milli {
+        step_bits += 1;
+    }
+    let node_bits = default.node_bits() + default.step_bits() - step_bits;
+    Layout::new(node_bits, step_bits).unwrap_or(default)
+}
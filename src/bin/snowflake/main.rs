@@ -0,0 +1,57 @@
+//! Command-line tools for Snowflake IDs
+//!
+//! ```text
+//! snowflake doctor [--duration-ms <ms>]
+//! snowflake parse <id> [--epoch <ms>] [--node-bits <n>] [--step-bits <n>] [--check-bits <n>]
+//!                      [--known-nodes <n,...>]
+//! ```
+
+use std::env;
+use std::process::ExitCode;
+
+mod doctor;
+mod parse;
+
+const USAGE: &str = "\
+Usage: snowflake <command> [options]
+
+Commands:
+  doctor [--duration-ms <ms>]   Check this host and print a recommended configuration
+  parse <id> [--epoch <ms>] [--node-bits <n>] [--step-bits <n>] [--check-bits <n>]
+        [--known-nodes <n,...>]
+                                Break an ID down into its fields and validate it,
+                                including its checksum if the layout has check bits
+                                and its node ID if known nodes are given
+  help                          Print this message";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("doctor") => doctor::run(&args[1..]),
+        Some("parse") => parse::run(&args[1..]),
+        Some("help") | Some("--help") | Some("-h") => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        Some(command) => Err(format!("unknown command `{}`\n\n{}", command, USAGE)),
+        None => Err(USAGE.to_string()),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("{}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// Returns the value following `flag` in `args`, parsed, or `default` if the flag is absent
+fn option<T: std::str::FromStr>(args: &[String], flag: &str, default: T) -> Result<T, String> {
+    match args.iter().position(|arg| arg == flag) {
+        None => Ok(default),
+        Some(index) => args
+            .get(index + 1)
+            .and_then(|value| value.parse().ok())
+            .ok_or_else(|| format!("`{}` expects a value", flag)),
+    }
+}
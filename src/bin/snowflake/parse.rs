@@ -0,0 +1,64 @@
+use std::sync::Arc;
+
+use snowflake_rs_impl::id::{IdValidator, SnowflakeId, BASE62_PREFIX};
+use snowflake_rs_impl::layout::Layout;
+use snowflake_rs_impl::origin::KnownNodes;
+
+/// Runs the `parse` command
+///
+/// Prints the breakdown of the ID, then fails if it does not validate against the
+/// layout (e.g. its checksum does not match) or, with `--known-nodes`, claims a node ID
+/// outside the given set.
+pub(crate) fn run(args: &[String]) -> Result<(), String> {
+    let id = args
+        .first()
+        .filter(|arg| !arg.starts_with("--"))
+        .ok_or_else(|| "`parse` expects an ID".to_string())?;
+    let id = parse_id(id).ok_or_else(|| format!("`{}` is not a Snowflake ID", id))?;
+    let epoch = if args.iter().any(|arg| arg == "--epoch") {
+        Some(super::option(args, "--epoch", 0)?)
+    } else {
+        None
+    };
+    let node_bits = super::option(args, "--node-bits", Layout::DEFAULT.node_bits())?;
+    let step_bits = super::option(args, "--step-bits", Layout::DEFAULT.step_bits())?;
+    let check_bits = super::option(args, "--check-bits", 0)?;
+    let layout = Layout::new(node_bits, step_bits)
+        .and_then(|layout| layout.with_check_bits(check_bits))
+        .map_err(|err| err.to_string())?;
+
+    let known_nodes = match args.iter().position(|arg| arg == "--known-nodes") {
+        Some(index) => Some(known_nodes(args.get(index + 1).map(String::as_str), layout)?),
+        None => None,
+    };
+
+    let explanation = id.explain_with_layout(epoch, &layout);
+    println!("{}", explanation);
+    let mut validator = IdValidator::new().epoch(explanation.epoch).layout(layout).allow_era(true);
+    if let Some(known_nodes) = known_nodes {
+        validator = validator.known_nodes(Arc::new(known_nodes));
+    }
+    validator
+        .validate(id.as_u64())
+        .map(|_| ())
+        .map_err(|err| err.to_string())
+}
+
+// Parses the comma-separated node IDs of `--known-nodes`
+fn known_nodes(value: Option<&str>, layout: Layout) -> Result<KnownNodes, String> {
+    let value = value.ok_or_else(|| "`--known-nodes` expects a value".to_string())?;
+    let mut known_nodes = KnownNodes::new(layout);
+    for node in value.split(',') {
+        let node = node.trim().parse().map_err(|_| format!("`{}` is not a node ID", node))?;
+        known_nodes.insert(node).map_err(|err| err.to_string())?;
+    }
+    Ok(known_nodes)
+}
+
+// Parses a decimal or `b62_`-prefixed base62 ID
+fn parse_id(id: &str) -> Option<SnowflakeId> {
+    match id.strip_prefix(BASE62_PREFIX) {
+        Some(encoded) => SnowflakeId::from_base62(encoded).ok(),
+        None => id.parse::<u64>().ok().map(SnowflakeId::from),
+    }
+}
@@ -0,0 +1,219 @@
+use crate::id::SnowflakeId;
+use crate::layout::Layout;
+use crate::snowflake::{Snowflake, SnowflakeBuilder, SnowflakeError};
+
+/// Epoch used by github.com/bwmarrin/snowflake (the Twitter epoch, 2010-11-04T01:42:54.657Z)
+pub const EPOCH: i64 = 1288834974657;
+
+/// Bit layout used by github.com/bwmarrin/snowflake with its default settings
+pub const LAYOUT: Layout = Layout::DEFAULT;
+
+/// Alphabet of `ID.Base32()` in the Go library (z-base-32 ordering)
+const BASE32_ALPHABET: &[u8; 32] = b"ybndrfg8ejkmcpqxot1uwisza345h769";
+
+/// Alphabet of `ID.Base58()` in the Go library (Flickr ordering)
+const BASE58_ALPHABET: &[u8; 58] = b"123456789abcdefghijkmnopqrstuvwxyzABCDEFGHJKLMNPQRSTUVWXYZ";
+
+/// Alphabet of `ID.Base36()` in the Go library (`strconv.FormatInt(id, 36)`)
+const BASE36_ALPHABET: &[u8; 36] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+/// Alphabet of `ID.Base2()` in the Go library
+const BASE2_ALPHABET: &[u8; 2] = b"01";
+
+/// Standard Base64 alphabet, used by `ID.Base64()` in the Go library
+const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Returns a builder configured like a `snowflake.Node` of github.com/bwmarrin/snowflake
+///
+/// With the same node ID, the generator produces IDs with the same bit layout and epoch as
+/// the Go library, so IDs from Rust and Go services can be mixed, ordered and parsed by
+/// either side.
+///
+/// # Example
+/// ```
+/// use snowflake_rs_impl::bwmarrin;
+///
+/// let snowflake = bwmarrin::builder(1).build().unwrap();
+/// let id = snowflake.generate_id().unwrap();
+/// assert_eq!(bwmarrin::parse_base58(&bwmarrin::to_base58(id)).unwrap(), id);
+/// assert_eq!(bwmarrin::node(id), 1);
+/// ```
+pub fn builder(node: u16) -> SnowflakeBuilder {
+    Snowflake::builder(node).epoch(EPOCH).layout(LAYOUT)
+}
+
+/// Returns the time an ID was generated in milliseconds since Unix epoch, like `ID.Time()`
+pub fn time_millis(id: SnowflakeId) -> i64 {
+    id.timestamp() as i64 + EPOCH
+}
+
+/// Returns the node ID of an ID, like `ID.Node()`
+pub fn node(id: SnowflakeId) -> u16 {
+    id.node()
+}
+
+/// Returns the sequence number of an ID, like `ID.Step()`
+pub fn step(id: SnowflakeId) -> u16 {
+    id.sequence()
+}
+
+/// Encodes an ID like `ID.Base2()`
+pub fn to_base2(id: SnowflakeId) -> String {
+    encode(id.as_u64(), BASE2_ALPHABET)
+}
+
+/// Encodes an ID like `ID.Base32()`
+pub fn to_base32(id: SnowflakeId) -> String {
+    encode(id.as_u64(), BASE32_ALPHABET)
+}
+
+/// Encodes an ID like `ID.Base36()`
+pub fn to_base36(id: SnowflakeId) -> String {
+    encode(id.as_u64(), BASE36_ALPHABET)
+}
+
+/// Encodes an ID like `ID.Base58()`
+pub fn to_base58(id: SnowflakeId) -> String {
+    encode(id.as_u64(), BASE58_ALPHABET)
+}
+
+/// Encodes an ID like `ID.Base64()`: standard Base64 of the decimal string
+pub fn to_base64(id: SnowflakeId) -> String {
+    let decimal = id.as_u64().to_string();
+    let mut encoded = String::with_capacity(decimal.len().div_ceil(3) * 4);
+    for chunk in decimal.as_bytes().chunks(3) {
+        let bytes = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let group = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+        for index in 0..4 {
+            if index <= chunk.len() {
+                encoded.push(BASE64_ALPHABET[(group >> (18 - 6 * index) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Encodes an ID like `ID.IntBytes()`: 8 bytes, big-endian
+pub fn to_int_bytes(id: SnowflakeId) -> [u8; 8] {
+    id.as_u64().to_be_bytes()
+}
+
+/// Parses a decimal ID, like `ParseString`
+///
+/// # Errors
+///
+/// Returns SnowflakeError::InvalidEncoding if the string is not a non-negative `int64`
+pub fn parse_string(id: &str) -> Result<SnowflakeId, SnowflakeError> {
+    id.parse::<i64>()
+        .ok()
+        .filter(|&id| id >= 0)
+        .map(|id| SnowflakeId::from(id as u64))
+        .ok_or(SnowflakeError::InvalidEncoding)
+}
+
+/// Parses an ID encoded with `to_base2`, like `ParseBase2`
+///
+/// # Errors
+///
+/// Returns SnowflakeError::InvalidEncoding if the string contains other characters or
+/// does not fit in a non-negative `int64`
+pub fn parse_base2(id: &str) -> Result<SnowflakeId, SnowflakeError> {
+    decode(id, BASE2_ALPHABET)
+}
+
+/// Parses an ID encoded with `to_base32`, like `ParseBase32`
+///
+/// # Errors
+///
+/// Same as `parse_base2`
+pub fn parse_base32(id: &str) -> Result<SnowflakeId, SnowflakeError> {
+    decode(id, BASE32_ALPHABET)
+}
+
+/// Parses an ID encoded with `to_base36`, like `ParseBase36`
+///
+/// # Errors
+///
+/// Same as `parse_base2`
+pub fn parse_base36(id: &str) -> Result<SnowflakeId, SnowflakeError> {
+    decode(id, BASE36_ALPHABET)
+}
+
+/// Parses an ID encoded with `to_base58`, like `ParseBase58`
+///
+/// # Errors
+///
+/// Same as `parse_base2`
+pub fn parse_base58(id: &str) -> Result<SnowflakeId, SnowflakeError> {
+    decode(id, BASE58_ALPHABET)
+}
+
+/// Parses an ID encoded with `to_base64`, like `ParseBase64`
+///
+/// # Errors
+///
+/// Returns SnowflakeError::InvalidEncoding if the string is not valid Base64 of a decimal
+/// ID
+pub fn parse_base64(id: &str) -> Result<SnowflakeId, SnowflakeError> {
+    let bytes = id.as_bytes();
+    if bytes.is_empty() || !bytes.len().is_multiple_of(4) {
+        return Err(SnowflakeError::InvalidEncoding);
+    }
+    let mut decimal = Vec::with_capacity(bytes.len() / 4 * 3);
+    for (position, chunk) in bytes.chunks(4).enumerate() {
+        let last = position == bytes.len() / 4 - 1;
+        let padding = chunk.iter().rev().take_while(|&&byte| byte == b'=').count();
+        if padding > 2 || (padding > 0 && !last) {
+            return Err(SnowflakeError::InvalidEncoding);
+        }
+        let mut group = 0u32;
+        for &byte in &chunk[..4 - padding] {
+            let value = BASE64_ALPHABET.iter().position(|&symbol| symbol == byte).ok_or(SnowflakeError::InvalidEncoding)?;
+            group = group << 6 | value as u32;
+        }
+        group <<= 6 * padding as u32;
+        decimal.extend_from_slice(&[(group >> 16) as u8, (group >> 8) as u8, group as u8][..3 - padding]);
+    }
+    std::str::from_utf8(&decimal)
+        .map_err(|_| SnowflakeError::InvalidEncoding)
+        .and_then(parse_string)
+}
+
+/// Parses an ID encoded with `to_int_bytes`, like `ParseIntBytes`
+pub fn parse_int_bytes(id: [u8; 8]) -> SnowflakeId {
+    SnowflakeId::from(u64::from_be_bytes(id))
+}
+
+// Positional encoding, most significant digit first, as in the Go library
+fn encode(mut id: u64, alphabet: &[u8]) -> String {
+    let base = alphabet.len() as u64;
+    let mut digits = Vec::with_capacity(64);
+    loop {
+        digits.push(alphabet[(id % base) as usize]);
+        id /= base;
+        if id == 0 {
+            break;
+        }
+    }
+    digits.iter().rev().map(|&digit| digit as char).collect()
+}
+
+// Inverse of `encode`; unlike the Go library, rejects values that overflow an `int64`
+fn decode(id: &str, alphabet: &[u8]) -> Result<SnowflakeId, SnowflakeError> {
+    if id.is_empty() {
+        return Err(SnowflakeError::InvalidEncoding);
+    }
+    let base = alphabet.len() as u64;
+    let mut value: u64 = 0;
+    for byte in id.bytes() {
+        let digit = alphabet.iter().position(|&symbol| symbol == byte).ok_or(SnowflakeError::InvalidEncoding)?;
+        value = value
+            .checked_mul(base)
+            .and_then(|value| value.checked_add(digit as u64))
+            .filter(|&value| value <= i64::MAX as u64)
+            .ok_or(SnowflakeError::InvalidEncoding)?;
+    }
+    Ok(SnowflakeId::from(value))
+}
@@ -0,0 +1,232 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::snowflake::SnowflakeError;
+
+/// Default refresh interval of `CachedClock` (about 1 kHz)
+pub const DEFAULT_CACHE_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Stored by `CachedClock` while its source fails; never a real reading
+const CACHED_READING_UNAVAILABLE: i64 = i64::MIN;
+
+/// A source of wall-clock time for Snowflake generators
+///
+/// Generators use `SystemClock` unless another clock is passed to
+/// `SnowflakeBuilder::clock`, e.g. to simulate clock skew in tests.
+///
+/// Generators read the clock through `try_now_millis`, so a clock that cannot produce a
+/// usable reading makes generation fail with an error rather than panic. Clocks that
+/// can fail should override it; the default never fails.
+pub trait Clock: Send + Sync {
+    /// Returns the current time in milliseconds since Unix epoch
+    ///
+    /// Must not panic; a clock that cannot produce a usable reading should return a
+    /// best-effort value here and an error from `try_now_millis`.
+    fn now_millis(&self) -> i64;
+
+    /// Returns the current time in milliseconds since Unix epoch, or an error if the
+    /// clock cannot produce a usable reading
+    ///
+    /// # Errors
+    ///
+    /// Returns SnowflakeError::ClockUnavailable if the clock cannot be read or reports a
+    /// time before the Unix epoch
+    fn try_now_millis(&self) -> Result<i64, SnowflakeError> {
+        Ok(self.now_millis())
+    }
+}
+
+/// The system wall clock (`SystemTime::now()`)
+///
+/// `try_now_millis` fails with SnowflakeError::ClockUnavailable if the system time is
+/// before the Unix epoch; `now_millis` then returns a negative value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_millis(&self) -> i64 {
+        match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(elapsed) => elapsed.as_millis() as i64,
+            Err(err) => -(err.duration().as_millis() as i64),
+        }
+    }
+
+    fn try_now_millis(&self) -> Result<i64, SnowflakeError> {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as i64)
+            .map_err(|_| SnowflakeError::ClockUnavailable("system time is before the Unix epoch".to_string()))
+    }
+}
+
+/// A low-overhead wall clock for high-throughput generators
+///
+/// On Linux this reads `CLOCK_REALTIME_COARSE`, which the kernel serves from the time
+/// of the last timer tick without touching the clock hardware, so it costs a few
+/// nanoseconds instead of the tens `SystemTime::now()` can take. The price is
+/// resolution: the reading only advances once per tick (1-4 ms, depending on the
+/// kernel's `CONFIG_HZ`), so consecutive IDs share a timestamp for longer. On other
+/// platforms it falls back to `SystemTime::now()`.
+///
+/// # Example
+/// ```
+/// use std::sync::Arc;
+/// use snowflake_rs_impl::clock::CoarseClock;
+/// use snowflake_rs_impl::snowflake::Snowflake;
+///
+/// let snowflake = Snowflake::builder(1).clock(Arc::new(CoarseClock)).build().unwrap();
+/// assert!(snowflake.generate().is_ok());
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CoarseClock;
+
+impl Clock for CoarseClock {
+    fn now_millis(&self) -> i64 {
+        self.try_now_millis().unwrap_or_else(|_| SystemClock.now_millis())
+    }
+
+    #[cfg(target_os = "linux")]
+    fn try_now_millis(&self) -> Result<i64, SnowflakeError> {
+        let mut now = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+        // SAFETY: `now` is a valid, writable timespec and the clock ID is a constant
+        let result = unsafe { libc::clock_gettime(libc::CLOCK_REALTIME_COARSE, &mut now) };
+        if result != 0 {
+            return SystemClock.try_now_millis();
+        }
+        // `time_t` and `c_long` are 32 bits wide on some targets
+        #[allow(clippy::unnecessary_cast)]
+        let millis = now.tv_sec as i64 * 1000 + now.tv_nsec as i64 / 1_000_000;
+        if millis < 0 {
+            return Err(SnowflakeError::ClockUnavailable("system time is before the Unix epoch".to_string()));
+        }
+        Ok(millis)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn try_now_millis(&self) -> Result<i64, SnowflakeError> {
+        SystemClock.try_now_millis()
+    }
+}
+
+/// A clock whose reading is kept up to date by a background thread
+///
+/// A dedicated thread reads the source clock every `interval` and stores the result in an
+/// atomic, so `now_millis` is a single load with no syscall at all. Readings lag the
+/// source by up to one interval (plus scheduling delay). The thread stops when the clock
+/// is dropped.
+///
+/// Readings mirror the source exactly, including backward steps, so a generator still
+/// detects clock rollback, and fail while the source fails. When a generator exhausts a
+/// millisecond's sequence numbers it waits for the cached reading to advance, which takes
+/// up to one interval.
+///
+/// # Example
+/// ```
+/// use std::sync::Arc;
+/// use snowflake_rs_impl::clock::CachedClock;
+/// use snowflake_rs_impl::snowflake::Snowflake;
+///
+/// let snowflake = Snowflake::builder(1).clock(Arc::new(CachedClock::new())).build().unwrap();
+/// assert!(snowflake.generate().is_ok());
+/// ```
+#[derive(Debug)]
+pub struct CachedClock {
+    now_ms: Arc<AtomicI64>,
+    stop: Arc<AtomicBool>,
+    interval: Duration,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl CachedClock {
+    /// Creates a clock caching `SystemClock` every `DEFAULT_CACHE_INTERVAL`
+    pub fn new() -> Self {
+        Self::with_source(Arc::new(SystemClock), DEFAULT_CACHE_INTERVAL)
+    }
+
+    /// Creates a clock caching `source`
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The clock read by the background thread
+    /// * `interval` - How often the reading is refreshed
+    ///
+    /// # Panics
+    ///
+    /// Panics if the background thread cannot be spawned
+    pub fn with_source(source: Arc<dyn Clock>, interval: Duration) -> Self {
+        let now_ms = Arc::new(AtomicI64::new(cached_reading(source.as_ref())));
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let now_ms = Arc::clone(&now_ms);
+            let stop = Arc::clone(&stop);
+            thread::Builder::new()
+                .name("snowflake-clock".to_string())
+                .spawn(move || {
+                    while !stop.load(Ordering::Acquire) {
+                        thread::park_timeout(interval);
+                        now_ms.store(cached_reading(source.as_ref()), Ordering::Release);
+                    }
+                })
+                .expect("Failed to spawn the clock thread")
+        };
+        CachedClock {
+            now_ms,
+            stop,
+            interval,
+            thread: Some(thread),
+        }
+    }
+
+    /// Returns how often the reading is refreshed
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+}
+
+impl Default for CachedClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for CachedClock {
+    #[inline]
+    fn now_millis(&self) -> i64 {
+        match self.now_ms.load(Ordering::Acquire) {
+            CACHED_READING_UNAVAILABLE => SystemClock.now_millis(),
+            now => now,
+        }
+    }
+
+    #[inline]
+    fn try_now_millis(&self) -> Result<i64, SnowflakeError> {
+        match self.now_ms.load(Ordering::Acquire) {
+            CACHED_READING_UNAVAILABLE => Err(SnowflakeError::ClockUnavailable("cached clock source failed".to_string())),
+            now => Ok(now),
+        }
+    }
+}
+
+// Reads the source of a `CachedClock`, mapping failures to the unavailable marker
+fn cached_reading(source: &dyn Clock) -> i64 {
+    source.try_now_millis().unwrap_or(CACHED_READING_UNAVAILABLE)
+}
+
+impl Drop for CachedClock {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Release);
+        if let Some(thread) = self.thread.take() {
+            thread.thread().unpark();
+            let _ = thread.join();
+        }
+    }
+}
+
+impl fmt::Debug for dyn Clock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Clock").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,100 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::exhaustion::{ExhaustionStrategy, DEFAULT_EXHAUSTION_HORIZON};
+use crate::layout::Layout;
+use crate::rate_limit::RateLimit;
+use crate::snowflake::{Snowflake, SnowflakeBuilder, SnowflakeError};
+
+/// Serializable configuration of a Snowflake generator
+///
+/// Captures everything that determines which IDs a generator issues and how it behaves
+/// under pressure, so the configuration can be logged, diffed across environments or
+/// shipped to another process. Runtime-only settings are not included and have to be set
+/// again on the builder returned by `builder`:
+///
+/// - the clock, the state file and the exhaustion warning hook
+/// - the region registry: `node` is the full node ID, region bits included, so the
+///   rebuilt generator issues the same IDs but `Snowflake::region` returns None
+/// - the audit log and the duplicate guard
+///
+/// # Example
+/// ```
+/// use snowflake_rs_impl::snowflake::Snowflake;
+///
+/// let snowflake = Snowflake::new(1, None).unwrap();
+/// let json = serde_json::to_string(&snowflake.config()).unwrap();
+/// let restored = Snowflake::from_config(&serde_json::from_str(&json).unwrap()).unwrap();
+/// assert_eq!(restored.config(), snowflake.config());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnowflakeConfig {
+    /// The node ID
+    pub node: u16,
+    /// The epoch in milliseconds since Unix epoch
+    pub epoch: i64,
+    /// The bit layout of generated IDs
+    #[serde(default)]
+    pub layout: Layout,
+    /// The rate limit, if any
+    #[serde(default)]
+    pub rate_limit: Option<RateLimit>,
+    /// How long before the timestamp field is exhausted the generator starts warning, or
+    /// None if the warning is disabled
+    #[serde(default = "default_exhaustion_horizon")]
+    pub exhaustion_horizon: Option<Duration>,
+    /// What the generator does once the timestamp field is exhausted
+    #[serde(default)]
+    pub exhaustion_strategy: ExhaustionStrategy,
+    /// The node ID used for backfilled IDs, or None if backfill is disabled
+    #[serde(default)]
+    pub backfill_node: Option<u16>,
+}
+
+impl SnowflakeConfig {
+    /// Returns a builder preconfigured with this configuration
+    pub fn builder(&self) -> SnowflakeBuilder {
+        let mut builder = Snowflake::builder(self.node)
+            .epoch(self.epoch)
+            .layout(self.layout)
+            .exhaustion_warning(self.exhaustion_horizon)
+            .on_exhaustion(self.exhaustion_strategy);
+        if let Some(rate_limit) = self.rate_limit {
+            builder = builder.rate_limit(rate_limit);
+        }
+        if let Some(backfill_node) = self.backfill_node {
+            builder = builder.backfill_node(backfill_node);
+        }
+        builder
+    }
+}
+
+// Default for configurations written before the exhaustion warning existed
+fn default_exhaustion_horizon() -> Option<Duration> {
+    Some(DEFAULT_EXHAUSTION_HORIZON)
+}
+
+impl Snowflake {
+    /// Returns the configuration of this generator
+    pub fn config(&self) -> SnowflakeConfig {
+        SnowflakeConfig {
+            node: self.node(),
+            epoch: self.epoch(),
+            layout: self.layout(),
+            rate_limit: self.rate_limit(),
+            exhaustion_horizon: self.exhaustion_horizon(),
+            exhaustion_strategy: self.exhaustion_strategy(),
+            backfill_node: self.backfill_node(),
+        }
+    }
+
+    /// Creates a new Snowflake instance from a configuration
+    ///
+    /// # Errors
+    ///
+    /// Same as `SnowflakeBuilder::build`
+    pub fn from_config(config: &SnowflakeConfig) -> Result<Snowflake, SnowflakeError> {
+        config.builder().build()
+    }
+}
@@ -0,0 +1,182 @@
+use std::time::Instant;
+
+use crate::clock::{Clock, SystemClock};
+use crate::exhaustion::ExhaustionStrategy;
+use crate::generator::{IdError, IdGenerator};
+use crate::id::SnowflakeId;
+use crate::layout::Layout;
+use crate::snowflake::{
+    decode_timestamp_and_sequence, encode_timestamp_and_sequence, validate_epoch, SnowflakeError, DEFAULT_EPOCH,
+};
+use crate::sync::{AtomicI64, Ordering};
+
+/// Snowflake ID generator with a layout fixed at compile time
+///
+/// `NODE_BITS` and `STEP_BITS` are the widths of the node ID and sequence fields; the
+/// timestamp gets the remaining `63 - NODE_BITS - STEP_BITS` bits. Since the layout is a
+/// constant, every shift and mask is resolved at compile time, and an invalid layout is a
+/// compile error rather than a runtime one:
+///
+/// ```compile_fail
+/// use snowflake_rs_impl::const_layout::ConstSnowflake;
+///
+/// // 17 node bits do not fit
+/// let snowflake = ConstSnowflake::<17, 12>::new(1, None);
+/// ```
+///
+/// `ConstSnowflake` covers the hot path only: it always reads `SystemClock`, and has
+/// no rate limit, persistence or exhaustion warning. Once the timestamp field is
+/// exhausted it fails with SnowflakeError::TimestampExhausted. Use `Snowflake` when
+/// any of those are needed.
+///
+/// # Example
+/// ```
+/// use snowflake_rs_impl::const_layout::ConstSnowflake;
+///
+/// // 8 node bits, 14 sequence bits
+/// let snowflake = ConstSnowflake::<8, 14>::new(200, None).unwrap();
+/// let id = snowflake.generate().unwrap();
+/// let (_, node, _) = ConstSnowflake::<8, 14>::parse_id(id);
+/// assert_eq!(node, 200);
+/// ```
+pub struct ConstSnowflake<const NODE_BITS: u8, const STEP_BITS: u8> {
+    node: u16,
+    epoch_ms: i64,
+    last_timestamp_and_sequence: AtomicI64,
+}
+
+/// `ConstSnowflake` with the default 41/10/12 layout
+pub type DefaultConstSnowflake = ConstSnowflake<10, 12>;
+
+impl<const NODE_BITS: u8, const STEP_BITS: u8> ConstSnowflake<NODE_BITS, STEP_BITS> {
+    /// The layout of generated IDs; evaluating it fails compilation if the layout is invalid
+    pub const LAYOUT: Layout = Layout::new_const(NODE_BITS, STEP_BITS);
+
+    const TIMESTAMP_SHIFT: u8 = Self::LAYOUT.timestamp_shift();
+    const NODE_SHIFT: u8 = Self::LAYOUT.node_shift();
+    const MAX_TIMESTAMP: u64 = Self::LAYOUT.max_timestamp();
+    const MAX_SEQUENCE: i64 = Self::LAYOUT.max_sequence() as i64;
+
+    /// Creates a new ConstSnowflake instance
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The node ID (0 to `2^NODE_BITS - 1`)
+    /// * `epoch` - The epoch in milliseconds. If None, DEFAULT_EPOCH is used.
+    ///
+    /// # Errors
+    ///
+    /// - SnowflakeError::MachineIdOutOfRange if the node ID does not fit in the layout
+    /// - SnowflakeError::InvalidEpoch if the epoch is in the future or too far in the past
+    pub fn new(node: u16, epoch: Option<i64>) -> Result<Self, SnowflakeError> {
+        if node > Self::LAYOUT.max_node() {
+            return Err(SnowflakeError::MachineIdOutOfRange);
+        }
+        let epoch_ms = epoch.unwrap_or(DEFAULT_EPOCH);
+        validate_epoch(epoch_ms, SystemClock.try_now_millis()?, &Self::LAYOUT, ExhaustionStrategy::Error)?;
+        Ok(ConstSnowflake {
+            node,
+            epoch_ms,
+            last_timestamp_and_sequence: AtomicI64::new(0),
+        })
+    }
+
+    /// Returns the node ID of this generator
+    pub fn node(&self) -> u16 {
+        self.node
+    }
+
+    /// Returns the epoch of this generator in milliseconds since Unix epoch
+    pub fn epoch(&self) -> i64 {
+        self.epoch_ms
+    }
+
+    /// Generates a new Snowflake ID
+    ///
+    /// # Errors
+    ///
+    /// - SnowflakeError::ClockMovedBackwards if the system time moves backwards
+    /// - SnowflakeError::SequenceOverflow if unable to generate a unique ID within 5 seconds
+    /// - SnowflakeError::TimestampExhausted if the timestamp field is exhausted
+    /// - SnowflakeError::ClockUnavailable if the system time is before the Unix epoch
+    pub fn generate(&self) -> Result<u64, SnowflakeError> {
+        let mut last_timestamp_and_sequence = self.last_timestamp_and_sequence.load(Ordering::Acquire);
+
+        loop {
+            let current_timestamp = SystemClock.try_now_millis()?;
+            let (last_timestamp, last_sequence) = decode_timestamp_and_sequence(last_timestamp_and_sequence);
+            if current_timestamp < last_timestamp {
+                return Err(SnowflakeError::ClockMovedBackwards);
+            }
+            let (new_timestamp, new_sequence) = if current_timestamp == last_timestamp {
+                let new_sequence = (last_sequence + 1) & Self::MAX_SEQUENCE;
+                if new_sequence == 0 {
+                    (Self::wait_next_millis(last_timestamp)?, 0)
+                } else {
+                    (current_timestamp, new_sequence)
+                }
+            } else {
+                (current_timestamp, 0)
+            };
+            match self.last_timestamp_and_sequence.compare_exchange_weak(
+                last_timestamp_and_sequence,
+                encode_timestamp_and_sequence(new_timestamp, new_sequence),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => return self.create_id(new_timestamp, new_sequence as u16),
+                Err(actual) => {
+                    last_timestamp_and_sequence = actual;
+                }
+            }
+        }
+    }
+
+    /// Generates a new Snowflake ID wrapped in a `SnowflakeId`
+    ///
+    /// The `SnowflakeId` field accessors assume the default layout; use `parse_id` for
+    /// other layouts.
+    ///
+    /// # Errors
+    ///
+    /// Same as `generate`
+    pub fn generate_id(&self) -> Result<SnowflakeId, SnowflakeError> {
+        self.generate().map(SnowflakeId::from)
+    }
+
+    /// Splits an ID into its timestamp, node ID and sequence number using this layout
+    pub const fn parse_id(id: u64) -> (u64, u16, u16) {
+        Self::LAYOUT.decompose(id)
+    }
+
+    // Waits until the next millisecond, for at most 5 seconds
+    fn wait_next_millis(last_timestamp: i64) -> Result<i64, SnowflakeError> {
+        let start = Instant::now();
+        loop {
+            let current_timestamp = SystemClock.try_now_millis()?;
+            if current_timestamp > last_timestamp {
+                return Ok(current_timestamp);
+            }
+            if start.elapsed().as_millis() > 5000 {
+                return Err(SnowflakeError::SequenceOverflow);
+            }
+            std::thread::yield_now();
+        }
+    }
+
+    // Combines timestamp, node ID and sequence with the compile-time shifts
+    #[inline]
+    fn create_id(&self, timestamp: i64, sequence: u16) -> Result<u64, SnowflakeError> {
+        let offset = (timestamp - self.epoch_ms) as u64;
+        if offset > Self::MAX_TIMESTAMP {
+            return Err(SnowflakeError::TimestampExhausted);
+        }
+        Ok((offset << Self::TIMESTAMP_SHIFT) | ((self.node as u64) << Self::NODE_SHIFT) | sequence as u64)
+    }
+}
+
+impl<const NODE_BITS: u8, const STEP_BITS: u8> IdGenerator for ConstSnowflake<NODE_BITS, STEP_BITS> {
+    fn next_id(&self) -> Result<SnowflakeId, IdError> {
+        self.generate_id()
+    }
+}
@@ -0,0 +1,200 @@
+use std::fmt;
+
+use log::error;
+use parking_lot::Mutex;
+
+use crate::snowflake::SnowflakeError;
+
+/// What a `DuplicateGuard` does when an ID may have been issued before
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateAction {
+    /// Panic with the duplicate ID (the default)
+    Panic,
+    /// Log the duplicate ID at error level and carry on
+    Log,
+}
+
+/// Bounded record of recently issued IDs that flags re-issued ones
+///
+/// Intended for staging and debug builds, to catch misconfiguration such as two generators
+/// sharing a node ID before the duplicates reach a database. Attach a guard to generators
+/// with `SnowflakeBuilder::duplicate_guard`; every ID they issue is checked against and
+/// added to the guard. Share one guard (through an `Arc`) between all generators in a
+/// process, and feed it IDs issued elsewhere with `check`, to catch collisions between
+/// them.
+///
+/// The guard keeps two Bloom filters of `capacity` IDs each: once the current one is full
+/// it replaces the previous one, so the guard remembers between `capacity` and
+/// `2 * capacity` of the most recent IDs in constant memory. As with any Bloom filter, an
+/// ID that was never issued is flagged with probability `false_positive_rate`; choose it
+/// small enough that a report is worth investigating.
+///
+/// # Example
+/// ```
+/// use std::sync::Arc;
+/// use snowflake_rs_impl::duplicate_guard::DuplicateGuard;
+/// use snowflake_rs_impl::snowflake::Snowflake;
+///
+/// let guard = Arc::new(DuplicateGuard::new(1_000_000, 1e-9).unwrap());
+/// let snowflake = Snowflake::builder(1).duplicate_guard(guard.clone()).build().unwrap();
+/// let id = snowflake.generate().unwrap();
+/// assert!(guard.contains(id));
+/// ```
+pub struct DuplicateGuard {
+    capacity: usize,
+    false_positive_rate: f64,
+    action: DuplicateAction,
+    filters: Mutex<Generations>,
+}
+
+// The filter being filled and the one it replaced
+struct Generations {
+    current: BloomFilter,
+    previous: BloomFilter,
+}
+
+impl DuplicateGuard {
+    /// Creates a guard remembering at least `capacity` recent IDs
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - The number of IDs per filter generation
+    /// * `false_positive_rate` - The probability that a new ID is flagged anyway, in (0, 1)
+    ///
+    /// # Errors
+    ///
+    /// Returns SnowflakeError::InvalidDuplicateGuard if `capacity` is zero or
+    /// `false_positive_rate` is not in (0, 1)
+    pub fn new(capacity: usize, false_positive_rate: f64) -> Result<Self, SnowflakeError> {
+        if capacity == 0 || !(false_positive_rate > 0.0 && false_positive_rate < 1.0) {
+            return Err(SnowflakeError::InvalidDuplicateGuard);
+        }
+        Ok(DuplicateGuard {
+            capacity,
+            false_positive_rate,
+            action: DuplicateAction::Panic,
+            filters: Mutex::new(Generations {
+                current: BloomFilter::new(capacity, false_positive_rate),
+                previous: BloomFilter::new(capacity, false_positive_rate),
+            }),
+        })
+    }
+
+    /// Sets what happens when a duplicate is found
+    pub fn action(mut self, action: DuplicateAction) -> Self {
+        self.action = action;
+        self
+    }
+
+    /// Returns the number of IDs per filter generation
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the configured false positive rate
+    pub fn false_positive_rate(&self) -> f64 {
+        self.false_positive_rate
+    }
+
+    /// Returns the action taken when a duplicate is found
+    pub fn duplicate_action(&self) -> DuplicateAction {
+        self.action
+    }
+
+    /// Returns true if `id` may have been recorded by the guard
+    pub fn contains(&self, id: u64) -> bool {
+        let filters = self.filters.lock();
+        filters.current.contains(id) || filters.previous.contains(id)
+    }
+
+    /// Records `id`, taking the configured action if it may have been recorded before
+    ///
+    /// # Returns
+    ///
+    /// True if `id` may be a duplicate (only returned with `DuplicateAction::Log`)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` may be a duplicate and the action is `DuplicateAction::Panic`
+    pub fn check(&self, id: u64) -> bool {
+        let duplicate = {
+            let mut filters = self.filters.lock();
+            let duplicate = filters.current.contains(id) || filters.previous.contains(id);
+            if filters.current.len() >= self.capacity {
+                let fresh = BloomFilter::new(self.capacity, self.false_positive_rate);
+                filters.previous = std::mem::replace(&mut filters.current, fresh);
+            }
+            filters.current.insert(id);
+            duplicate
+        };
+        if duplicate {
+            match self.action {
+                DuplicateAction::Panic => panic!("Snowflake ID {} may have been issued twice; check for duplicate node IDs", id),
+                DuplicateAction::Log => error!("Snowflake ID {} may have been issued twice; check for duplicate node IDs", id),
+            }
+        }
+        duplicate
+    }
+}
+
+impl fmt::Debug for DuplicateGuard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DuplicateGuard")
+            .field("capacity", &self.capacity)
+            .field("false_positive_rate", &self.false_positive_rate)
+            .field("action", &self.action)
+            .finish_non_exhaustive()
+    }
+}
+
+// Bloom filter over u64 IDs using double hashing
+struct BloomFilter {
+    bits: Vec<u64>,
+    hashes: u32,
+    len: usize,
+}
+
+impl BloomFilter {
+    // Sizes the filter for `capacity` items at `false_positive_rate`
+    fn new(capacity: usize, false_positive_rate: f64) -> Self {
+        let ln2 = std::f64::consts::LN_2;
+        let bits = (-(capacity as f64) * false_positive_rate.ln() / (ln2 * ln2)).ceil().max(64.0) as usize;
+        let hashes = ((bits as f64 / capacity as f64) * ln2).round().clamp(1.0, 32.0) as u32;
+        BloomFilter {
+            bits: vec![0; bits.div_ceil(64)],
+            hashes,
+            len: 0,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn insert(&mut self, id: u64) {
+        for bit in self.bit_indexes(id) {
+            self.bits[bit / 64] |= 1 << (bit % 64);
+        }
+        self.len += 1;
+    }
+
+    fn contains(&self, id: u64) -> bool {
+        self.bit_indexes(id).all(|bit| self.bits[bit / 64] & (1 << (bit % 64)) != 0)
+    }
+
+    // Bit positions for `id`: h1 + i * h2 for i in 0..hashes
+    fn bit_indexes(&self, id: u64) -> impl Iterator<Item = usize> {
+        let total_bits = self.bits.len() as u64 * 64;
+        let h1 = splitmix64(id);
+        let h2 = splitmix64(h1) | 1;
+        (0..self.hashes as u64).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % total_bits) as usize)
+    }
+}
+
+// SplitMix64 finalizer; spreads the mostly sequential bits of an ID over the whole word
+fn splitmix64(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9e3779b97f4a7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^ (x >> 31)
+}
@@ -0,0 +1,95 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+/// Default warning horizon: two years before the timestamp field is exhausted
+pub const DEFAULT_EXHAUSTION_HORIZON: Duration = Duration::from_secs(2 * 365 * 24 * 60 * 60);
+
+/// Callback invoked with the remaining time when a generator enters its exhaustion horizon
+#[derive(Clone)]
+pub(crate) struct ExhaustionHook(pub(crate) Arc<dyn Fn(Duration) + Send + Sync>);
+
+impl fmt::Debug for ExhaustionHook {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("ExhaustionHook")
+    }
+}
+
+// Warns once when generation gets within the horizon of the last encodable timestamp
+pub(crate) struct ExhaustionMonitor {
+    node: u16,
+    horizon: Duration,
+    warn_after_ms: i64,
+    max_timestamp_ms: i64,
+    hook: Option<ExhaustionHook>,
+    warned: AtomicBool,
+}
+
+impl ExhaustionMonitor {
+    // Creates a monitor for a generator whose last encodable timestamp is `max_timestamp_ms`
+    pub(crate) fn new(node: u16, max_timestamp_ms: i64, horizon: Duration, hook: Option<ExhaustionHook>) -> Self {
+        let horizon_ms = i64::try_from(horizon.as_millis()).unwrap_or(i64::MAX);
+        ExhaustionMonitor {
+            node,
+            horizon,
+            warn_after_ms: max_timestamp_ms.saturating_sub(horizon_ms),
+            max_timestamp_ms,
+            hook,
+            warned: AtomicBool::new(false),
+        }
+    }
+
+    pub(crate) fn horizon(&self) -> Duration {
+        self.horizon
+    }
+
+    pub(crate) fn hook(&self) -> Option<ExhaustionHook> {
+        self.hook.clone()
+    }
+
+    // Called with the timestamp of every issued ID; cheap unless the horizon is reached
+    #[inline]
+    pub(crate) fn check(&self, timestamp_ms: i64) {
+        if timestamp_ms < self.warn_after_ms || self.warned.swap(true, Ordering::Relaxed) {
+            return;
+        }
+        let remaining = Duration::from_millis(self.max_timestamp_ms.saturating_sub(timestamp_ms).max(0) as u64);
+        warn!(
+            "Snowflake node {} will exhaust its timestamp field in {} days (at {} ms since Unix epoch)",
+            self.node,
+            remaining.as_secs() / 86_400,
+            self.max_timestamp_ms
+        );
+        if let Some(hook) = &self.hook {
+            (hook.0)(remaining);
+        }
+    }
+}
+
+/// What a generator does once the timestamp field is exhausted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum ExhaustionStrategy {
+    /// Fail with SnowflakeError::TimestampExhausted (the default)
+    #[default]
+    Error,
+    /// Continue for one more era by setting the reserved sign bit (bit 63)
+    ///
+    /// Once the timestamp field rolls over, IDs have bit 63 set and their timestamp field
+    /// counts again from `epoch + max_timestamp + 1`. IDs stay unique and ascending as
+    /// `u64`, but no longer fit in a non-negative `i64`; use `era` to tell the eras
+    /// apart. At the end of the second era the generator fails with
+    /// SnowflakeError::TimestampExhausted.
+    Era,
+}
+
+/// Bit marking IDs generated in the second era under `ExhaustionStrategy::Era`
+pub const ERA_BIT: u64 = 1 << 63;
+
+/// Returns the era of an ID: 0 for the first era, 1 if `ERA_BIT` is set
+pub const fn era(id: u64) -> u8 {
+    (id >> 63) as u8
+}
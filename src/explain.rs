@@ -0,0 +1,173 @@
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::exhaustion::{era, ERA_BIT};
+use crate::id::SnowflakeId;
+use crate::layout::Layout;
+use crate::snowflake::DEFAULT_EPOCH;
+
+/// Field-by-field breakdown of a Snowflake ID
+///
+/// Produced by `SnowflakeId::explain`. The `Display` output is a multi-line report for
+/// humans; the struct itself serializes to a flat record for tooling.
+///
+/// # Example
+/// ```
+/// use snowflake_rs_impl::id::SnowflakeId;
+///
+/// let explanation = SnowflakeId::from_u64(1 << 22 | 5 << 12 | 7).explain(None);
+/// assert_eq!(explanation.utc, "2021-01-01T00:00:00.001Z");
+/// assert_eq!((explanation.node, explanation.sequence), (5, 7));
+/// println!("{}", explanation);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct IdExplanation {
+    /// The raw ID
+    pub id: u64,
+    /// The epoch assumed, in milliseconds since Unix epoch
+    pub epoch: i64,
+    /// The bit layout assumed
+    pub layout: Layout,
+    /// The era (1 if the reserved sign bit is set, see `ExhaustionStrategy::Era`)
+    pub era: u8,
+    /// The timestamp field (milliseconds since the epoch, within the era)
+    pub timestamp: u64,
+    /// The absolute time the ID was generated, in milliseconds since Unix epoch
+    pub unix_millis: i64,
+    /// The absolute time the ID was generated, as an RFC 3339 UTC datetime
+    pub utc: String,
+    /// The node ID field
+    pub node: u16,
+    /// The sequence number field
+    pub sequence: u16,
+    /// Whether the check field matches the rest of the ID (always true if the layout has
+    /// no check field)
+    pub checksum_valid: bool,
+}
+
+impl IdExplanation {
+    /// Returns the raw bits of each field, from most to least significant: era, timestamp,
+    /// node ID and sequence number (the check field, if any, is not included)
+    pub fn bit_segments(&self) -> [String; 4] {
+        let layout = &self.layout;
+        [
+            format!("{:01b}", self.era),
+            format!("{:0width$b}", self.timestamp, width = layout.timestamp_bits() as usize),
+            if layout.node_bits() == 0 {
+                String::new()
+            } else {
+                format!("{:0width$b}", self.node, width = layout.node_bits() as usize)
+            },
+            format!("{:0width$b}", self.sequence, width = layout.step_bits() as usize),
+        ]
+    }
+}
+
+impl fmt::Display for IdExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let layout = &self.layout;
+        writeln!(f, "ID:        {}", self.id)?;
+        if layout.check_bits() == 0 {
+            writeln!(
+                f,
+                "Layout:    {}/{}/{} (timestamp/node/sequence bits)",
+                layout.timestamp_bits(),
+                layout.node_bits(),
+                layout.step_bits()
+            )?;
+        } else {
+            writeln!(
+                f,
+                "Layout:    {}/{}/{}/{} (timestamp/node/sequence/check bits)",
+                layout.timestamp_bits(),
+                layout.node_bits(),
+                layout.step_bits(),
+                layout.check_bits()
+            )?;
+        }
+        writeln!(f, "Epoch:     {} ({})", self.epoch, format_utc(self.epoch))?;
+        writeln!(f, "Era:       {}", self.era)?;
+        writeln!(f, "Timestamp: {} ms since epoch ({})", self.timestamp, self.utc)?;
+        writeln!(f, "Node:      {}", self.node)?;
+        writeln!(f, "Sequence:  {}", self.sequence)?;
+        if layout.check_bits() == 0 {
+            return write!(f, "Bits:      {}", self.bit_segments().join(" | "));
+        }
+        writeln!(f, "Checksum:  {}", if self.checksum_valid { "valid" } else { "MISMATCH" })?;
+        let check = self.id & ((1 << layout.check_bits()) - 1);
+        write!(
+            f,
+            "Bits:      {} | {:0width$b}",
+            self.bit_segments().join(" | "),
+            check,
+            width = layout.check_bits() as usize
+        )
+    }
+}
+
+impl SnowflakeId {
+    /// Breaks the ID down into its fields, assuming the default layout
+    ///
+    /// # Arguments
+    ///
+    /// * `epoch` - The epoch in milliseconds the ID was generated with. If None, DEFAULT_EPOCH is used.
+    pub fn explain(&self, epoch: Option<i64>) -> IdExplanation {
+        self.explain_with_layout(epoch, &Layout::DEFAULT)
+    }
+
+    /// Breaks the ID down into its fields, assuming the given layout
+    ///
+    /// # Arguments
+    ///
+    /// * `epoch` - The epoch in milliseconds the ID was generated with. If None, DEFAULT_EPOCH is used.
+    /// * `layout` - The bit layout the ID was generated with
+    pub fn explain_with_layout(&self, epoch: Option<i64>, layout: &Layout) -> IdExplanation {
+        let epoch = epoch.unwrap_or(DEFAULT_EPOCH);
+        let id = self.as_u64();
+        let era = era(id);
+        let (timestamp, node, sequence) = layout.decompose(id & !ERA_BIT);
+        let era_offset = era as i64 * (layout.max_timestamp() as i64 + 1);
+        let unix_millis = epoch.saturating_add(era_offset).saturating_add(timestamp as i64);
+        IdExplanation {
+            id,
+            epoch,
+            layout: *layout,
+            era,
+            timestamp,
+            unix_millis,
+            utc: format_utc(unix_millis),
+            node,
+            sequence,
+            checksum_valid: layout.has_valid_checksum(id),
+        }
+    }
+}
+
+// Formats milliseconds since Unix epoch as an RFC 3339 UTC datetime with millisecond
+// precision, using the civil-from-days algorithm from Howard Hinnant's date library
+fn format_utc(unix_millis: i64) -> String {
+    let days = unix_millis.div_euclid(86_400_000);
+    let ms_of_day = unix_millis.rem_euclid(86_400_000);
+
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        ms_of_day / 3_600_000,
+        ms_of_day / 60_000 % 60,
+        ms_of_day / 1000 % 60,
+        ms_of_day % 1000
+    )
+}
@@ -0,0 +1,51 @@
+use std::sync::Arc;
+
+use crate::id::SnowflakeId;
+use crate::snowflake::{Snowflake, SnowflakeError};
+
+/// Error type returned by `IdGenerator` implementations
+pub type IdError = SnowflakeError;
+
+/// A source of Snowflake IDs
+///
+/// The trait is object-safe, so application code can hold an `Arc<dyn IdGenerator>` and
+/// swap the implementation (or a mock in tests) without becoming generic.
+///
+/// # Example
+/// ```
+/// use std::sync::Arc;
+/// use snowflake_rs_impl::generator::IdGenerator;
+/// use snowflake_rs_impl::snowflake::Snowflake;
+///
+/// let generator: Arc<dyn IdGenerator> = Arc::new(Snowflake::new(1, None).unwrap());
+/// let id = generator.next_id().unwrap();
+/// assert_eq!(id.node(), 1);
+/// ```
+pub trait IdGenerator: Send + Sync {
+    /// Returns the next ID
+    fn next_id(&self) -> Result<SnowflakeId, IdError>;
+}
+
+impl IdGenerator for Snowflake {
+    fn next_id(&self) -> Result<SnowflakeId, IdError> {
+        self.generate_id()
+    }
+}
+
+impl<T: IdGenerator + ?Sized> IdGenerator for &T {
+    fn next_id(&self) -> Result<SnowflakeId, IdError> {
+        (**self).next_id()
+    }
+}
+
+impl<T: IdGenerator + ?Sized> IdGenerator for Box<T> {
+    fn next_id(&self) -> Result<SnowflakeId, IdError> {
+        (**self).next_id()
+    }
+}
+
+impl<T: IdGenerator + ?Sized> IdGenerator for Arc<T> {
+    fn next_id(&self) -> Result<SnowflakeId, IdError> {
+        (**self).next_id()
+    }
+}
@@ -0,0 +1,252 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::clock::{Clock, SystemClock};
+use crate::exhaustion::ExhaustionStrategy;
+use crate::generator::{IdError, IdGenerator};
+use crate::id::SnowflakeId;
+use crate::layout::Layout;
+use crate::snowflake::{
+    decode_timestamp_and_sequence, encode_timestamp_and_sequence, validate_epoch, SnowflakeError, DEFAULT_EPOCH,
+};
+use crate::sync::{AtomicI64, Ordering};
+
+/// Default limit on how far an observed timestamp may be ahead of the local clock
+pub const DEFAULT_MAX_DRIFT: Duration = Duration::from_secs(60);
+
+/// Snowflake ID generator driven by a Hybrid Logical Clock
+///
+/// The timestamp field holds the HLC's physical part and the sequence field its logical
+/// counter. Like a regular generator, `generate` follows the local clock, but it never
+/// waits or fails when the clock stalls or steps backwards: it keeps the last timestamp
+/// and bumps the counter, moving on to the next millisecond when the counter runs out.
+///
+/// `observe` merges an ID (or timestamp) received from another node, so every ID generated
+/// afterwards sorts after it, even if the sender's clock is ahead of ours. IDs therefore
+/// never go backwards relative to causally related events across nodes, as long as all
+/// nodes share the same epoch and layout and their clocks are within `max_drift` of each
+/// other.
+///
+/// # Example
+/// ```
+/// use snowflake_rs_impl::hlc::HlcSnowflake;
+///
+/// let sender = HlcSnowflake::builder(1).build().unwrap();
+/// let receiver = HlcSnowflake::builder(2).build().unwrap();
+///
+/// let sent = sender.generate().unwrap();
+/// receiver.observe(sent).unwrap();
+/// let reply = receiver.generate().unwrap();
+/// assert!(reply > sent);
+/// ```
+#[derive(Debug)]
+pub struct HlcSnowflake {
+    node: u16,
+    epoch_ms: i64,
+    layout: Layout,
+    max_drift_ms: i64,
+    last_timestamp_and_sequence: AtomicI64,
+    clock: Arc<dyn Clock>,
+}
+
+/// Builder for `HlcSnowflake`
+#[derive(Debug, Clone)]
+pub struct HlcSnowflakeBuilder {
+    node: u16,
+    epoch: Option<i64>,
+    layout: Layout,
+    max_drift: Duration,
+    clock: Option<Arc<dyn Clock>>,
+}
+
+impl HlcSnowflakeBuilder {
+    /// Sets the epoch in milliseconds. If not set, DEFAULT_EPOCH is used.
+    pub fn epoch(mut self, epoch: i64) -> Self {
+        self.epoch = Some(epoch);
+        self
+    }
+
+    /// Sets the bit layout of generated IDs. If not set, `Layout::DEFAULT` is used.
+    pub fn layout(mut self, layout: Layout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Sets how far an observed timestamp may be ahead of the local clock. Defaults to
+    /// `DEFAULT_MAX_DRIFT` (one minute).
+    pub fn max_drift(mut self, max_drift: Duration) -> Self {
+        self.max_drift = max_drift;
+        self
+    }
+
+    /// Sets the clock used for the physical part. If not set, `SystemClock` is used.
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Builds the HlcSnowflake instance
+    ///
+    /// # Errors
+    ///
+    /// - SnowflakeError::MachineIdOutOfRange if the node ID does not fit in the layout
+    /// - SnowflakeError::InvalidEpoch if the epoch is in the future or too far in the past
+    pub fn build(self) -> Result<HlcSnowflake, SnowflakeError> {
+        if self.node > self.layout.max_node() {
+            return Err(SnowflakeError::MachineIdOutOfRange);
+        }
+        let epoch_ms = self.epoch.unwrap_or(DEFAULT_EPOCH);
+        let clock = self.clock.unwrap_or_else(|| Arc::new(SystemClock));
+        validate_epoch(epoch_ms, clock.try_now_millis()?, &self.layout, ExhaustionStrategy::Error)?;
+        Ok(HlcSnowflake {
+            node: self.node,
+            epoch_ms,
+            layout: self.layout,
+            max_drift_ms: i64::try_from(self.max_drift.as_millis()).unwrap_or(i64::MAX),
+            last_timestamp_and_sequence: AtomicI64::new(0),
+            clock,
+        })
+    }
+}
+
+impl HlcSnowflake {
+    /// Returns a builder for an HlcSnowflake with the given node ID
+    pub fn builder(node: u16) -> HlcSnowflakeBuilder {
+        HlcSnowflakeBuilder {
+            node,
+            epoch: None,
+            layout: Layout::DEFAULT,
+            max_drift: DEFAULT_MAX_DRIFT,
+            clock: None,
+        }
+    }
+
+    /// Returns the node ID of this generator
+    pub fn node(&self) -> u16 {
+        self.node
+    }
+
+    /// Returns the epoch of this generator in milliseconds since Unix epoch
+    pub fn epoch(&self) -> i64 {
+        self.epoch_ms
+    }
+
+    /// Returns the bit layout of generated IDs
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    /// Returns how far an observed timestamp may be ahead of the local clock
+    pub fn max_drift(&self) -> Duration {
+        Duration::from_millis(self.max_drift_ms as u64)
+    }
+
+    /// Generates a new Snowflake ID
+    ///
+    /// The ID sorts after every ID previously generated or observed by this generator.
+    ///
+    /// # Errors
+    ///
+    /// - SnowflakeError::TimestampExhausted if the timestamp field is exhausted
+    /// - SnowflakeError::ClockUnavailable if the clock cannot be read
+    pub fn generate(&self) -> Result<u64, SnowflakeError> {
+        let max_sequence = self.layout.max_sequence() as i64;
+        let mut last_timestamp_and_sequence = self.last_timestamp_and_sequence.load(Ordering::Acquire);
+
+        loop {
+            let current_timestamp = self.clock.try_now_millis()?;
+            let (last_timestamp, last_sequence) = decode_timestamp_and_sequence(last_timestamp_and_sequence);
+            let (new_timestamp, new_sequence) = if current_timestamp > last_timestamp {
+                (current_timestamp, 0)
+            } else if last_sequence < max_sequence {
+                (last_timestamp, last_sequence + 1)
+            } else {
+                (last_timestamp + 1, 0)
+            };
+            match self.last_timestamp_and_sequence.compare_exchange_weak(
+                last_timestamp_and_sequence,
+                encode_timestamp_and_sequence(new_timestamp, new_sequence),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => return self.create_id(new_timestamp, new_sequence as u16),
+                Err(actual) => {
+                    last_timestamp_and_sequence = actual;
+                }
+            }
+        }
+    }
+
+    /// Generates a new Snowflake ID wrapped in a `SnowflakeId`
+    ///
+    /// # Errors
+    ///
+    /// Same as `generate`
+    pub fn generate_id(&self) -> Result<SnowflakeId, SnowflakeError> {
+        self.generate().map(SnowflakeId::from)
+    }
+
+    /// Merges an ID received from another node into the clock
+    ///
+    /// Every ID generated afterwards sorts after `id`. The ID must use the same epoch and
+    /// layout as this generator.
+    ///
+    /// # Errors
+    ///
+    /// - SnowflakeError::ClockDriftExceeded if the ID's timestamp is more than `max_drift`
+    ///   ahead of the local clock; the clock is left unchanged
+    /// - SnowflakeError::ClockUnavailable if the local clock cannot be read
+    pub fn observe(&self, id: u64) -> Result<(), SnowflakeError> {
+        let (timestamp, node, sequence) = self.layout.decompose(id);
+        // The node field sits between timestamp and sequence, so an ID from a higher node
+        // outranks every local ID of its millisecond: move past that millisecond
+        let sequence = if node > self.node { self.layout.max_sequence() } else { sequence };
+        self.merge(self.epoch_ms.saturating_add(timestamp as i64), sequence as i64)
+    }
+
+    /// Merges a physical timestamp received from another node into the clock
+    ///
+    /// Every ID generated afterwards has a timestamp after `timestamp_ms`.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp_ms` - The remote timestamp in milliseconds since Unix epoch
+    ///
+    /// # Errors
+    ///
+    /// Same as `observe`
+    pub fn observe_millis(&self, timestamp_ms: i64) -> Result<(), SnowflakeError> {
+        self.merge(timestamp_ms, self.layout.max_sequence() as i64)
+    }
+
+    // Moves the clock to the remote reading if it is ahead. The packed state orders like
+    // (timestamp, sequence), so merging is a single atomic max.
+    fn merge(&self, timestamp: i64, sequence: i64) -> Result<(), SnowflakeError> {
+        let drift = timestamp.saturating_sub(self.clock.try_now_millis()?);
+        if drift > self.max_drift_ms {
+            return Err(SnowflakeError::ClockDriftExceeded(drift));
+        }
+        self.last_timestamp_and_sequence
+            .fetch_max(encode_timestamp_and_sequence(timestamp, sequence), Ordering::AcqRel);
+        Ok(())
+    }
+
+    // Creates the final ID by combining timestamp, node ID, and sequence
+    fn create_id(&self, timestamp: i64, sequence: u16) -> Result<u64, SnowflakeError> {
+        let offset = (timestamp - self.epoch_ms) as u64;
+        if offset > self.layout.max_timestamp() {
+            return Err(SnowflakeError::TimestampExhausted);
+        }
+        Ok(self.layout.with_checksum(
+            (offset << self.layout.timestamp_shift())
+                | ((self.node as u64) << self.layout.node_shift())
+                | ((sequence as u64) << self.layout.sequence_shift()),
+        ))
+    }
+}
+
+impl IdGenerator for HlcSnowflake {
+    fn next_id(&self) -> Result<SnowflakeId, IdError> {
+        self.generate_id()
+    }
+}
@@ -0,0 +1,436 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::clock::{Clock, SystemClock};
+use crate::exhaustion::ERA_BIT;
+use crate::layout::Layout;
+use crate::origin::KnownNodes;
+use crate::snowflake::{
+    InvalidIdReason, SnowflakeError, DEFAULT_EPOCH, NODE_BITS, NODE_SHIFT, STEP_BITS, TIMESTAMP_BITS, TIMESTAMP_SHIFT,
+};
+
+/// Largest timestamp offset (in milliseconds since the epoch) that fits in the timestamp field
+const TIMESTAMP_MAX: u64 = (1 << TIMESTAMP_BITS) - 1;
+
+/// Mask covering the node and sequence fields
+const NODE_AND_STEP_MASK: u64 = (1 << (NODE_BITS + STEP_BITS)) - 1;
+
+/// Base62 alphabet (digits, then upper-case, then lower-case letters)
+const BASE62_ALPHABET: &[u8; 62] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// Prefix marking a base62-encoded ID in deserialized strings
+pub const BASE62_PREFIX: &str = "b62_";
+
+/// Length of the strings produced by `SnowflakeId::to_sortable_string`; `u64::MAX` has 20
+/// decimal digits
+pub const SORTABLE_STRING_LEN: usize = 20;
+
+/// A Snowflake ID
+///
+/// Thin wrapper around the raw `u64` produced by `Snowflake::generate`, using the
+/// default bit layout:
+/// - Timestamp (41 bits)
+/// - Node ID (10 bits)
+/// - Sequence number (12 bits)
+///
+/// The ordering of `SnowflakeId` values is the numeric ordering of the raw IDs.
+///
+/// Serializes as a number. In human-readable formats such as JSON, it deserializes from
+/// a number, a decimal string (`"1234"`) or a base62 string with `BASE62_PREFIX`
+/// (`"b62_KQ"`), since JavaScript clients often cannot represent 64-bit numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(transparent)]
+pub struct SnowflakeId(u64);
+
+impl SnowflakeId {
+    /// The smallest possible ID
+    pub const MIN: SnowflakeId = SnowflakeId(0);
+    /// The largest possible first-era ID (all timestamp, node and sequence bits set)
+    ///
+    /// Second-era IDs (with `ERA_BIT` set, see `ExhaustionStrategy::Era`) are larger.
+    pub const MAX: SnowflakeId = SnowflakeId((TIMESTAMP_MAX << TIMESTAMP_SHIFT) | NODE_AND_STEP_MASK);
+
+    /// Wraps a raw ID value
+    pub const fn from_u64(id: u64) -> Self {
+        SnowflakeId(id)
+    }
+
+    /// Returns the raw ID value
+    pub const fn as_u64(&self) -> u64 {
+        self.0
+    }
+
+    /// Returns the timestamp field (milliseconds since the generator's epoch)
+    pub const fn timestamp(&self) -> u64 {
+        (self.0 >> TIMESTAMP_SHIFT) & TIMESTAMP_MAX
+    }
+
+    /// Returns the node ID field
+    pub const fn node(&self) -> u16 {
+        ((self.0 >> NODE_SHIFT) & ((1 << NODE_BITS) - 1)) as u16
+    }
+
+    /// Returns the sequence number field
+    pub const fn sequence(&self) -> u16 {
+        (self.0 & ((1 << STEP_BITS) - 1)) as u16
+    }
+
+    /// Returns the next possible ID in the same era, or None if this is the last ID of
+    /// its era (`SnowflakeId::MAX` for the first era)
+    ///
+    /// The successor is the smallest ID strictly greater than this one, which makes it
+    /// suitable as an exclusive upper bound for "everything up to and including `self`".
+    pub const fn checked_successor(&self) -> Option<SnowflakeId> {
+        if self.0 & !ERA_BIT >= Self::MAX.0 {
+            None
+        } else {
+            Some(SnowflakeId(self.0 + 1))
+        }
+    }
+
+    /// Returns the previous possible ID in the same era, or None if this is the first ID
+    /// of its era (`SnowflakeId::MIN` for the first era)
+    pub const fn checked_predecessor(&self) -> Option<SnowflakeId> {
+        if self.0 & !ERA_BIT == Self::MIN.0 {
+            None
+        } else {
+            Some(SnowflakeId(self.0 - 1))
+        }
+    }
+
+    /// Shifts the timestamp field forward by `duration`
+    ///
+    /// Only the timestamp field changes; the era, node and sequence fields are kept as-is
+    /// and never carry into the timestamp. Sub-millisecond parts of `duration` are ignored.
+    ///
+    /// # Returns
+    ///
+    /// The shifted ID, or None if the new timestamp does not fit in the timestamp field
+    /// (the ID would have to move to the next era)
+    pub fn offset_by(&self, duration: Duration) -> Option<SnowflakeId> {
+        let millis = u64::try_from(duration.as_millis()).ok()?;
+        let timestamp = self.timestamp().checked_add(millis)?;
+        self.with_timestamp(timestamp)
+    }
+
+    /// Shifts the timestamp field backward by `duration`
+    ///
+    /// Like `offset_by`, only the timestamp field changes.
+    ///
+    /// # Returns
+    ///
+    /// The shifted ID, or None if the new timestamp would be before the epoch (or, for a
+    /// second-era ID, before the start of its era)
+    pub fn offset_back_by(&self, duration: Duration) -> Option<SnowflakeId> {
+        let millis = u64::try_from(duration.as_millis()).ok()?;
+        let timestamp = self.timestamp().checked_sub(millis)?;
+        self.with_timestamp(timestamp)
+    }
+
+    /// Wraps a raw ID value, rejecting values with the reserved top bit set
+    ///
+    /// `From<u64>` accepts any value; use this at trust boundaries instead. Use
+    /// `IdValidator` to accept second-era IDs or to check the timestamp as well.
+    ///
+    /// # Errors
+    ///
+    /// Returns SnowflakeError::InvalidId with `InvalidIdReason::ReservedBitSet` if the top
+    /// bit is set
+    pub fn try_from_u64(id: u64) -> Result<Self, SnowflakeError> {
+        IdValidator::new().validate(id)
+    }
+
+    /// Encodes the ID in base62 (without `BASE62_PREFIX`)
+    pub fn to_base62(&self) -> String {
+        let mut id = self.0;
+        let mut digits = Vec::with_capacity(11);
+        loop {
+            digits.push(BASE62_ALPHABET[(id % 62) as usize]);
+            id /= 62;
+            if id == 0 {
+                break;
+            }
+        }
+        digits.iter().rev().map(|&digit| digit as char).collect()
+    }
+
+    /// Parses an ID encoded with `to_base62`
+    ///
+    /// # Errors
+    ///
+    /// Returns SnowflakeError::InvalidEncoding if the string is empty, contains other
+    /// characters or does not fit in 64 bits
+    pub fn from_base62(encoded: &str) -> Result<Self, SnowflakeError> {
+        if encoded.is_empty() {
+            return Err(SnowflakeError::InvalidEncoding);
+        }
+        let mut id: u64 = 0;
+        for byte in encoded.bytes() {
+            let digit = match byte {
+                b'0'..=b'9' => byte - b'0',
+                b'A'..=b'Z' => byte - b'A' + 10,
+                b'a'..=b'z' => byte - b'a' + 36,
+                _ => return Err(SnowflakeError::InvalidEncoding),
+            };
+            id = id
+                .checked_mul(62)
+                .and_then(|id| id.checked_add(digit as u64))
+                .ok_or(SnowflakeError::InvalidEncoding)?;
+        }
+        Ok(SnowflakeId(id))
+    }
+
+    /// Formats the ID as a decimal string zero-padded to `SORTABLE_STRING_LEN` digits
+    ///
+    /// Every ID formats to the same width, so the strings sort lexicographically in the
+    /// same order as the IDs, e.g. for stores that keep sort keys as strings.
+    ///
+    /// # Example
+    /// ```
+    /// use snowflake_rs_impl::id::SnowflakeId;
+    ///
+    /// assert_eq!(SnowflakeId::from_u64(1266).to_sortable_string(), "00000000000000001266");
+    /// ```
+    pub fn to_sortable_string(&self) -> String {
+        format!("{:0width$}", self.0, width = SORTABLE_STRING_LEN)
+    }
+
+    /// Parses an ID formatted with `to_sortable_string`
+    ///
+    /// # Errors
+    ///
+    /// Returns SnowflakeError::InvalidEncoding if the string is not exactly
+    /// `SORTABLE_STRING_LEN` decimal digits or does not fit in 64 bits
+    pub fn from_sortable_string(encoded: &str) -> Result<Self, SnowflakeError> {
+        if encoded.len() != SORTABLE_STRING_LEN || !encoded.bytes().all(|byte| byte.is_ascii_digit()) {
+            return Err(SnowflakeError::InvalidEncoding);
+        }
+        encoded.parse::<u64>().map(SnowflakeId).map_err(|_| SnowflakeError::InvalidEncoding)
+    }
+
+    // Replaces the timestamp field, keeping the era, node and sequence
+    fn with_timestamp(&self, timestamp: u64) -> Option<SnowflakeId> {
+        if timestamp > TIMESTAMP_MAX {
+            return None;
+        }
+        Some(SnowflakeId((self.0 & ERA_BIT) | (timestamp << TIMESTAMP_SHIFT) | (self.0 & NODE_AND_STEP_MASK)))
+    }
+}
+
+impl From<u64> for SnowflakeId {
+    fn from(id: u64) -> Self {
+        SnowflakeId(id)
+    }
+}
+
+impl From<SnowflakeId> for u64 {
+    fn from(id: SnowflakeId) -> Self {
+        id.0
+    }
+}
+
+impl fmt::Display for SnowflakeId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for SnowflakeId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // Binary formats may not support `deserialize_any`, and never carry strings
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(SnowflakeIdVisitor)
+        } else {
+            deserializer.deserialize_u64(SnowflakeIdVisitor)
+        }
+    }
+}
+
+// Accepts numbers, decimal strings and prefixed base62 strings
+struct SnowflakeIdVisitor;
+
+impl<'de> Visitor<'de> for SnowflakeIdVisitor {
+    type Value = SnowflakeId;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a Snowflake ID as a number, a decimal string or a \"{}\"-prefixed base62 string", BASE62_PREFIX)
+    }
+
+    fn visit_u64<E: de::Error>(self, id: u64) -> Result<SnowflakeId, E> {
+        Ok(SnowflakeId(id))
+    }
+
+    fn visit_i64<E: de::Error>(self, id: i64) -> Result<SnowflakeId, E> {
+        u64::try_from(id)
+            .map(SnowflakeId)
+            .map_err(|_| E::invalid_value(de::Unexpected::Signed(id), &self))
+    }
+
+    fn visit_str<E: de::Error>(self, id: &str) -> Result<SnowflakeId, E> {
+        let parsed = match id.strip_prefix(BASE62_PREFIX) {
+            Some(encoded) => SnowflakeId::from_base62(encoded).ok(),
+            None => id.parse::<u64>().ok().map(SnowflakeId),
+        };
+        parsed.ok_or_else(|| E::invalid_value(de::Unexpected::Str(id), &self))
+    }
+}
+
+/// Validates the sign bit only; see `IdValidator` for more checks
+impl TryFrom<i64> for SnowflakeId {
+    type Error = SnowflakeError;
+
+    fn try_from(id: i64) -> Result<Self, Self::Error> {
+        IdValidator::new().validate_i64(id)
+    }
+}
+
+/// Checks applied to raw values before accepting them as Snowflake IDs
+///
+/// By default only the reserved top bit is checked, plus the check field if the layout
+/// has one. Timestamp plausibility checks are opt-in, since they need the epoch the IDs
+/// were generated with, and so is the origin check against a `KnownNodes` set.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use snowflake_rs_impl::id::IdValidator;
+/// use snowflake_rs_impl::snowflake::Snowflake;
+///
+/// let snowflake = Snowflake::new(1, None).unwrap();
+/// let validator = IdValidator::new()
+///     .epoch(snowflake.epoch())
+///     .not_before(1609459200000)
+///     .max_future_skew(Duration::from_secs(60));
+/// assert!(validator.validate(snowflake.generate().unwrap()).is_ok());
+/// assert!(validator.validate(u64::MAX).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdValidator {
+    epoch_ms: i64,
+    layout: Layout,
+    allow_era: bool,
+    not_before_ms: Option<i64>,
+    max_future_skew: Option<Duration>,
+    known_nodes: Option<Arc<KnownNodes>>,
+}
+
+impl IdValidator {
+    /// Creates a validator checking the reserved top bit only
+    pub fn new() -> Self {
+        IdValidator {
+            epoch_ms: DEFAULT_EPOCH,
+            layout: Layout::DEFAULT,
+            allow_era: false,
+            not_before_ms: None,
+            max_future_skew: None,
+            known_nodes: None,
+        }
+    }
+
+    /// Sets the epoch the IDs were generated with. Defaults to DEFAULT_EPOCH.
+    pub fn epoch(mut self, epoch: i64) -> Self {
+        self.epoch_ms = epoch;
+        self
+    }
+
+    /// Sets the layout the IDs were generated with. Defaults to `Layout::DEFAULT`.
+    ///
+    /// If the layout has a check field, every ID's checksum is verified.
+    pub fn layout(mut self, layout: Layout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Accepts IDs with `ERA_BIT` set, as issued with `ExhaustionStrategy::Era`
+    pub fn allow_era(mut self, allow: bool) -> Self {
+        self.allow_era = allow;
+        self
+    }
+
+    /// Rejects IDs generated before `unix_millis`, e.g. the date the system went live
+    pub fn not_before(mut self, unix_millis: i64) -> Self {
+        self.not_before_ms = Some(unix_millis);
+        self
+    }
+
+    /// Rejects IDs generated more than `skew` ahead of the system clock
+    pub fn max_future_skew(mut self, skew: Duration) -> Self {
+        self.max_future_skew = Some(skew);
+        self
+    }
+
+    /// Rejects IDs claiming a node ID that is not in `known_nodes` (see
+    /// `KnownNodes::validate_origin`)
+    ///
+    /// Node IDs are decoded with the set's layout, which should match `layout`.
+    pub fn known_nodes(mut self, known_nodes: Arc<KnownNodes>) -> Self {
+        self.known_nodes = Some(known_nodes);
+        self
+    }
+
+    /// Validates a raw ID
+    ///
+    /// # Errors
+    ///
+    /// - SnowflakeError::InvalidId if the value fails a check; the reason says which
+    /// - SnowflakeError::UnknownNode if `known_nodes` is set and the ID's node ID is not
+    ///   in the set
+    /// - SnowflakeError::ClockUnavailable if `max_future_skew` is set and the system time
+    ///   is before the Unix epoch
+    pub fn validate(&self, id: u64) -> Result<SnowflakeId, SnowflakeError> {
+        let era = id & ERA_BIT != 0;
+        if era && !self.allow_era {
+            return Err(SnowflakeError::InvalidId(InvalidIdReason::ReservedBitSet));
+        }
+        if !self.layout.has_valid_checksum(id) {
+            return Err(SnowflakeError::InvalidId(InvalidIdReason::ChecksumMismatch));
+        }
+        if let Some(known_nodes) = &self.known_nodes {
+            known_nodes.validate_origin(id & !ERA_BIT)?;
+        }
+        if self.not_before_ms.is_none() && self.max_future_skew.is_none() {
+            return Ok(SnowflakeId(id));
+        }
+
+        let (timestamp, _, _) = self.layout.decompose(id & !ERA_BIT);
+        let era_offset = if era { self.layout.max_timestamp() + 1 } else { 0 };
+        let unix_millis = self.epoch_ms.saturating_add((timestamp + era_offset) as i64);
+        if let Some(not_before) = self.not_before_ms {
+            if unix_millis < not_before {
+                return Err(SnowflakeError::InvalidId(InvalidIdReason::TooOld { unix_millis, not_before }));
+            }
+        }
+        if let Some(skew) = self.max_future_skew {
+            let now = SystemClock.try_now_millis()?;
+            let skew_ms = i64::try_from(skew.as_millis()).unwrap_or(i64::MAX);
+            if unix_millis > now.saturating_add(skew_ms) {
+                return Err(SnowflakeError::InvalidId(InvalidIdReason::InFuture { unix_millis, now }));
+            }
+        }
+        Ok(SnowflakeId(id))
+    }
+
+    /// Validates a raw ID stored as a signed integer, e.g. a database `BIGINT`
+    ///
+    /// # Errors
+    ///
+    /// Same as `validate`, plus SnowflakeError::InvalidId with `InvalidIdReason::Negative`
+    /// for negative values (unless `allow_era` is set, since second-era IDs are negative
+    /// as `i64`)
+    pub fn validate_i64(&self, id: i64) -> Result<SnowflakeId, SnowflakeError> {
+        if id < 0 && !self.allow_era {
+            return Err(SnowflakeError::InvalidId(InvalidIdReason::Negative));
+        }
+        self.validate(id as u64)
+    }
+}
+
+impl Default for IdValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,318 @@
+use serde::{Deserialize, Serialize};
+
+use crate::snowflake::{SnowflakeError, NODE_BITS, STEP_BITS};
+
+/// Number of bits available to an ID; the sign bit is always left unset
+const ID_BITS: u8 = 63;
+
+/// Maximum width of the node and sequence fields
+const FIELD_BITS_MAX: u8 = 16;
+
+/// Maximum width of the check field
+const CHECK_BITS_MAX: u8 = 8;
+
+/// CRC generator polynomial for each check field width (1-8 bits), without the leading
+/// term: parity, x^2+x+1, CRC-3-GSM, CRC-4-ITU, CRC-5-USB, CRC-6-ITU, CRC-7 and CRC-8
+const CHECK_POLYNOMIALS: [u64; CHECK_BITS_MAX as usize] = [0x1, 0x3, 0x3, 0x3, 0x05, 0x03, 0x09, 0x07];
+
+/// Bit layout of a Snowflake ID
+///
+/// A layout splits the 63 usable bits of an ID into three fields, from most to least
+/// significant:
+/// - Timestamp (whatever is left over)
+/// - Node ID (`node_bits`)
+/// - Sequence number (`step_bits`)
+///
+/// Optionally, `with_check_bits` reserves the lowest bits for a check value (see
+/// `checksum`), so corrupted IDs are detected when parsed.
+///
+/// `Layout::DEFAULT` is the 41/10/12 split used by `Snowflake`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "RawLayout")]
+pub struct Layout {
+    node_bits: u8,
+    step_bits: u8,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    check_bits: u8,
+}
+
+// Serialized form of a Layout, validated by `Layout::new` and `with_check_bits` when
+// deserializing
+#[derive(Deserialize)]
+struct RawLayout {
+    node_bits: u8,
+    step_bits: u8,
+    #[serde(default)]
+    check_bits: u8,
+}
+
+impl TryFrom<RawLayout> for Layout {
+    type Error = SnowflakeError;
+
+    fn try_from(raw: RawLayout) -> Result<Self, Self::Error> {
+        Layout::new(raw.node_bits, raw.step_bits).and_then(|layout| layout.with_check_bits(raw.check_bits))
+    }
+}
+
+impl Layout {
+    /// The default layout: 41 timestamp bits, 10 node bits, 12 sequence bits
+    pub const DEFAULT: Layout = Layout {
+        node_bits: NODE_BITS,
+        step_bits: STEP_BITS,
+        check_bits: 0,
+    };
+
+    /// Creates a new layout
+    ///
+    /// # Arguments
+    ///
+    /// * `node_bits` - Width of the node ID field (0-16)
+    /// * `step_bits` - Width of the sequence number field (1-16)
+    ///
+    /// # Errors
+    ///
+    /// Returns SnowflakeError::InvalidLayout if either field width is out of range
+    pub fn new(node_bits: u8, step_bits: u8) -> Result<Self, SnowflakeError> {
+        if node_bits > FIELD_BITS_MAX || step_bits == 0 || step_bits > FIELD_BITS_MAX {
+            return Err(SnowflakeError::InvalidLayout);
+        }
+        Ok(Layout {
+            node_bits,
+            step_bits,
+            check_bits: 0,
+        })
+    }
+
+    /// Creates a new layout in a const context
+    ///
+    /// Same as `new`, but usable in constants, where an invalid layout fails compilation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either field width is out of range
+    pub const fn new_const(node_bits: u8, step_bits: u8) -> Self {
+        assert!(node_bits <= FIELD_BITS_MAX, "node_bits must be at most 16");
+        assert!(step_bits >= 1 && step_bits <= FIELD_BITS_MAX, "step_bits must be between 1 and 16");
+        Layout {
+            node_bits,
+            step_bits,
+            check_bits: 0,
+        }
+    }
+
+    /// Returns this layout with the lowest `check_bits` bits reserved for a check value
+    ///
+    /// The check value is a CRC over the rest of the ID, so every single-bit error and
+    /// every burst of up to `check_bits` flipped bits is detected, and other corruption
+    /// goes unnoticed with probability `2^-check_bits`. The bits are taken from the
+    /// timestamp field, shortening the time the layout covers; start from a layout with
+    /// a narrower node or sequence field to keep it.
+    ///
+    /// # Arguments
+    ///
+    /// * `check_bits` - Width of the check field (0-8); 0 removes it
+    ///
+    /// # Errors
+    ///
+    /// Returns SnowflakeError::InvalidLayout if `check_bits` is greater than 8
+    ///
+    /// # Example
+    /// ```
+    /// use snowflake_rs_impl::layout::Layout;
+    ///
+    /// let layout = Layout::DEFAULT.with_check_bits(4).unwrap();
+    /// let id = layout.compose(1000, 5, 7).unwrap();
+    /// assert!(layout.has_valid_checksum(id));
+    /// assert!(!layout.has_valid_checksum(id ^ 1 << 20));
+    /// assert_eq!(layout.decompose(id), (1000, 5, 7));
+    /// ```
+    pub fn with_check_bits(self, check_bits: u8) -> Result<Self, SnowflakeError> {
+        if check_bits > CHECK_BITS_MAX {
+            return Err(SnowflakeError::InvalidLayout);
+        }
+        Ok(Layout { check_bits, ..self })
+    }
+
+    /// Width of the timestamp field
+    pub const fn timestamp_bits(&self) -> u8 {
+        ID_BITS - self.node_bits - self.step_bits - self.check_bits
+    }
+
+    /// Width of the node ID field
+    pub const fn node_bits(&self) -> u8 {
+        self.node_bits
+    }
+
+    /// Width of the sequence number field
+    pub const fn step_bits(&self) -> u8 {
+        self.step_bits
+    }
+
+    /// Width of the check field (0 if the layout has none)
+    pub const fn check_bits(&self) -> u8 {
+        self.check_bits
+    }
+
+    /// Largest timestamp (milliseconds since the epoch) the layout can represent
+    pub const fn max_timestamp(&self) -> u64 {
+        (1 << self.timestamp_bits()) - 1
+    }
+
+    /// Largest node ID the layout can represent
+    pub const fn max_node(&self) -> u16 {
+        ((1u32 << self.node_bits) - 1) as u16
+    }
+
+    /// Largest sequence number the layout can represent
+    pub const fn max_sequence(&self) -> u16 {
+        ((1u32 << self.step_bits) - 1) as u16
+    }
+
+    /// Shift of the timestamp field
+    pub const fn timestamp_shift(&self) -> u8 {
+        self.node_bits + self.step_bits + self.check_bits
+    }
+
+    /// Shift of the node ID field
+    pub const fn node_shift(&self) -> u8 {
+        self.step_bits + self.check_bits
+    }
+
+    /// Shift of the sequence number field (the width of the check field below it)
+    pub const fn sequence_shift(&self) -> u8 {
+        self.check_bits
+    }
+
+    /// Splits an ID into its timestamp, node ID and sequence number using this layout
+    ///
+    /// The check field, if any, is ignored; use `has_valid_checksum` to verify it.
+    pub const fn decompose(&self, id: u64) -> (u64, u16, u16) {
+        let timestamp = (id >> self.timestamp_shift()) & self.max_timestamp();
+        let node = ((id >> self.node_shift()) & self.max_node() as u64) as u16;
+        let sequence = ((id >> self.sequence_shift()) & self.max_sequence() as u64) as u16;
+        (timestamp, node, sequence)
+    }
+
+    /// Returns the check value of an ID: the CRC of every bit above the check field
+    ///
+    /// Returns 0 if the layout has no check field.
+    pub const fn checksum(&self, id: u64) -> u64 {
+        let width = self.check_bits as u32;
+        if width == 0 {
+            return 0;
+        }
+        // Polynomial long division of the bits above the check field, shifted left by
+        // the width as in any CRC
+        let polynomial = (1 << width) | CHECK_POLYNOMIALS[width as usize - 1];
+        let mut remainder = (id >> width) << width;
+        let mut bit = 63;
+        while bit >= width {
+            if remainder & (1 << bit) != 0 {
+                remainder ^= polynomial << (bit - width);
+            }
+            bit -= 1;
+        }
+        remainder
+    }
+
+    /// Returns the ID with its check field set to `checksum(id)`
+    ///
+    /// Returns the ID unchanged if the layout has no check field.
+    pub const fn with_checksum(&self, id: u64) -> u64 {
+        if self.check_bits == 0 {
+            return id;
+        }
+        (id >> self.check_bits << self.check_bits) | self.checksum(id)
+    }
+
+    /// Returns true if the check field of the ID matches the rest of it
+    ///
+    /// Always true if the layout has no check field.
+    pub const fn has_valid_checksum(&self, id: u64) -> bool {
+        self.with_checksum(id) == id
+    }
+
+    /// Combines a timestamp, node ID and sequence number into an ID using this layout
+    ///
+    /// The check field, if any, is filled in.
+    ///
+    /// # Errors
+    ///
+    /// - SnowflakeError::TimestampOutOfRange if the timestamp does not fit
+    /// - SnowflakeError::MachineIdOutOfRange if the node ID does not fit
+    /// - SnowflakeError::SequenceOutOfRange if the sequence number does not fit
+    pub fn compose(&self, timestamp: u64, node: u16, sequence: u16) -> Result<u64, SnowflakeError> {
+        if timestamp > self.max_timestamp() {
+            return Err(SnowflakeError::TimestampOutOfRange);
+        }
+        if node > self.max_node() {
+            return Err(SnowflakeError::MachineIdOutOfRange);
+        }
+        if sequence > self.max_sequence() {
+            return Err(SnowflakeError::SequenceOutOfRange);
+        }
+        Ok(self.with_checksum(
+            (timestamp << self.timestamp_shift())
+                | ((node as u64) << self.node_shift())
+                | ((sequence as u64) << self.sequence_shift()),
+        ))
+    }
+
+    /// Encodes a block of IDs sharing one timestamp and node ID
+    ///
+    /// `out[i]` receives the ID for `sequences[i]`. The timestamp and node fields are
+    /// validated and combined once, so without a check field the per-ID work is a single
+    /// bitwise OR that the compiler can vectorize.
+    ///
+    /// # Errors
+    ///
+    /// Same as `compose`; nothing is written to `out` on error
+    ///
+    /// # Panics
+    ///
+    /// Panics if `sequences` and `out` have different lengths
+    pub fn encode_block(&self, timestamp: u64, node: u16, sequences: &[u16], out: &mut [u64]) -> Result<(), SnowflakeError> {
+        assert_eq!(sequences.len(), out.len(), "sequences and out must have the same length");
+        let prefix = self.compose(timestamp, node, 0)?;
+        let max_sequence = sequences.iter().copied().fold(0, u16::max);
+        if max_sequence > self.max_sequence() {
+            return Err(SnowflakeError::SequenceOutOfRange);
+        }
+        for (id, &sequence) in out.iter_mut().zip(sequences) {
+            *id = self.with_checksum(prefix | (sequence as u64) << self.sequence_shift());
+        }
+        Ok(())
+    }
+
+    /// Encodes a block of IDs with consecutive sequence numbers starting at `first_sequence`
+    ///
+    /// `out[i]` receives the ID for sequence number `first_sequence + i`.
+    ///
+    /// # Errors
+    ///
+    /// Same as `compose`, including for the last sequence number of the block; nothing is
+    /// written to `out` on error
+    pub fn encode_range(&self, timestamp: u64, node: u16, first_sequence: u16, out: &mut [u64]) -> Result<(), SnowflakeError> {
+        let prefix = self.compose(timestamp, node, 0)?;
+        let last_sequence = first_sequence as u64 + out.len().saturating_sub(1) as u64;
+        if last_sequence > self.max_sequence() as u64 {
+            return Err(SnowflakeError::SequenceOutOfRange);
+        }
+        for (offset, id) in out.iter_mut().enumerate() {
+            *id = self.with_checksum(prefix | (first_sequence as u64 + offset as u64) << self.sequence_shift());
+        }
+        Ok(())
+    }
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Layout::DEFAULT
+    }
+}
+
+// Keeps the check field out of serialized layouts that have none, so they serialize as
+// before check fields existed
+fn is_zero(bits: &u8) -> bool {
+    *bits == 0
+}
@@ -1 +1,41 @@
 pub mod snowflake;
+#[cfg(feature = "async")]
+pub mod actor;
+#[cfg(feature = "arrow")]
+pub mod arrow;
+pub mod audit;
+#[cfg(feature = "avro")]
+pub mod avro;
+mod backfill;
+pub mod bwmarrin;
+pub mod clock;
+pub mod config;
+pub mod const_layout;
+#[cfg(feature = "duplicate-guard")]
+pub mod duplicate_guard;
+pub mod exhaustion;
+pub mod explain;
+pub mod generator;
+pub mod hlc;
+pub mod id;
+pub mod layout;
+pub mod migrate;
+pub mod origin;
+#[cfg(feature = "rayon")]
+pub mod parallel;
+pub mod persist;
+pub mod rate_limit;
+pub mod redact;
+pub mod region;
+pub mod registry;
+pub mod remote;
+#[cfg(feature = "scylla")]
+pub mod scylla;
+pub mod self_test;
+mod sync;
+#[cfg(feature = "test-utils")]
+pub mod test_utils;
+#[cfg(feature = "tower")]
+pub mod tower;
+#[cfg(feature = "uuid")]
+pub mod uuid;
@@ -0,0 +1,128 @@
+use crate::id::IdValidator;
+use crate::layout::Layout;
+use crate::snowflake::{SnowflakeError, TIMESTAMP_BITS, TIMESTAMP_SHIFT};
+
+/// Largest timestamp offset that fits in the timestamp field
+const TIMESTAMP_MAX: i64 = (1 << TIMESTAMP_BITS) - 1;
+
+/// Mask covering the node and sequence fields
+const NODE_AND_STEP_MASK: u64 = (1 << TIMESTAMP_SHIFT) - 1;
+
+/// Re-encodes a Snowflake ID generated with one epoch so it is relative to another epoch
+///
+/// The absolute point in time, node ID and sequence number are preserved; only the
+/// timestamp field is rebased.
+///
+/// # Arguments
+///
+/// * `id` - The Snowflake ID to migrate
+/// * `from_epoch` - The epoch (milliseconds since Unix epoch) the ID was generated with
+/// * `to_epoch` - The epoch (milliseconds since Unix epoch) to re-encode the ID for
+///
+/// # Returns
+///
+/// A Result containing the migrated ID or a SnowflakeError
+///
+/// # Errors
+///
+/// Returns SnowflakeError::TimestampOutOfRange if the ID's point in time is before
+/// `to_epoch` or too far after it to fit in the timestamp field
+///
+/// # Example
+/// ```
+/// use snowflake_rs_impl::migrate::migrate_epoch;
+/// use snowflake_rs_impl::snowflake::Snowflake;
+///
+/// let old_epoch = 1288834974657; // Twitter epoch
+/// let new_epoch = 1609459200000; // 2021-01-01T00:00:00Z
+/// let id = Snowflake::new(1, Some(old_epoch)).unwrap().generate().unwrap();
+/// let migrated = migrate_epoch(id, old_epoch, new_epoch).unwrap();
+/// assert!(migrated < id);
+/// ```
+pub fn migrate_epoch(id: u64, from_epoch: i64, to_epoch: i64) -> Result<u64, SnowflakeError> {
+    let timestamp = (id >> TIMESTAMP_SHIFT) as i64;
+    let migrated = timestamp
+        .checked_add(from_epoch)
+        .and_then(|absolute| absolute.checked_sub(to_epoch))
+        .ok_or(SnowflakeError::TimestampOutOfRange)?;
+    if !(0..=TIMESTAMP_MAX).contains(&migrated) {
+        return Err(SnowflakeError::TimestampOutOfRange);
+    }
+    Ok(((migrated as u64) << TIMESTAMP_SHIFT) | (id & NODE_AND_STEP_MASK))
+}
+
+/// Re-encodes a slice of Snowflake IDs from one epoch to another
+///
+/// This is the bulk variant of `migrate_epoch`. The migration is all-or-nothing: if any
+/// ID cannot be migrated, no result is returned.
+///
+/// # Returns
+///
+/// A Result containing the migrated IDs, in input order, or a SnowflakeError
+///
+/// # Errors
+///
+/// Returns SnowflakeError::TimestampOutOfRange if any ID cannot be represented relative
+/// to `to_epoch`
+pub fn migrate_epoch_bulk(ids: &[u64], from_epoch: i64, to_epoch: i64) -> Result<Vec<u64>, SnowflakeError> {
+    ids.iter()
+        .map(|&id| migrate_epoch(id, from_epoch, to_epoch))
+        .collect()
+}
+
+/// Re-packs a Snowflake ID from one bit layout into another
+///
+/// The timestamp, node ID and sequence number are decoded with `from` and re-encoded
+/// with `to`. The conversion only succeeds when every field fits in the target layout,
+/// so no information is ever silently dropped. Second-era IDs (with `ERA_BIT` set) are
+/// rejected, since their timestamp only has a meaning relative to the width of the
+/// timestamp field in `from`.
+///
+/// # Arguments
+///
+/// * `id` - The Snowflake ID to re-pack
+/// * `from` - The layout the ID was generated with
+/// * `to` - The layout to re-encode the ID with
+///
+/// # Returns
+///
+/// A Result containing the re-packed ID or a SnowflakeError
+///
+/// # Errors
+///
+/// - SnowflakeError::InvalidId with `InvalidIdReason::ReservedBitSet` if `ERA_BIT` is
+///   set, or with `InvalidIdReason::ChecksumMismatch` if `from` has a check field that
+///   does not match the ID
+/// - SnowflakeError::TimestampOutOfRange if the timestamp does not fit in `to`
+/// - SnowflakeError::MachineIdOutOfRange if the node ID does not fit in `to`
+/// - SnowflakeError::SequenceOutOfRange if the sequence number does not fit in `to`
+///
+/// # Example
+/// ```
+/// use snowflake_rs_impl::layout::Layout;
+/// use snowflake_rs_impl::migrate::repack_layout;
+///
+/// let narrow_nodes = Layout::new(8, 14).unwrap();
+/// let id = Layout::DEFAULT.compose(1000, 200, 3000).unwrap();
+/// let repacked = repack_layout(id, &Layout::DEFAULT, &narrow_nodes).unwrap();
+/// assert_eq!(narrow_nodes.decompose(repacked), (1000, 200, 3000));
+/// ```
+pub fn repack_layout(id: u64, from: &Layout, to: &Layout) -> Result<u64, SnowflakeError> {
+    IdValidator::new().layout(*from).validate(id)?;
+    let (timestamp, node, sequence) = from.decompose(id);
+    to.compose(timestamp, node, sequence)
+}
+
+/// Re-packs a slice of Snowflake IDs from one bit layout into another
+///
+/// This is the bulk variant of `repack_layout`. The conversion is all-or-nothing: if any
+/// ID would lose information, no result is returned.
+///
+/// # Errors
+///
+/// Same as `repack_layout`, for the first ID that does not fit
+pub fn repack_layout_bulk(ids: &[u64], from: &Layout, to: &Layout) -> Result<Vec<u64>, SnowflakeError> {
+    ids.iter()
+        .map(|&id| repack_layout(id, from, to))
+        .collect()
+}
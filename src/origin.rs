@@ -0,0 +1,114 @@
+use crate::layout::Layout;
+use crate::snowflake::SnowflakeError;
+
+/// The set of node IDs a fleet legitimately uses
+///
+/// Incoming IDs can be checked with `validate_origin` to flag IDs claiming a node that
+/// is not part of the fleet, which catches both forged IDs and misconfigured emitters.
+/// Pass the set to `IdValidator::known_nodes` to check origins wherever IDs are parsed
+/// and validated, e.g. by the `snowflake parse --known-nodes` command.
+/// Node IDs are decoded with the set's `Layout`, so IDs from custom layouts are
+/// supported as well.
+///
+/// # Example
+/// ```
+/// use snowflake_rs_impl::origin::KnownNodes;
+/// use snowflake_rs_impl::snowflake::Snowflake;
+///
+/// let known = KnownNodes::from_nodes([1, 2, 3]).unwrap();
+/// let id = Snowflake::new(2, None).unwrap().generate().unwrap();
+/// assert_eq!(known.validate_origin(id).unwrap(), 2);
+///
+/// let rogue = Snowflake::new(99, None).unwrap().generate().unwrap();
+/// assert!(known.validate_origin(rogue).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KnownNodes {
+    layout: Layout,
+    // One bit per possible node ID
+    bits: Vec<u64>,
+}
+
+impl KnownNodes {
+    /// Creates an empty set for IDs using `layout`
+    pub fn new(layout: Layout) -> Self {
+        let words = (layout.max_node() as usize + 1).div_ceil(64);
+        KnownNodes {
+            layout,
+            bits: vec![0; words],
+        }
+    }
+
+    /// Creates a set of node IDs for the default layout
+    ///
+    /// # Errors
+    ///
+    /// Returns SnowflakeError::MachineIdOutOfRange if a node ID is greater than 1023
+    pub fn from_nodes(nodes: impl IntoIterator<Item = u16>) -> Result<Self, SnowflakeError> {
+        let mut known = KnownNodes::new(Layout::DEFAULT);
+        for node in nodes {
+            known.insert(node)?;
+        }
+        Ok(known)
+    }
+
+    /// Returns the layout used to decode node IDs
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    /// Registers a node ID
+    ///
+    /// # Errors
+    ///
+    /// Returns SnowflakeError::MachineIdOutOfRange if the node ID does not fit in the layout
+    pub fn insert(&mut self, node: u16) -> Result<(), SnowflakeError> {
+        if node > self.layout.max_node() {
+            return Err(SnowflakeError::MachineIdOutOfRange);
+        }
+        self.bits[node as usize / 64] |= 1 << (node % 64);
+        Ok(())
+    }
+
+    /// Unregisters a node ID, returning true if it was registered
+    pub fn remove(&mut self, node: u16) -> bool {
+        let was_known = self.contains(node);
+        if was_known {
+            self.bits[node as usize / 64] &= !(1 << (node % 64));
+        }
+        was_known
+    }
+
+    /// Returns true if the node ID is registered
+    pub fn contains(&self, node: u16) -> bool {
+        node <= self.layout.max_node() && self.bits[node as usize / 64] & (1 << (node % 64)) != 0
+    }
+
+    /// Returns the number of registered node IDs
+    pub fn len(&self) -> usize {
+        self.bits.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    /// Returns true if no node IDs are registered
+    pub fn is_empty(&self) -> bool {
+        self.bits.iter().all(|&word| word == 0)
+    }
+
+    /// Checks that an ID was issued by a registered node
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the ID's node ID or a SnowflakeError
+    ///
+    /// # Errors
+    ///
+    /// Returns SnowflakeError::UnknownNode if the ID's node ID is not registered
+    pub fn validate_origin(&self, id: u64) -> Result<u16, SnowflakeError> {
+        let (_, node, _) = self.layout.decompose(id);
+        if self.contains(node) {
+            Ok(node)
+        } else {
+            Err(SnowflakeError::UnknownNode(node))
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use rayon::prelude::*;
+
+use crate::id::SnowflakeId;
+use crate::snowflake::{Snowflake, SnowflakeError};
+
+impl Snowflake {
+    /// Generates `n` Snowflake IDs in parallel on the current Rayon thread pool
+    ///
+    /// The work is split into chunks of one millisecond's worth of sequence numbers (4096
+    /// IDs with the default layout), or of the rate limit's burst size if that is smaller,
+    /// each generated with `generate_batch`, so the shared atomic state is updated once per
+    /// chunk rather than once per ID. The result is sorted, so IDs are returned in ID order.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number of IDs to generate
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the generated IDs in ascending order or a SnowflakeError
+    ///
+    /// # Errors
+    ///
+    /// Same as `generate`; if any chunk fails, the whole call fails, and the rate limit
+    /// tokens taken by chunks that succeeded are used up
+    pub fn generate_parallel(&self, n: usize) -> Result<Vec<SnowflakeId>, SnowflakeError> {
+        let mut chunk_size = self.layout().max_sequence() as usize + 1;
+        if let Some(rate_limit) = self.rate_limit() {
+            chunk_size = chunk_size.min(rate_limit.burst_size() as usize);
+        }
+        let chunks: Vec<Vec<SnowflakeId>> = (0..n.div_ceil(chunk_size))
+            .into_par_iter()
+            .map(|chunk| {
+                let len = chunk_size.min(n - chunk * chunk_size);
+                self.generate_batch(len)
+            })
+            .collect::<Result<_, _>>()?;
+
+        let mut ids: Vec<SnowflakeId> = chunks.into_iter().flatten().collect();
+        ids.par_sort_unstable();
+        Ok(ids)
+    }
+}
@@ -0,0 +1,131 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::layout::Layout;
+use crate::snowflake::GeneratorSnapshot;
+
+/// Generator state as recorded in a state file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PersistedState {
+    /// The generator state at the time it was written
+    pub snapshot: GeneratorSnapshot,
+    /// True if the state was written by a clean shutdown (`close` or drop), false if it
+    /// was written at startup by a generator that has not shut down since
+    pub clean_shutdown: bool,
+}
+
+/// File-backed store for a generator's last issued timestamp
+///
+/// The file is a small `key=value` text file, replaced atomically on every write:
+///
+/// ```text
+/// node=1
+/// epoch=1609459200000
+/// last_timestamp=1700000000000
+/// last_sequence=12
+/// node_bits=10
+/// step_bits=12
+/// check_bits=0
+/// clean_shutdown=true
+/// ```
+///
+/// Files without `node_bits`/`step_bits` are read as using `Layout::DEFAULT`, and files
+/// without `check_bits` as having no check field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateFile {
+    path: PathBuf,
+}
+
+impl StateFile {
+    /// Creates a state store backed by the file at `path`
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        StateFile { path: path.into() }
+    }
+
+    /// Returns the path of the state file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Reads the state file
+    ///
+    /// # Returns
+    ///
+    /// The persisted state, or None if the file does not exist
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is malformed
+    pub fn load(&self) -> io::Result<Option<PersistedState>> {
+        let contents = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        parse_state(&contents).map(Some)
+    }
+
+    /// Writes the state file
+    ///
+    /// The state is written to a temporary file, synced to disk and then renamed over the
+    /// state file, so a crash mid-write never leaves a truncated file behind.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written
+    pub fn store(&self, state: &PersistedState) -> io::Result<()> {
+        let mut tmp_path = self.path.clone().into_os_string();
+        tmp_path.push(".tmp");
+        let tmp_path = PathBuf::from(tmp_path);
+
+        let mut file = fs::File::create(&tmp_path)?;
+        write!(
+            file,
+            "node={}\nepoch={}\nlast_timestamp={}\nlast_sequence={}\nnode_bits={}\nstep_bits={}\ncheck_bits={}\nclean_shutdown={}\n",
+            state.snapshot.node,
+            state.snapshot.epoch,
+            state.snapshot.last_timestamp,
+            state.snapshot.last_sequence,
+            state.snapshot.layout.node_bits(),
+            state.snapshot.layout.step_bits(),
+            state.snapshot.layout.check_bits(),
+            state.clean_shutdown,
+        )?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, &self.path)
+    }
+}
+
+// Parses the key=value contents of a state file
+fn parse_state(contents: &str) -> io::Result<PersistedState> {
+    fn field<T: std::str::FromStr>(contents: &str, key: &str) -> io::Result<T> {
+        contents
+            .lines()
+            .filter_map(|line| line.split_once('='))
+            .find(|(name, _)| name.trim() == key)
+            .and_then(|(_, value)| value.trim().parse().ok())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("missing or invalid `{}` in state file", key)))
+    }
+
+    let layout = if contents.contains("node_bits=") {
+        Layout::new(field(contents, "node_bits")?, field(contents, "step_bits")?)
+    } else {
+        Ok(Layout::DEFAULT)
+    };
+    // Files written before check fields existed have no `check_bits`
+    let check_bits = if contents.contains("check_bits=") { field(contents, "check_bits")? } else { 0 };
+    let layout = layout
+        .and_then(|layout| layout.with_check_bits(check_bits))
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+    Ok(PersistedState {
+        snapshot: GeneratorSnapshot {
+            node: field(contents, "node")?,
+            epoch: field(contents, "epoch")?,
+            last_timestamp: field(contents, "last_timestamp")?,
+            last_sequence: field(contents, "last_sequence")?,
+            layout,
+        },
+        clean_shutdown: field(contents, "clean_shutdown")?,
+    })
+}
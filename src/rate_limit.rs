@@ -0,0 +1,159 @@
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::snowflake::SnowflakeError;
+use crate::sync::{AtomicUsize, Ordering};
+
+/// What a rate-limited generator does when no token is available
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ThrottleMode {
+    /// Return SnowflakeError::Throttled immediately
+    Error,
+    /// Sleep until a token becomes available
+    Wait,
+}
+
+/// Token-bucket rate limit for ID generation
+///
+/// The bucket holds up to `burst` tokens and refills at `max_per_second` tokens per
+/// second. Each generated ID consumes one token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimit {
+    max_per_second: u32,
+    burst: u32,
+    mode: ThrottleMode,
+}
+
+impl RateLimit {
+    /// Creates a rate limit of `max_per_second` IDs per second
+    ///
+    /// The burst size defaults to `max_per_second` and the mode to `ThrottleMode::Error`.
+    pub fn per_second(max_per_second: u32) -> Self {
+        RateLimit {
+            max_per_second,
+            burst: max_per_second,
+            mode: ThrottleMode::Error,
+        }
+    }
+
+    /// Sets the maximum number of IDs that can be generated back-to-back
+    pub fn burst(mut self, burst: u32) -> Self {
+        self.burst = burst;
+        self
+    }
+
+    /// Sets what happens when the limit is reached
+    pub fn mode(mut self, mode: ThrottleMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Returns the sustained rate in IDs per second
+    pub fn max_per_second(&self) -> u32 {
+        self.max_per_second
+    }
+
+    /// Returns the burst size
+    pub fn burst_size(&self) -> u32 {
+        self.burst
+    }
+
+    /// Returns the throttle mode
+    pub fn throttle_mode(&self) -> ThrottleMode {
+        self.mode
+    }
+}
+
+// Current token count and the last time the bucket was refilled
+struct BucketState {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+// Token bucket enforcing a RateLimit
+pub(crate) struct TokenBucket {
+    limit: RateLimit,
+    state: Mutex<BucketState>,
+    waiting: AtomicUsize,
+}
+
+impl TokenBucket {
+    // Creates a full bucket for the given limit
+    pub(crate) fn new(limit: RateLimit) -> Result<Self, SnowflakeError> {
+        if limit.max_per_second == 0 || limit.burst == 0 {
+            return Err(SnowflakeError::InvalidRateLimit);
+        }
+        Ok(TokenBucket {
+            limit,
+            state: Mutex::new(BucketState {
+                tokens: limit.burst as f64,
+                last_refill: Instant::now(),
+            }),
+            waiting: AtomicUsize::new(0),
+        })
+    }
+
+    // Returns the limit this bucket enforces
+    pub(crate) fn limit(&self) -> RateLimit {
+        self.limit
+    }
+
+    // Returns true if a caller is sleeping until a token becomes available
+    pub(crate) fn is_waiting(&self) -> bool {
+        self.waiting.load(Ordering::Relaxed) > 0
+    }
+
+    // Takes one token, waiting or failing according to the throttle mode
+    pub(crate) fn acquire(&self) -> Result<(), SnowflakeError> {
+        self.acquire_many(1)
+    }
+
+    // Takes `n` tokens at once, waiting or failing according to the throttle mode. Either
+    // all of them are taken or none.
+    pub(crate) fn acquire_many(&self, n: usize) -> Result<(), SnowflakeError> {
+        if n > self.limit.burst as usize {
+            return Err(SnowflakeError::BatchExceedsBurst);
+        }
+        loop {
+            let wait = match self.take(n as f64) {
+                Ok(()) => return Ok(()),
+                Err(wait) => wait,
+            };
+            match self.limit.mode {
+                ThrottleMode::Error => return Err(SnowflakeError::Throttled),
+                ThrottleMode::Wait => {
+                    self.waiting.fetch_add(1, Ordering::Relaxed);
+                    std::thread::sleep(wait);
+                    self.waiting.fetch_sub(1, Ordering::Relaxed);
+                }
+            }
+        }
+    }
+
+    // Takes one token if one is available, whatever the throttle mode
+    pub(crate) fn try_acquire(&self) -> bool {
+        self.take(1.0).is_ok()
+    }
+
+    // Puts back `n` tokens taken for IDs that were not issued
+    pub(crate) fn release(&self, n: usize) {
+        let mut state = self.state.lock();
+        state.tokens = (state.tokens + n as f64).min(self.limit.burst as f64);
+    }
+
+    // Refills the bucket and takes `n` tokens, or returns how long until they are available
+    fn take(&self, n: f64) -> Result<(), Duration> {
+        let mut state = self.state.lock();
+        let now = Instant::now();
+        let refill = now.duration_since(state.last_refill).as_secs_f64() * self.limit.max_per_second as f64;
+        state.tokens = (state.tokens + refill).min(self.limit.burst as f64);
+        state.last_refill = now;
+        if state.tokens >= n {
+            state.tokens -= n;
+            return Ok(());
+        }
+        Err(Duration::from_secs_f64((n - state.tokens) / self.limit.max_per_second as f64))
+    }
+}
@@ -0,0 +1,162 @@
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::OnceLock;
+
+use crate::id::SnowflakeId;
+use crate::snowflake::SnowflakeError;
+
+/// Number of hex digits shown by `Redacted` (48 bits of the digest)
+const REDACTED_DIGITS: usize = 12;
+
+// Key used by `SnowflakeId::redacted`, set by `RedactionKey::install` or randomly on first use
+static GLOBAL_KEY: OnceLock<RedactionKey> = OnceLock::new();
+
+/// Secret key for redacting IDs in logs
+///
+/// IDs are hashed with SipHash-2-4 under this key, so the same ID always renders the same
+/// way under the same key, but the raw ID cannot be recovered (or the ID space
+/// enumerated) without the key. Services whose logs need to be correlated must share the
+/// key; keep it out of the logs themselves.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct RedactionKey {
+    k0: u64,
+    k1: u64,
+}
+
+impl RedactionKey {
+    /// Creates a key from 16 secret bytes
+    pub const fn new(key: [u8; 16]) -> Self {
+        let [a, b, c, d, e, f, g, h, i, j, k, l, m, n, o, p] = key;
+        RedactionKey {
+            k0: u64::from_le_bytes([a, b, c, d, e, f, g, h]),
+            k1: u64::from_le_bytes([i, j, k, l, m, n, o, p]),
+        }
+    }
+
+    /// Creates a random key, e.g. for redaction that only needs to be stable within a process
+    pub fn random() -> Self {
+        RedactionKey {
+            k0: RandomState::new().build_hasher().finish(),
+            k1: RandomState::new().build_hasher().finish(),
+        }
+    }
+
+    /// Makes this the key used by `SnowflakeId::redacted`
+    ///
+    /// # Errors
+    ///
+    /// Returns SnowflakeError::RedactionKeyAlreadySet if a key has already been installed,
+    /// or `SnowflakeId::redacted` has already been called and picked a random key
+    pub fn install(self) -> Result<(), SnowflakeError> {
+        GLOBAL_KEY.set(self).map_err(|_| SnowflakeError::RedactionKeyAlreadySet)
+    }
+
+    /// Returns the key used by `SnowflakeId::redacted`
+    ///
+    /// If no key has been installed, a random key is picked on first use, so redacted IDs
+    /// are only stable within the current process.
+    pub fn global() -> RedactionKey {
+        *GLOBAL_KEY.get_or_init(RedactionKey::random)
+    }
+
+    // SipHash-2-4 of the ID's 8 little-endian bytes
+    fn digest(&self, id: u64) -> u64 {
+        let mut state = [
+            self.k0 ^ 0x736f_6d65_7073_6575,
+            self.k1 ^ 0x646f_7261_6e64_6f6d,
+            self.k0 ^ 0x6c79_6765_6e65_7261,
+            self.k1 ^ 0x7465_6462_7974_6573,
+        ];
+        // One message word, then the final word carrying the message length (8 bytes)
+        for word in [id, 8 << 56] {
+            state[3] ^= word;
+            sip_round(&mut state);
+            sip_round(&mut state);
+            state[0] ^= word;
+        }
+        state[2] ^= 0xff;
+        for _ in 0..4 {
+            sip_round(&mut state);
+        }
+        state[0] ^ state[1] ^ state[2] ^ state[3]
+    }
+}
+
+impl fmt::Debug for RedactionKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RedactionKey").finish_non_exhaustive()
+    }
+}
+
+fn sip_round(v: &mut [u64; 4]) {
+    v[0] = v[0].wrapping_add(v[1]);
+    v[1] = v[1].rotate_left(13) ^ v[0];
+    v[0] = v[0].rotate_left(32);
+    v[2] = v[2].wrapping_add(v[3]);
+    v[3] = v[3].rotate_left(16) ^ v[2];
+    v[0] = v[0].wrapping_add(v[3]);
+    v[3] = v[3].rotate_left(21) ^ v[0];
+    v[2] = v[2].wrapping_add(v[1]);
+    v[1] = v[1].rotate_left(17) ^ v[2];
+    v[2] = v[2].rotate_left(32);
+}
+
+/// An ID rendered as a keyed, stable short hash for log output
+///
+/// Displays (and debug-prints) as `~` followed by 12 hex digits, e.g. `~3f9a1c0b2d4e`.
+/// Equal IDs under the same key always render the same, so log lines stay correlatable.
+///
+/// # Example
+/// ```
+/// use snowflake_rs_impl::id::SnowflakeId;
+/// use snowflake_rs_impl::redact::RedactionKey;
+///
+/// let key = RedactionKey::new(*b"0123456789abcdef");
+/// let id = SnowflakeId::from_u64(1234567890);
+/// let redacted = id.redacted_with(&key).to_string();
+/// assert_eq!(redacted, id.redacted_with(&key).to_string());
+/// assert!(!redacted.contains("1234567890"));
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Redacted {
+    digest: u64,
+}
+
+impl Redacted {
+    /// Redacts an ID under `key`
+    pub fn new(id: SnowflakeId, key: &RedactionKey) -> Self {
+        Redacted {
+            digest: key.digest(id.as_u64()),
+        }
+    }
+
+    /// Returns the full 64-bit SipHash-2-4 digest; the display shows its top 48 bits
+    pub fn digest(&self) -> u64 {
+        self.digest
+    }
+}
+
+impl fmt::Display for Redacted {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "~{:0width$x}", self.digest >> (64 - 4 * REDACTED_DIGITS), width = REDACTED_DIGITS)
+    }
+}
+
+impl fmt::Debug for Redacted {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl SnowflakeId {
+    /// Returns the ID redacted under the global key (see `RedactionKey::global`)
+    pub fn redacted(&self) -> Redacted {
+        Redacted::new(*self, &RedactionKey::global())
+    }
+
+    /// Returns the ID redacted under `key`
+    pub fn redacted_with(&self, key: &RedactionKey) -> Redacted {
+        Redacted::new(*self, key)
+    }
+}
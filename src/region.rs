@@ -0,0 +1,148 @@
+use std::collections::BTreeMap;
+
+use crate::layout::Layout;
+use crate::snowflake::SnowflakeError;
+
+/// Maps region names to the region bits carved out of the node field
+///
+/// The top `region_bits` bits of the node field hold the region and the remaining bits
+/// the node within the region, so every ID records the region it was issued in. Build
+/// generators with `SnowflakeBuilder::region`, and parse IDs with `region_of`.
+///
+/// # Example
+/// ```
+/// use std::sync::Arc;
+/// use snowflake_rs_impl::layout::Layout;
+/// use snowflake_rs_impl::region::RegionRegistry;
+/// use snowflake_rs_impl::snowflake::Snowflake;
+///
+/// // 2 of the 10 node bits for the region: 4 regions of 256 nodes each
+/// let mut regions = RegionRegistry::new(Layout::DEFAULT, 2).unwrap();
+/// regions.register("us-east", 0).unwrap();
+/// regions.register("eu-west", 1).unwrap();
+/// let regions = Arc::new(regions);
+///
+/// let snowflake = Snowflake::builder(7).region(Arc::clone(&regions), "eu-west").build().unwrap();
+/// let id = snowflake.generate().unwrap();
+/// assert_eq!(regions.region_of(id), Some("eu-west"));
+/// assert_eq!(regions.local_node(id), 7);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegionRegistry {
+    layout: Layout,
+    region_bits: u8,
+    by_name: BTreeMap<String, u16>,
+    by_value: BTreeMap<u16, String>,
+}
+
+impl RegionRegistry {
+    /// Creates an empty registry for IDs using `layout`
+    ///
+    /// # Errors
+    ///
+    /// Returns SnowflakeError::InvalidRegionConfig if `region_bits` is zero or leaves no
+    /// bits of the node field for nodes
+    pub fn new(layout: Layout, region_bits: u8) -> Result<Self, SnowflakeError> {
+        if region_bits == 0 || region_bits >= layout.node_bits() {
+            return Err(SnowflakeError::InvalidRegionConfig);
+        }
+        Ok(RegionRegistry {
+            layout,
+            region_bits,
+            by_name: BTreeMap::new(),
+            by_value: BTreeMap::new(),
+        })
+    }
+
+    /// Returns the layout of the IDs
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    /// Returns the number of region bits
+    pub fn region_bits(&self) -> u8 {
+        self.region_bits
+    }
+
+    /// Returns the largest region value
+    pub fn max_region(&self) -> u16 {
+        (1 << self.region_bits) - 1
+    }
+
+    /// Returns the largest node ID within a region
+    pub fn max_local_node(&self) -> u16 {
+        (1 << self.local_node_bits()) - 1
+    }
+
+    /// Registers a region name with its region value
+    ///
+    /// # Errors
+    ///
+    /// Returns SnowflakeError::InvalidRegionConfig if the value does not fit in the region
+    /// bits, or the name or value is already registered
+    pub fn register(&mut self, name: &str, value: u16) -> Result<(), SnowflakeError> {
+        if value > self.max_region() || self.by_name.contains_key(name) || self.by_value.contains_key(&value) {
+            return Err(SnowflakeError::InvalidRegionConfig);
+        }
+        self.by_name.insert(name.to_string(), value);
+        self.by_value.insert(value, name.to_string());
+        Ok(())
+    }
+
+    /// Returns the region value of a name
+    pub fn value(&self, name: &str) -> Option<u16> {
+        self.by_name.get(name).copied()
+    }
+
+    /// Returns the name of a region value
+    pub fn name(&self, value: u16) -> Option<&str> {
+        self.by_value.get(&value).map(String::as_str)
+    }
+
+    /// Returns the registered regions, ordered by name
+    pub fn regions(&self) -> impl Iterator<Item = (&str, u16)> {
+        self.by_name.iter().map(|(name, &value)| (name.as_str(), value))
+    }
+
+    /// Returns the full node ID of a node within a region
+    ///
+    /// # Errors
+    ///
+    /// - SnowflakeError::UnknownRegion if the region is not registered
+    /// - SnowflakeError::MachineIdOutOfRange if the node does not fit in the bits left
+    ///   for nodes
+    pub fn node_id(&self, region: &str, local_node: u16) -> Result<u16, SnowflakeError> {
+        let value = self.value(region).ok_or_else(|| SnowflakeError::UnknownRegion(region.to_string()))?;
+        if local_node > self.max_local_node() {
+            return Err(SnowflakeError::MachineIdOutOfRange);
+        }
+        Ok((value << self.local_node_bits()) | local_node)
+    }
+
+    /// Splits a full node ID into its region value and the node within the region
+    pub fn split_node(&self, node: u16) -> (u16, u16) {
+        (node >> self.local_node_bits(), node & self.max_local_node())
+    }
+
+    /// Returns the region value of an ID
+    pub fn region_value(&self, id: u64) -> u16 {
+        let (_, node, _) = self.layout.decompose(id);
+        self.split_node(node).0
+    }
+
+    /// Returns the region name of an ID, or None if its region value is not registered
+    pub fn region_of(&self, id: u64) -> Option<&str> {
+        self.name(self.region_value(id))
+    }
+
+    /// Returns the node within the region of an ID
+    pub fn local_node(&self, id: u64) -> u16 {
+        let (_, node, _) = self.layout.decompose(id);
+        self.split_node(node).1
+    }
+
+    // Bits of the node field left for nodes within a region
+    fn local_node_bits(&self) -> u8 {
+        self.layout.node_bits() - self.region_bits
+    }
+}
@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+
+use crate::snowflake::{Snowflake, SnowflakeError, NODE_MAX};
+
+// Per-name configuration and the generator, once it has been created
+struct Entry {
+    node: u16,
+    epoch: Option<i64>,
+    generator: Option<Arc<Snowflake>>,
+}
+
+/// A registry of named Snowflake generators
+///
+/// Each name (typically a tenant) gets its own `Snowflake` instance, created lazily on
+/// first use. Names without an explicit configuration use the registry's default node ID
+/// and epoch.
+///
+/// IDs are only unique per generator: two names sharing the same node ID and epoch can
+/// produce the same IDs. Give names distinct node IDs via `configure` if their IDs end up
+/// in the same ID space.
+pub struct GeneratorRegistry {
+    default_node: u16,
+    default_epoch: Option<i64>,
+    entries: RwLock<HashMap<String, Entry>>,
+}
+
+impl GeneratorRegistry {
+    /// Creates a new, empty registry
+    ///
+    /// # Arguments
+    ///
+    /// * `default_node` - The node ID used for names without an explicit configuration (0-1023)
+    /// * `default_epoch` - The epoch used for names without an explicit configuration.
+    ///   If None, DEFAULT_EPOCH is used.
+    ///
+    /// # Errors
+    ///
+    /// Returns SnowflakeError::MachineIdOutOfRange if the default node ID is greater than 1023
+    pub fn new(default_node: u16, default_epoch: Option<i64>) -> Result<Self, SnowflakeError> {
+        if default_node > NODE_MAX {
+            return Err(SnowflakeError::MachineIdOutOfRange);
+        }
+        Ok(GeneratorRegistry {
+            default_node,
+            default_epoch,
+            entries: RwLock::new(HashMap::new()),
+        })
+    }
+
+    /// Sets the node ID and epoch for a name
+    ///
+    /// `None` fields fall back to the registry defaults. The generator itself is still
+    /// created lazily by `get`.
+    ///
+    /// # Errors
+    ///
+    /// - SnowflakeError::MachineIdOutOfRange if the node ID is greater than 1023
+    /// - SnowflakeError::GeneratorAlreadyInitialized if the generator for this name has
+    ///   already been created; reconfiguring it could re-issue IDs
+    pub fn configure(&self, name: &str, node: Option<u16>, epoch: Option<i64>) -> Result<(), SnowflakeError> {
+        let node = node.unwrap_or(self.default_node);
+        if node > NODE_MAX {
+            return Err(SnowflakeError::MachineIdOutOfRange);
+        }
+        let mut entries = self.entries.write();
+        if entries.get(name).is_some_and(|entry| entry.generator.is_some()) {
+            return Err(SnowflakeError::GeneratorAlreadyInitialized);
+        }
+        entries.insert(name.to_string(), Entry {
+            node,
+            epoch: epoch.or(self.default_epoch),
+            generator: None,
+        });
+        Ok(())
+    }
+
+    /// Returns the generator for a name, creating it on first use
+    ///
+    /// # Errors
+    ///
+    /// Returns any error from `Snowflake::new` for the name's configuration
+    pub fn get(&self, name: &str) -> Result<Arc<Snowflake>, SnowflakeError> {
+        if let Some(generator) = self.entries.read().get(name).and_then(|entry| entry.generator.as_ref()) {
+            return Ok(Arc::clone(generator));
+        }
+
+        let mut entries = self.entries.write();
+        let entry = entries.entry(name.to_string()).or_insert_with(|| Entry {
+            node: self.default_node,
+            epoch: self.default_epoch,
+            generator: None,
+        });
+        if let Some(generator) = &entry.generator {
+            return Ok(Arc::clone(generator));
+        }
+        let generator = Arc::new(Snowflake::new(entry.node, entry.epoch)?);
+        entry.generator = Some(Arc::clone(&generator));
+        Ok(generator)
+    }
+
+    /// Generates a new Snowflake ID from the generator for a name
+    ///
+    /// # Errors
+    ///
+    /// Same as `get` and `Snowflake::generate`
+    pub fn generate(&self, name: &str) -> Result<u64, SnowflakeError> {
+        self.get(name)?.generate()
+    }
+
+    /// Removes a name and its configuration from the registry
+    ///
+    /// Returns the generator if it had been created. Existing `Arc` handles keep working.
+    pub fn remove(&self, name: &str) -> Option<Arc<Snowflake>> {
+        self.entries.write().remove(name).and_then(|entry| entry.generator)
+    }
+
+    /// Returns true if the name has been configured or used
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.read().contains_key(name)
+    }
+
+    /// Returns the number of names in the registry
+    pub fn len(&self) -> usize {
+        self.entries.read().len()
+    }
+
+    /// Returns true if the registry has no names
+    pub fn is_empty(&self) -> bool {
+        self.entries.read().is_empty()
+    }
+}
@@ -0,0 +1,343 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread::{self, Thread};
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+
+use crate::generator::{IdError, IdGenerator};
+use crate::id::SnowflakeId;
+use crate::snowflake::{Snowflake, SnowflakeError};
+
+/// Default number of IDs fetched per request to the server
+pub const DEFAULT_BLOCK_SIZE: usize = 1000;
+
+/// Default buffer level at which the next block is fetched
+pub const DEFAULT_LOW_WATER: usize = 250;
+
+/// Default wait before retrying a failed fetch
+pub const DEFAULT_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A remote source of ID blocks, e.g. a gRPC or HTTP client for an ID service
+///
+/// The crate does not ship a transport: implement this for the client of whatever
+/// service hands out IDs. On the server side, `Snowflake` implements it with
+/// `generate_batch`, and so does any `Fn(usize) -> Result<Vec<SnowflakeId>, SnowflakeError>`.
+pub trait IdBlockSource: Send + Sync {
+    /// Fetches up to `count` unused IDs in ascending order
+    ///
+    /// `RemoteSnowflakeClient` treats an empty block like a failed fetch and retries after
+    /// its retry interval.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error (typically SnowflakeError::RemoteUnavailable) if the server
+    /// cannot be reached
+    fn fetch_block(&self, count: usize) -> Result<Vec<SnowflakeId>, SnowflakeError>;
+}
+
+impl IdBlockSource for Snowflake {
+    fn fetch_block(&self, count: usize) -> Result<Vec<SnowflakeId>, SnowflakeError> {
+        self.generate_batch(count)
+    }
+}
+
+impl<F> IdBlockSource for F
+where
+    F: Fn(usize) -> Result<Vec<SnowflakeId>, SnowflakeError> + Send + Sync,
+{
+    fn fetch_block(&self, count: usize) -> Result<Vec<SnowflakeId>, SnowflakeError> {
+        self(count)
+    }
+}
+
+/// Builder for `RemoteSnowflakeClient`
+pub struct RemoteSnowflakeClientBuilder {
+    source: Arc<dyn IdBlockSource>,
+    block_size: usize,
+    low_water: usize,
+    retry_interval: Duration,
+    fallback: Option<Snowflake>,
+}
+
+impl RemoteSnowflakeClientBuilder {
+    /// Sets how many IDs are fetched per request. Defaults to `DEFAULT_BLOCK_SIZE`.
+    pub fn block_size(mut self, block_size: usize) -> Self {
+        self.block_size = block_size;
+        self
+    }
+
+    /// Sets the buffer level at which the next block is fetched in the background.
+    /// Defaults to `DEFAULT_LOW_WATER`.
+    ///
+    /// Size it to cover the IDs used during one round-trip to the server.
+    pub fn low_water(mut self, low_water: usize) -> Self {
+        self.low_water = low_water;
+        self
+    }
+
+    /// Sets how long to wait before retrying a failed fetch. Defaults to
+    /// `DEFAULT_RETRY_INTERVAL` (one second).
+    pub fn retry_interval(mut self, retry_interval: Duration) -> Self {
+        self.retry_interval = retry_interval;
+        self
+    }
+
+    /// Sets a local generator to fall back to while the buffer is empty
+    ///
+    /// Its node ID must be distinct from every node ID the server hands out, so the
+    /// fallback IDs never collide with remote ones; blocks holding IDs of the fallback's
+    /// node are rejected. The server must use the fallback's epoch and layout, since
+    /// server IDs are decoded with that layout for this check.
+    pub fn fallback(mut self, fallback: Snowflake) -> Self {
+        self.fallback = Some(fallback);
+        self
+    }
+
+    /// Fetches the first block and starts the background refill
+    ///
+    /// # Errors
+    ///
+    /// - SnowflakeError::InvalidPrefetchConfig if the block size is 0 or the low-water
+    ///   mark is not below it
+    /// - The fetch error if the first block cannot be fetched and there is no fallback
+    pub fn build(self) -> Result<RemoteSnowflakeClient, SnowflakeError> {
+        if self.block_size == 0 || self.low_water >= self.block_size {
+            return Err(SnowflakeError::InvalidPrefetchConfig);
+        }
+        let shared = Arc::new(Shared {
+            source: self.source,
+            block_size: self.block_size,
+            low_water: self.low_water,
+            retry_interval: self.retry_interval,
+            fallback: self.fallback,
+            buffer: Mutex::new(VecDeque::with_capacity(self.block_size * 2)),
+            last_error: Mutex::new(None),
+            fallback_ids: AtomicU64::new(0),
+            stop: AtomicBool::new(false),
+        });
+        if let Err(err) = shared.refill() {
+            if shared.fallback.is_none() {
+                return Err(err);
+            }
+        }
+        let worker = {
+            let shared = Arc::clone(&shared);
+            thread::Builder::new()
+                .name("snowflake-prefetch".to_string())
+                .spawn(move || shared.run())
+                .expect("Failed to spawn the prefetch thread")
+        };
+        Ok(RemoteSnowflakeClient {
+            shared,
+            worker: worker.thread().clone(),
+        })
+    }
+}
+
+impl fmt::Debug for RemoteSnowflakeClientBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RemoteSnowflakeClientBuilder")
+            .field("block_size", &self.block_size)
+            .field("low_water", &self.low_water)
+            .field("retry_interval", &self.retry_interval)
+            .field("fallback", &self.fallback.as_ref().map(Snowflake::node))
+            .finish_non_exhaustive()
+    }
+}
+
+/// ID generator serving IDs fetched in blocks from a central ID service
+///
+/// Node IDs are managed centrally by the service, without a network round-trip per ID:
+/// the client keeps a local buffer of prefetched IDs and, once it drops to the low-water
+/// mark, a background thread fetches the next block. While the buffer is empty (the
+/// server is unreachable or not keeping up), IDs come from the fallback generator if one
+/// is set, and otherwise `next_id` fails. Failed fetches are retried every
+/// `retry_interval`.
+///
+/// IDs from one block are ascending, but fallback IDs interleave with remote ones, so the
+/// client as a whole does not guarantee ascending IDs.
+///
+/// # Example
+/// ```
+/// use std::sync::Arc;
+/// use snowflake_rs_impl::generator::IdGenerator;
+/// use snowflake_rs_impl::remote::RemoteSnowflakeClient;
+/// use snowflake_rs_impl::snowflake::Snowflake;
+///
+/// // Stands in for a gRPC/HTTP client of the central service
+/// let server = Arc::new(Snowflake::new(1, None).unwrap());
+/// let client = RemoteSnowflakeClient::builder(server)
+///     .block_size(100)
+///     .low_water(20)
+///     .fallback(Snowflake::new(1023, None).unwrap())
+///     .build()
+///     .unwrap();
+/// assert_eq!(client.next_id().unwrap().node(), 1);
+/// ```
+pub struct RemoteSnowflakeClient {
+    shared: Arc<Shared>,
+    worker: Thread,
+}
+
+// State shared between the client and its prefetch thread
+struct Shared {
+    source: Arc<dyn IdBlockSource>,
+    block_size: usize,
+    low_water: usize,
+    retry_interval: Duration,
+    fallback: Option<Snowflake>,
+    buffer: Mutex<VecDeque<SnowflakeId>>,
+    last_error: Mutex<Option<String>>,
+    fallback_ids: AtomicU64,
+    stop: AtomicBool,
+}
+
+impl RemoteSnowflakeClient {
+    /// Returns a builder for a client fetching ID blocks from `source`
+    pub fn builder(source: Arc<dyn IdBlockSource>) -> RemoteSnowflakeClientBuilder {
+        RemoteSnowflakeClientBuilder {
+            source,
+            block_size: DEFAULT_BLOCK_SIZE,
+            low_water: DEFAULT_LOW_WATER,
+            retry_interval: DEFAULT_RETRY_INTERVAL,
+            fallback: None,
+        }
+    }
+
+    /// Returns the next ID, from the buffer or, if it is empty, the fallback generator
+    ///
+    /// # Errors
+    ///
+    /// - SnowflakeError::RemoteUnavailable if the buffer is empty and there is no fallback
+    /// - Same as `Snowflake::generate` for fallback IDs
+    pub fn generate_id(&self) -> Result<SnowflakeId, SnowflakeError> {
+        let (id, remaining) = {
+            let mut buffer = self.shared.buffer.lock();
+            (buffer.pop_front(), buffer.len())
+        };
+        if remaining <= self.shared.low_water {
+            self.worker.unpark();
+        }
+        if let Some(id) = id {
+            return Ok(id);
+        }
+        match &self.shared.fallback {
+            Some(fallback) => {
+                self.shared.fallback_ids.fetch_add(1, Ordering::Relaxed);
+                fallback.generate_id()
+            }
+            None => Err(SnowflakeError::RemoteUnavailable(
+                self.last_error().unwrap_or_else(|| "prefetch buffer is empty".to_string()),
+            )),
+        }
+    }
+
+    /// Returns the number of prefetched IDs left in the buffer
+    pub fn buffered(&self) -> usize {
+        self.shared.buffer.lock().len()
+    }
+
+    /// Returns how many IDs have come from the fallback generator so far
+    pub fn fallback_ids(&self) -> u64 {
+        self.shared.fallback_ids.load(Ordering::Relaxed)
+    }
+
+    /// Returns the error of the last fetch, or None if it succeeded
+    pub fn last_error(&self) -> Option<String> {
+        self.shared.last_error.lock().clone()
+    }
+
+    /// Returns the fallback generator, if any
+    pub fn fallback(&self) -> Option<&Snowflake> {
+        self.shared.fallback.as_ref()
+    }
+}
+
+impl Shared {
+    // Refills the buffer whenever it is at or below the low-water mark, until stopped
+    fn run(&self) {
+        while !self.stop.load(Ordering::Acquire) {
+            if self.buffer.lock().len() > self.low_water {
+                thread::park();
+                continue;
+            }
+            if self.refill().is_err() {
+                self.wait_to_retry();
+            }
+        }
+    }
+
+    // Waits out the retry interval, even if callers unpark the thread meanwhile
+    fn wait_to_retry(&self) {
+        let retry_at = Instant::now() + self.retry_interval;
+        while !self.stop.load(Ordering::Acquire) {
+            let now = Instant::now();
+            if now >= retry_at {
+                return;
+            }
+            thread::park_timeout(retry_at - now);
+        }
+    }
+
+    // Fetches one block into the buffer, recording the outcome
+    fn refill(&self) -> Result<(), SnowflakeError> {
+        match self.source.fetch_block(self.block_size).and_then(|ids| self.check_block(ids)) {
+            Ok(ids) => {
+                self.buffer.lock().extend(ids);
+                *self.last_error.lock() = None;
+                Ok(())
+            }
+            Err(err) => {
+                *self.last_error.lock() = Some(err.to_string());
+                Err(err)
+            }
+        }
+    }
+
+    // Rejects an empty block, which would make `run` fetch again at once, and a block
+    // holding IDs of the fallback's node, which could collide with fallback IDs
+    fn check_block(&self, ids: Vec<SnowflakeId>) -> Result<Vec<SnowflakeId>, SnowflakeError> {
+        if ids.is_empty() {
+            return Err(SnowflakeError::RemoteUnavailable("server returned an empty block".to_string()));
+        }
+        let Some(fallback) = &self.fallback else {
+            return Ok(ids);
+        };
+        let layout = fallback.layout();
+        if ids.iter().any(|id| layout.decompose(id.as_u64()).1 == fallback.node()) {
+            return Err(SnowflakeError::RemoteUnavailable(format!(
+                "server handed out IDs of fallback node {}",
+                fallback.node()
+            )));
+        }
+        Ok(ids)
+    }
+}
+
+impl IdGenerator for RemoteSnowflakeClient {
+    fn next_id(&self) -> Result<SnowflakeId, IdError> {
+        self.generate_id()
+    }
+}
+
+impl fmt::Debug for RemoteSnowflakeClient {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RemoteSnowflakeClient")
+            .field("block_size", &self.shared.block_size)
+            .field("low_water", &self.shared.low_water)
+            .field("buffered", &self.buffered())
+            .field("fallback", &self.shared.fallback.as_ref().map(Snowflake::node))
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for RemoteSnowflakeClient {
+    fn drop(&mut self) {
+        // The thread is not joined: it may be blocked in a fetch, and exits once it returns
+        self.shared.stop.store(true, Ordering::Release);
+        self.worker.unpark();
+    }
+}
@@ -0,0 +1,38 @@
+use scylla_cql::frame::response::cql_to_rust::{FromCqlVal, FromCqlValError};
+use scylla_cql::frame::response::result::{ColumnType, CqlValue};
+use scylla_cql::types::serialize::value::SerializeValue;
+use scylla_cql::types::serialize::writers::{CellWriter, WrittenCellProof};
+use scylla_cql::types::serialize::SerializationError;
+
+use crate::id::SnowflakeId;
+use crate::snowflake::SnowflakeError;
+
+/// Binds the ID as a CQL `bigint`
+///
+/// CQL has no unsigned integers, so the ID is sent as a signed `bigint`; IDs with the top
+/// bit clear sort the same either way. IDs with the top bit set (second-era IDs, see
+/// `ExhaustionStrategy::Era`) would turn negative, so they fail with
+/// SnowflakeError::InvalidCqlValue. Binding to a column of another type fails with the
+/// driver's type-check error.
+impl SerializeValue for SnowflakeId {
+    fn serialize<'b>(
+        &self,
+        typ: &ColumnType,
+        writer: CellWriter<'b>,
+    ) -> Result<WrittenCellProof<'b>, SerializationError> {
+        let id = i64::try_from(self.as_u64()).map_err(|_| SerializationError::new(SnowflakeError::InvalidCqlValue))?;
+        id.serialize(typ, writer)
+    }
+}
+
+/// Reads the ID from a CQL `bigint`
+///
+/// Fails with `FromCqlValError::BadCqlType` if the value is not a `bigint`, and with
+/// `FromCqlValError::BadVal` if it is negative. Nullable columns read as
+/// `Option<SnowflakeId>`.
+impl FromCqlVal<CqlValue> for SnowflakeId {
+    fn from_cql(cql_val: CqlValue) -> Result<Self, FromCqlValError> {
+        let id = cql_val.as_bigint().ok_or(FromCqlValError::BadCqlType)?;
+        u64::try_from(id).map(SnowflakeId::from).map_err(|_| FromCqlValError::BadVal)
+    }
+}
@@ -0,0 +1,155 @@
+use std::time::{Duration, Instant};
+
+use crate::snowflake::{Snowflake, SnowflakeError};
+
+/// How long `Snowflake::self_test` checks that IDs keep increasing
+pub const DEFAULT_SELF_TEST_WINDOW: Duration = Duration::from_millis(20);
+
+/// Outcome of `Snowflake::self_test`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelfTestReport {
+    /// Smallest step between two distinct clock readings, or None if the clock did not
+    /// advance during the test
+    pub clock_resolution: Option<Duration>,
+    /// Number of IDs generated while checking for monotonic progression
+    pub ids_checked: u64,
+    /// True if every ID was greater than the previous one and the clock never went back
+    pub monotonic: bool,
+    /// Number of IDs generated back-to-back during the burst
+    pub burst_size: u64,
+    /// IDs per second achieved during the burst
+    pub throughput_per_sec: f64,
+    /// Problems worth looking into before taking traffic
+    pub warnings: Vec<String>,
+}
+
+impl SelfTestReport {
+    /// Returns true if no problems were found
+    pub fn is_ok(&self) -> bool {
+        self.warnings.is_empty()
+    }
+}
+
+impl Snowflake {
+    /// Checks the generator at startup, before it takes traffic
+    ///
+    /// Measures the resolution of the generator's clock, generates IDs for
+    /// `DEFAULT_SELF_TEST_WINDOW` to verify that they keep increasing, and times a burst of
+    /// IDs to confirm the achievable throughput. This also warms up the generation path.
+    /// Takes a few tens of milliseconds. The rate limit does not apply, and the IDs
+    /// generated are discarded, though an attached audit log and duplicate guard still
+    /// record them like any other issued ID.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the report (see `SelfTestReport::is_ok`) or a SnowflakeError
+    ///
+    /// # Errors
+    ///
+    /// Same as `generate` (except SnowflakeError::ClockMovedBackwards, which is reported
+    /// as `monotonic: false`), if an ID cannot be generated at all
+    ///
+    /// # Example
+    /// ```
+    /// use snowflake_rs_impl::snowflake::Snowflake;
+    ///
+    /// let snowflake = Snowflake::new(1, None).unwrap();
+    /// let report = snowflake.self_test().unwrap();
+    /// assert!(report.monotonic);
+    /// if !report.is_ok() {
+    ///     eprintln!("Snowflake self-test warnings: {:?}", report.warnings);
+    /// }
+    /// ```
+    pub fn self_test(&self) -> Result<SelfTestReport, SnowflakeError> {
+        let mut warnings = Vec::new();
+
+        let clock_resolution = self.measure_clock_resolution()?;
+        match clock_resolution {
+            None => warnings.push("clock did not advance during the self-test".to_string()),
+            Some(resolution) if resolution > Duration::from_millis(1) => warnings.push(format!(
+                "clock resolution is {:?}; IDs within one tick share a timestamp",
+                resolution
+            )),
+            Some(_) => {}
+        }
+
+        let (ids_checked, mut monotonic) = self.check_monotonic()?;
+
+        let burst_size = 4 * (self.layout().max_sequence() as u64 + 1);
+        let start = Instant::now();
+        for _ in 0..burst_size {
+            match self.generate_unthrottled() {
+                Ok(_) => {}
+                Err(SnowflakeError::ClockMovedBackwards) => monotonic = false,
+                Err(err) => return Err(err),
+            }
+        }
+        if !monotonic {
+            warnings.push("IDs or clock readings went backwards".to_string());
+        }
+        let throughput_per_sec = burst_size as f64 / start.elapsed().as_secs_f64().max(f64::MIN_POSITIVE);
+        if let Some(rate_limit) = self.rate_limit() {
+            if throughput_per_sec < rate_limit.max_per_second() as f64 {
+                warnings.push(format!(
+                    "achieved {:.0} IDs/sec, below the rate limit of {} IDs/sec",
+                    throughput_per_sec,
+                    rate_limit.max_per_second()
+                ));
+            }
+        }
+
+        Ok(SelfTestReport {
+            clock_resolution,
+            ids_checked,
+            monotonic,
+            burst_size,
+            throughput_per_sec,
+            warnings,
+        })
+    }
+
+    // Smallest step between distinct clock readings over a few ticks, within 50 ms
+    fn measure_clock_resolution(&self) -> Result<Option<Duration>, SnowflakeError> {
+        let deadline = Instant::now() + Duration::from_millis(50);
+        let mut smallest: Option<i64> = None;
+        let mut last = self.current_time_millis()?;
+        let mut ticks = 0;
+        while ticks < 5 && Instant::now() < deadline {
+            let now = self.current_time_millis()?;
+            if now != last {
+                if now > last {
+                    smallest = Some(smallest.map_or(now - last, |smallest| smallest.min(now - last)));
+                }
+                last = now;
+                ticks += 1;
+            }
+            std::hint::spin_loop();
+        }
+        Ok(smallest.map(|millis| Duration::from_millis(millis as u64)))
+    }
+
+    // Generates IDs for the self-test window, checking IDs and clock readings never go back.
+    // A clock rollback fails the check rather than the whole self-test.
+    fn check_monotonic(&self) -> Result<(u64, bool), SnowflakeError> {
+        let deadline = Instant::now() + DEFAULT_SELF_TEST_WINDOW;
+        let mut last_id = self.generate_unthrottled()?;
+        let mut last_reading = self.current_time_millis()?;
+        let mut ids_checked = 1;
+        let mut monotonic = true;
+        while Instant::now() < deadline {
+            let reading = self.current_time_millis()?;
+            monotonic &= reading >= last_reading;
+            last_reading = reading;
+            match self.generate_unthrottled() {
+                Ok(id) => {
+                    monotonic &= id > last_id;
+                    last_id = id;
+                    ids_checked += 1;
+                }
+                Err(SnowflakeError::ClockMovedBackwards) => monotonic = false,
+                Err(err) => return Err(err),
+            }
+        }
+        Ok((ids_checked, monotonic))
+    }
+}
@@ -1,26 +1,44 @@
 use std::time::Instant;
-use std::sync::atomic::{AtomicI64, Ordering};
 use std::error::Error;
 use std::fmt;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
 
-use log::{debug, warn};
+use log::error;
 use serde::{Deserialize, Serialize};
 
+use crate::audit::AuditLog;
+use crate::backfill::Backfill;
+use crate::clock::{Clock, SystemClock};
+#[cfg(feature = "duplicate-guard")]
+use crate::duplicate_guard::DuplicateGuard;
+use crate::exhaustion::{ExhaustionHook, ExhaustionMonitor, ExhaustionStrategy, DEFAULT_EXHAUSTION_HORIZON, ERA_BIT};
+use crate::id::SnowflakeId;
+use crate::layout::Layout;
+use crate::persist::{PersistedState, StateFile};
+use crate::rate_limit::{RateLimit, ThrottleMode, TokenBucket};
+use crate::region::RegionRegistry;
+use crate::sync::{AtomicI64, AtomicUsize, Ordering};
+
 /// Bit allocation for different parts of the Snowflake ID
-const NODE_BITS: u8 = 10;
-const STEP_BITS: u8 = 12;
-const TIMESTAMP_BITS: u8 = 41;
+pub(crate) const NODE_BITS: u8 = 10;
+pub(crate) const STEP_BITS: u8 = 12;
+pub(crate) const TIMESTAMP_BITS: u8 = 41;
 
-/// Maximum values for node and step
-const NODE_MAX: u16 = (1 << NODE_BITS) - 1;
-const STEP_MAX: u16 = (1 << STEP_BITS) - 1;
+/// Maximum value for node
+pub(crate) const NODE_MAX: u16 = (1 << NODE_BITS) - 1;
 
 /// Bit shifting constants
-const TIMESTAMP_SHIFT: u8 = NODE_BITS + STEP_BITS;
-const NODE_SHIFT: u8 = STEP_BITS;
+pub(crate) const TIMESTAMP_SHIFT: u8 = NODE_BITS + STEP_BITS;
+pub(crate) const NODE_SHIFT: u8 = STEP_BITS;
+
+/// Width of the sequence part of the packed generator state; wide enough for any layout
+const STATE_SEQUENCE_BITS: u8 = 16;
 
 /// Default epoch (2021-01-01T00:00:00Z in milliseconds since Unix epoch)
-const DEFAULT_EPOCH: i64 = 1609459200000;
+pub(crate) const DEFAULT_EPOCH: i64 = 1609459200000;
 
 /// Errors that can occur during Snowflake ID generation
 #[derive(Debug,Serialize,Deserialize)]
@@ -31,6 +49,114 @@ pub enum SnowflakeError {
     MachineIdOutOfRange,
     /// Indicates that the sequence number has overflowed
     SequenceOverflow,
+    /// Indicates that a timestamp does not fit in the timestamp field of an ID
+    TimestampOutOfRange,
+    /// Indicates that a sequence number does not fit in the sequence field of an ID
+    SequenceOutOfRange,
+    /// Indicates that a bit layout has invalid field widths
+    InvalidLayout,
+    /// Indicates that a registry generator has already been created and cannot be reconfigured
+    GeneratorAlreadyInitialized,
+    /// Indicates that a rate limit has a zero rate or burst size
+    InvalidRateLimit,
+    /// Indicates that the generator's rate limit has been reached
+    Throttled,
+    /// Indicates that a batch needs more rate limit tokens than the bucket's burst size
+    BatchExceedsBurst,
+    /// Indicates that an Avro value cannot be converted to or from a Snowflake ID
+    InvalidAvroValue,
+    /// Indicates that the generator state could not be read from or written to its state file
+    StateStore(String),
+    /// Indicates that an ID claims a node ID that is not in the known node set
+    UnknownNode(u16),
+    /// Indicates that the current time no longer fits in the timestamp field and the
+    /// exhaustion strategy does not allow continuing
+    TimestampExhausted,
+    /// Indicates that the epoch cannot be used with the generator's clock and layout
+    InvalidEpoch {
+        /// The rejected epoch, in milliseconds since Unix epoch
+        epoch: i64,
+        /// The clock reading the epoch was checked against
+        now: i64,
+        /// Why the epoch was rejected
+        reason: InvalidEpochReason,
+    },
+    /// Indicates that a duplicate guard has a zero capacity or a false positive rate
+    /// outside (0, 1)
+    InvalidDuplicateGuard,
+    /// Indicates that an observed timestamp is further ahead of the local clock than the
+    /// allowed drift; carries the drift in milliseconds
+    ClockDriftExceeded(i64),
+    /// Indicates that a UUID does not contain an embedded Snowflake ID
+    InvalidUuid,
+    /// Indicates that an epoch cannot be embedded in a UUID
+    UnembeddableEpoch(i64),
+    /// Indicates that an encoded ID string is malformed or out of range
+    InvalidEncoding,
+    /// Indicates that `generate_at` was called on a generator without a backfill node
+    BackfillNotConfigured,
+    /// Indicates that a backfill node ID is also the node ID of a live generator (the
+    /// generator itself or a fork)
+    InvalidBackfillNode(u16),
+    /// Indicates that the global redaction key has already been set
+    RedactionKeyAlreadySet,
+    /// Indicates that a raw value is not a valid Snowflake ID
+    InvalidId(InvalidIdReason),
+    /// Indicates that region bits do not fit the layout, or a region name or value is
+    /// registered twice or out of range
+    InvalidRegionConfig,
+    /// Indicates that a region name is not registered
+    UnknownRegion(String),
+    /// Indicates that the clock could not produce a usable reading
+    ClockUnavailable(String),
+    /// Indicates that a value cannot be stored as, or read from, a CQL `bigint`
+    InvalidCqlValue,
+    /// Indicates that an Arrow array cannot hold Snowflake IDs (wrong type or negative values)
+    InvalidArrowArray,
+    /// Indicates that the background task serving an `IdHandle` is no longer running
+    ServiceStopped,
+    /// Indicates that a remote ID service could not provide IDs
+    RemoteUnavailable(String),
+    /// Indicates that a prefetch block size is 0 or its low-water mark is not below it
+    InvalidPrefetchConfig,
+}
+
+/// Why an epoch was rejected by `SnowflakeBuilder::build`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InvalidEpochReason {
+    /// The epoch is later than the current time, so timestamps would be negative
+    InFuture,
+    /// The epoch is so far in the past that the current time no longer fits in the
+    /// timestamp field
+    BeyondLayoutRange {
+        /// Largest timestamp offset the generator can encode, in milliseconds
+        max_offset: u64,
+    },
+}
+
+/// Why a raw value was rejected as a Snowflake ID (see `IdValidator`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InvalidIdReason {
+    /// The value is negative
+    Negative,
+    /// The reserved top bit is set
+    ReservedBitSet,
+    /// The check field does not match the rest of the ID (see `Layout::with_check_bits`)
+    ChecksumMismatch,
+    /// The ID was generated before the earliest accepted time
+    TooOld {
+        /// When the ID claims to have been generated, in milliseconds since Unix epoch
+        unix_millis: i64,
+        /// The earliest accepted time, in milliseconds since Unix epoch
+        not_before: i64,
+    },
+    /// The ID claims to have been generated in the future
+    InFuture {
+        /// When the ID claims to have been generated, in milliseconds since Unix epoch
+        unix_millis: i64,
+        /// The clock reading it was checked against
+        now: i64,
+    },
 }
 
 impl fmt::Display for SnowflakeError {
@@ -39,24 +165,377 @@ impl fmt::Display for SnowflakeError {
             SnowflakeError::ClockMovedBackwards => write!(f, "Clock moved backwards"),
             SnowflakeError::MachineIdOutOfRange => write!(f, "Machine ID is out of range"),
             SnowflakeError::SequenceOverflow => write!(f, "Sequence overflow"),
+            SnowflakeError::TimestampOutOfRange => write!(f, "Timestamp is out of range"),
+            SnowflakeError::SequenceOutOfRange => write!(f, "Sequence number is out of range"),
+            SnowflakeError::InvalidLayout => write!(f, "Invalid bit layout"),
+            SnowflakeError::GeneratorAlreadyInitialized => write!(f, "Generator is already initialized"),
+            SnowflakeError::InvalidRateLimit => write!(f, "Invalid rate limit"),
+            SnowflakeError::Throttled => write!(f, "Rate limit reached"),
+            SnowflakeError::BatchExceedsBurst => write!(f, "Batch is larger than the rate limit's burst size"),
+            SnowflakeError::InvalidAvroValue => write!(f, "Invalid Avro value for a Snowflake ID"),
+            SnowflakeError::StateStore(reason) => write!(f, "State store error: {}", reason),
+            SnowflakeError::UnknownNode(node) => write!(f, "Unknown node ID {}", node),
+            SnowflakeError::TimestampExhausted => write!(f, "Timestamp field is exhausted"),
+            SnowflakeError::InvalidEpoch { epoch, now, reason: InvalidEpochReason::InFuture } => {
+                write!(f, "Invalid epoch {}: it is {} ms in the future", epoch, epoch - now)
+            }
+            SnowflakeError::InvalidEpoch { epoch, now, reason: InvalidEpochReason::BeyondLayoutRange { max_offset } } => write!(
+                f,
+                "Invalid epoch {}: it is {} ms in the past, but the layout only encodes {} ms",
+                epoch,
+                now.saturating_sub(*epoch),
+                max_offset
+            ),
+            SnowflakeError::InvalidDuplicateGuard => write!(f, "Invalid duplicate guard configuration"),
+            SnowflakeError::ClockDriftExceeded(drift) => write!(f, "Observed timestamp is {} ms ahead of the local clock", drift),
+            SnowflakeError::InvalidUuid => write!(f, "UUID does not contain a Snowflake ID"),
+            SnowflakeError::UnembeddableEpoch(epoch) => write!(f, "Epoch {} cannot be embedded in a UUID", epoch),
+            SnowflakeError::InvalidEncoding => write!(f, "Invalid encoded ID"),
+            SnowflakeError::BackfillNotConfigured => write!(f, "No backfill node is configured"),
+            SnowflakeError::InvalidBackfillNode(node) => {
+                write!(f, "Backfill node ID {} is also a live node ID", node)
+            }
+            SnowflakeError::RedactionKeyAlreadySet => write!(f, "Redaction key is already set"),
+            SnowflakeError::InvalidRegionConfig => write!(f, "Invalid region configuration"),
+            SnowflakeError::UnknownRegion(region) => write!(f, "Unknown region {}", region),
+            SnowflakeError::ClockUnavailable(reason) => write!(f, "Clock unavailable: {}", reason),
+            SnowflakeError::InvalidCqlValue => write!(f, "Invalid CQL value for a Snowflake ID"),
+            SnowflakeError::InvalidArrowArray => write!(f, "Invalid Arrow array for Snowflake IDs"),
+            SnowflakeError::ServiceStopped => write!(f, "ID generator service has stopped"),
+            SnowflakeError::RemoteUnavailable(reason) => write!(f, "Remote ID service unavailable: {}", reason),
+            SnowflakeError::InvalidPrefetchConfig => write!(f, "Invalid prefetch block size or low-water mark"),
+            SnowflakeError::InvalidId(InvalidIdReason::Negative) => write!(f, "Invalid ID: value is negative"),
+            SnowflakeError::InvalidId(InvalidIdReason::ChecksumMismatch) => {
+                write!(f, "Invalid ID: checksum does not match, the ID is corrupted")
+            }
+            SnowflakeError::InvalidId(InvalidIdReason::ReservedBitSet) => {
+                write!(f, "Invalid ID: reserved top bit is set")
+            }
+            SnowflakeError::InvalidId(InvalidIdReason::TooOld { unix_millis, not_before }) => write!(
+                f,
+                "Invalid ID: generated at {}, {} ms before the earliest accepted time",
+                unix_millis,
+                not_before - unix_millis
+            ),
+            SnowflakeError::InvalidId(InvalidIdReason::InFuture { unix_millis, now }) => {
+                write!(f, "Invalid ID: generated at {}, {} ms in the future", unix_millis, unix_millis - now)
+            }
         }
     }
 }
 
 impl Error for SnowflakeError {}
 
+/// Serializable snapshot of a generator's runtime state
+///
+/// Taking a snapshot before shutdown and restoring it with `Snowflake::from_snapshot`
+/// lets a restarted process continue after the last issued ID instead of from scratch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GeneratorSnapshot {
+    /// Node ID of the generator
+    pub node: u16,
+    /// Epoch of the generator in milliseconds since Unix epoch
+    pub epoch: i64,
+    /// Timestamp of the last issued ID in milliseconds since Unix epoch (0 if none)
+    pub last_timestamp: i64,
+    /// Sequence number of the last issued ID
+    pub last_sequence: u16,
+    /// Bit layout of the generator
+    #[serde(default)]
+    pub layout: Layout,
+}
+
+/// Point-in-time view of a generator's progress, returned by `Snowflake::state`
+///
+/// Meant for dashboards showing how close each node runs to sequence saturation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GeneratorState {
+    /// Timestamp of the last issued ID in milliseconds since Unix epoch (0 if none)
+    pub last_timestamp: i64,
+    /// Sequence number of the last issued ID within its millisecond
+    pub sequence: u16,
+    /// Largest sequence number per millisecond of the generator's layout
+    pub max_sequence: u16,
+    /// Whether callers are currently held up
+    pub status: GeneratorStatus,
+}
+
+impl GeneratorState {
+    /// Returns the fraction of the last millisecond's sequence numbers in use, from 0.0
+    /// (no IDs issued yet) to 1.0 (the millisecond is full)
+    pub fn saturation(&self) -> f64 {
+        if self.last_timestamp == 0 {
+            return 0.0;
+        }
+        (self.sequence as f64 + 1.0) / (self.max_sequence as f64 + 1.0)
+    }
+}
+
+/// Whether a generator can issue IDs right away, as reported by `Snowflake::state`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GeneratorStatus {
+    /// IDs are issued without waiting
+    Ready,
+    /// The sequence numbers of the current millisecond are used up and callers are waiting
+    /// for the next millisecond
+    WaitingForNextMillis,
+    /// Callers are waiting for the rate limit (in `ThrottleMode::Wait`)
+    Throttled,
+    /// The clock is behind the last issued timestamp, so generation fails with
+    /// SnowflakeError::ClockMovedBackwards until it catches up
+    Suspended,
+}
+
 /// Snowflake ID generator
 ///
 /// This struct implements the Snowflake algorithm for generating unique IDs.
-/// Each ID is composed of:
+/// With the default layout, each ID is composed of:
 /// - Timestamp (41 bits)
 /// - Node ID (10 bits)
 /// - Sequence number (12 bits)
+///
+/// Other splits can be configured with `SnowflakeBuilder::layout`.
 pub struct Snowflake {
     node: u16,
     epoch_ms: i64,
+    layout: Layout,
     last_timestamp_and_sequence: AtomicI64,
-    start: Instant,
+    waiting: AtomicUsize,
+    rate_limiter: Option<TokenBucket>,
+    persistence: Option<Persistence>,
+    clock: Arc<dyn Clock>,
+    exhaustion: Option<ExhaustionMonitor>,
+    exhaustion_strategy: ExhaustionStrategy,
+    backfill: Option<Backfill>,
+    regions: Option<Arc<RegionRegistry>>,
+    audit_log: Option<Arc<AuditLog>>,
+    #[cfg(feature = "duplicate-guard")]
+    duplicate_guard: Option<Arc<DuplicateGuard>>,
+}
+
+// State file of a generator built with `persist_on_drop`
+struct Persistence {
+    store: StateFile,
+    previous: Option<PersistedState>,
+    closed: AtomicBool,
+}
+
+/// Builder for Snowflake instances with optional settings
+///
+/// # Example
+/// ```
+/// use snowflake_rs_impl::rate_limit::{RateLimit, ThrottleMode};
+/// use snowflake_rs_impl::snowflake::Snowflake;
+///
+/// let snowflake = Snowflake::builder(1)
+///     .epoch(1672531200000)
+///     .rate_limit(RateLimit::per_second(10_000).mode(ThrottleMode::Wait))
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct SnowflakeBuilder {
+    node: u16,
+    epoch: Option<i64>,
+    layout: Layout,
+    rate_limit: Option<RateLimit>,
+    state_file: Option<StateFile>,
+    clock: Option<Arc<dyn Clock>>,
+    exhaustion_horizon: Option<Duration>,
+    exhaustion_hook: Option<ExhaustionHook>,
+    exhaustion_strategy: ExhaustionStrategy,
+    backfill_node: Option<u16>,
+    regions: Option<Arc<RegionRegistry>>,
+    region: Option<String>,
+    audit_log: Option<Arc<AuditLog>>,
+    #[cfg(feature = "duplicate-guard")]
+    duplicate_guard: Option<Arc<DuplicateGuard>>,
+}
+
+impl SnowflakeBuilder {
+    /// Sets the node ID
+    pub fn node(mut self, node: u16) -> Self {
+        self.node = node;
+        self
+    }
+
+    /// Sets a custom epoch in milliseconds since Unix epoch
+    pub fn epoch(mut self, epoch: i64) -> Self {
+        self.epoch = Some(epoch);
+        self
+    }
+
+    /// Sets the bit layout of generated IDs. If not set, `Layout::DEFAULT` is used.
+    pub fn layout(mut self, layout: Layout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Limits how many IDs the generator hands out per second
+    pub fn rate_limit(mut self, rate_limit: RateLimit) -> Self {
+        self.rate_limit = Some(rate_limit);
+        self
+    }
+
+    /// Sets the clock used for timestamps. If not set, `SystemClock` is used.
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Sets how long before the timestamp field is exhausted the generator starts warning
+    ///
+    /// When an ID is generated within `horizon` of the last encodable timestamp, a warning
+    /// is logged once and the `on_exhaustion_warning` hook, if any, is called. Defaults to
+    /// `DEFAULT_EXHAUSTION_HORIZON` (two years); `None` disables the warning.
+    pub fn exhaustion_warning(mut self, horizon: Option<Duration>) -> Self {
+        self.exhaustion_horizon = horizon;
+        self
+    }
+
+    /// Sets a hook called with the remaining time when the exhaustion warning fires
+    pub fn on_exhaustion_warning(mut self, hook: impl Fn(Duration) + Send + Sync + 'static) -> Self {
+        self.exhaustion_hook = Some(ExhaustionHook(Arc::new(hook)));
+        self
+    }
+
+    /// Sets what happens once the timestamp field is exhausted
+    ///
+    /// Defaults to `ExhaustionStrategy::Error`.
+    pub fn on_exhaustion(mut self, strategy: ExhaustionStrategy) -> Self {
+        self.exhaustion_strategy = strategy;
+        self
+    }
+
+    /// Records the ranges of issued IDs, including backfilled ones, in an audit log
+    ///
+    /// Share the log between generators to record all of them in one place.
+    pub fn audit_log(mut self, audit_log: Arc<AuditLog>) -> Self {
+        self.audit_log = Some(audit_log);
+        self
+    }
+
+    /// Checks every issued ID against a duplicate guard
+    ///
+    /// Share the guard between generators to catch IDs issued by more than one of them.
+    #[cfg(feature = "duplicate-guard")]
+    pub fn duplicate_guard(mut self, guard: Arc<DuplicateGuard>) -> Self {
+        self.duplicate_guard = Some(guard);
+        self
+    }
+
+    /// Sets the node ID used for IDs minted by `Snowflake::generate_at`
+    ///
+    /// Backfilled IDs carry this node ID instead of the generator's own, so they can never
+    /// collide with live IDs, whatever timestamp they are minted for. The node ID must not
+    /// be used by any live generator sharing the epoch, and only one generator at a time
+    /// should backfill with it. Not set by default, so `generate_at` is disabled.
+    pub fn backfill_node(mut self, node: u16) -> Self {
+        self.backfill_node = Some(node);
+        self
+    }
+
+    /// Places the generator in a region of `regions`
+    ///
+    /// The node ID passed to `Snowflake::builder` then becomes the node within the region,
+    /// and the generator's full node ID combines it with the region bits (see
+    /// `RegionRegistry::node_id`). The registry must use the generator's layout.
+    pub fn region(mut self, regions: Arc<RegionRegistry>, region: &str) -> Self {
+        self.regions = Some(regions);
+        self.region = Some(region.to_string());
+        self
+    }
+
+    /// Persists the generator state to a state file
+    ///
+    /// At build time, the state file is read (if it exists) and generation resumes after
+    /// its last timestamp; the previous state is available from `Snowflake::previous_state`.
+    /// The file is then rewritten with `clean_shutdown=false`. On `close`, or on drop if
+    /// `close` was never called, the last issued timestamp is written synchronously with
+    /// `clean_shutdown=true`. A state file still marked unclean at the next startup means
+    /// the previous process crashed.
+    pub fn persist_on_drop(mut self, state_file: StateFile) -> Self {
+        self.state_file = Some(state_file);
+        self
+    }
+
+    /// Builds the Snowflake instance
+    ///
+    /// # Errors
+    ///
+    /// - SnowflakeError::MachineIdOutOfRange if the node ID does not fit in the layout
+    /// - SnowflakeError::InvalidEpoch if the epoch is later than the clock's current time,
+    ///   or so far in the past that the current time does not fit in the timestamp field
+    ///   (two eras with `ExhaustionStrategy::Era`)
+    /// - SnowflakeError::InvalidRateLimit if the rate limit has a zero rate or burst size
+    /// - SnowflakeError::StateStore if the state file cannot be read or written, or
+    ///   belongs to a generator with a different node ID or epoch
+    /// - SnowflakeError::InvalidBackfillNode if the backfill node ID is the generator's own
+    ///   node ID (or SnowflakeError::MachineIdOutOfRange if it does not fit in the layout)
+    /// - SnowflakeError::UnknownRegion if the region is not registered,
+    ///   SnowflakeError::InvalidRegionConfig if the region registry uses another layout, or
+    ///   SnowflakeError::MachineIdOutOfRange if the node does not fit in the region
+    /// - SnowflakeError::ClockUnavailable if the clock cannot be read
+    pub fn build(mut self) -> Result<Snowflake, SnowflakeError> {
+        if let Some(regions) = &self.regions {
+            if regions.layout() != self.layout {
+                return Err(SnowflakeError::InvalidRegionConfig);
+            }
+            if let Some(region) = &self.region {
+                self.node = regions.node_id(region, self.node)?;
+            }
+        }
+        if self.node > self.layout.max_node() {
+            return Err(SnowflakeError::MachineIdOutOfRange);
+        }
+        if let Some(backfill_node) = self.backfill_node {
+            if backfill_node == self.node {
+                return Err(SnowflakeError::InvalidBackfillNode(backfill_node));
+            }
+            if backfill_node > self.layout.max_node() {
+                return Err(SnowflakeError::MachineIdOutOfRange);
+            }
+        }
+        let epoch_ms = self.epoch.unwrap_or(DEFAULT_EPOCH);
+        let clock = self.clock.unwrap_or_else(|| Arc::new(SystemClock));
+        validate_epoch(epoch_ms, clock.try_now_millis()?, &self.layout, self.exhaustion_strategy)?;
+        let rate_limiter = self.rate_limit.map(TokenBucket::new).transpose()?;
+        let persistence = self
+            .state_file
+            .map(|store| Persistence::open(store, self.node, epoch_ms, &self.layout))
+            .transpose()?;
+
+        let snowflake = Snowflake {
+            node: self.node,
+            epoch_ms,
+            layout: self.layout,
+            last_timestamp_and_sequence: AtomicI64::new(0),
+            waiting: AtomicUsize::new(0),
+            rate_limiter,
+            persistence,
+            clock,
+            exhaustion: self.exhaustion_horizon.map(|horizon| {
+                let max_timestamp_ms = epoch_ms.saturating_add(self.layout.max_timestamp() as i64);
+                ExhaustionMonitor::new(self.node, max_timestamp_ms, horizon, self.exhaustion_hook)
+            }),
+            exhaustion_strategy: self.exhaustion_strategy,
+            backfill: self.backfill_node.map(Backfill::new),
+            regions: self.regions,
+            audit_log: self.audit_log,
+            #[cfg(feature = "duplicate-guard")]
+            duplicate_guard: self.duplicate_guard,
+        };
+        if let Some(persistence) = &snowflake.persistence {
+            if let Some(previous) = &persistence.previous {
+                snowflake.last_timestamp_and_sequence.store(
+                    encode_timestamp_and_sequence(previous.snapshot.last_timestamp, previous.snapshot.last_sequence as i64),
+                    Ordering::Release,
+                );
+            }
+            snowflake.flush_state(false)?;
+        }
+        Ok(snowflake)
+    }
 }
 
 impl Snowflake {
@@ -73,18 +552,266 @@ impl Snowflake {
     ///
     /// # Errors
     ///
-    /// Returns SnowflakeError::MachineIdOutOfRange if the node ID is greater than 1023
+    /// - SnowflakeError::MachineIdOutOfRange if the node ID is greater than 1023
+    /// - SnowflakeError::InvalidEpoch if the epoch is in the future or too far in the past
+    /// - SnowflakeError::ClockUnavailable if the system time is before the Unix epoch
     pub fn new(node: u16, epoch: Option<i64>) -> Result<Self, SnowflakeError> {
-        if node > NODE_MAX {
-            return Err(SnowflakeError::MachineIdOutOfRange);
+        SnowflakeBuilder {
+            epoch,
+            ..Snowflake::builder(node)
         }
-        let epoch_ms = epoch.unwrap_or(DEFAULT_EPOCH);
-        Ok(Snowflake {
+        .build()
+    }
+
+    /// Returns a builder for a Snowflake instance with the given node ID
+    pub fn builder(node: u16) -> SnowflakeBuilder {
+        SnowflakeBuilder {
             node,
-            epoch_ms,
-            last_timestamp_and_sequence: AtomicI64::new(0),
-            start: Instant::now(),
-        })
+            epoch: None,
+            layout: Layout::DEFAULT,
+            rate_limit: None,
+            state_file: None,
+            clock: None,
+            exhaustion_horizon: Some(DEFAULT_EXHAUSTION_HORIZON),
+            exhaustion_hook: None,
+            exhaustion_strategy: ExhaustionStrategy::Error,
+            backfill_node: None,
+            regions: None,
+            region: None,
+            audit_log: None,
+            #[cfg(feature = "duplicate-guard")]
+            duplicate_guard: None,
+        }
+    }
+
+    /// Returns the node ID of this generator
+    pub fn node(&self) -> u16 {
+        self.node
+    }
+
+    /// Returns the epoch of this generator in milliseconds since Unix epoch
+    pub fn epoch(&self) -> i64 {
+        self.epoch_ms
+    }
+
+    /// Returns the bit layout of the IDs this generator produces
+    ///
+    /// The layout reports the field widths, the largest node ID and the largest sequence
+    /// number per millisecond, and the largest timestamp offset relative to `epoch`.
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    /// Returns the latest time, in milliseconds since Unix epoch, that this generator can
+    /// encode before the timestamp field is exhausted
+    pub fn max_timestamp_millis(&self) -> i64 {
+        self.epoch_ms.saturating_add(self.layout.max_timestamp() as i64)
+    }
+
+    /// Returns the time left until the timestamp field is exhausted
+    ///
+    /// Returns `Duration::ZERO` once the clock is past `max_timestamp_millis`.
+    pub fn time_until_exhaustion(&self) -> Duration {
+        let remaining = self.max_timestamp_millis().saturating_sub(self.clock.now_millis());
+        Duration::from_millis(remaining.max(0) as u64)
+    }
+
+    /// Returns the exhaustion warning horizon, or None if the warning is disabled
+    pub fn exhaustion_horizon(&self) -> Option<Duration> {
+        self.exhaustion.as_ref().map(ExhaustionMonitor::horizon)
+    }
+
+    /// Returns what the generator does once the timestamp field is exhausted
+    pub fn exhaustion_strategy(&self) -> ExhaustionStrategy {
+        self.exhaustion_strategy
+    }
+
+    /// Returns the audit log recording this generator's IDs, if any
+    pub fn audit_log(&self) -> Option<&Arc<AuditLog>> {
+        self.audit_log.as_ref()
+    }
+
+    /// Returns the duplicate guard checking this generator's IDs, if any
+    #[cfg(feature = "duplicate-guard")]
+    pub fn duplicate_guard(&self) -> Option<&Arc<DuplicateGuard>> {
+        self.duplicate_guard.as_ref()
+    }
+
+    /// Returns the rate limit of this generator, if any
+    pub fn rate_limit(&self) -> Option<RateLimit> {
+        self.rate_limiter.as_ref().map(TokenBucket::limit)
+    }
+
+    /// Returns a snapshot of the generator's current state
+    ///
+    /// The snapshot is only a lower bound while other threads keep generating; take it
+    /// once generation has stopped.
+    pub fn snapshot(&self) -> GeneratorSnapshot {
+        let (last_timestamp, last_sequence) =
+            decode_timestamp_and_sequence(self.last_timestamp_and_sequence.load(Ordering::Acquire));
+        GeneratorSnapshot {
+            node: self.node,
+            epoch: self.epoch_ms,
+            last_timestamp,
+            last_sequence: last_sequence as u16,
+            layout: self.layout,
+        }
+    }
+
+    /// Returns the generator's current progress and whether callers are held up
+    ///
+    /// Never blocks or fails: it reads the packed state, the waiting counters and the
+    /// clock once.
+    ///
+    /// # Example
+    /// ```
+    /// use snowflake_rs_impl::snowflake::{GeneratorStatus, Snowflake};
+    ///
+    /// let snowflake = Snowflake::new(1, None).unwrap();
+    /// snowflake.generate().unwrap();
+    /// let state = snowflake.state();
+    /// assert_eq!(state.status, GeneratorStatus::Ready);
+    /// assert!(state.saturation() > 0.0);
+    /// ```
+    pub fn state(&self) -> GeneratorState {
+        let (last_timestamp, sequence) =
+            decode_timestamp_and_sequence(self.last_timestamp_and_sequence.load(Ordering::Acquire));
+        let status = if self.clock.now_millis() < last_timestamp {
+            GeneratorStatus::Suspended
+        } else if self.waiting.load(Ordering::Relaxed) > 0 {
+            GeneratorStatus::WaitingForNextMillis
+        } else if self.rate_limiter.as_ref().is_some_and(TokenBucket::is_waiting) {
+            GeneratorStatus::Throttled
+        } else {
+            GeneratorStatus::Ready
+        };
+        GeneratorState {
+            last_timestamp,
+            sequence: sequence as u16,
+            max_sequence: self.layout.max_sequence(),
+            status,
+        }
+    }
+
+    /// Creates a new generator with the same configuration but a different node ID
+    ///
+    /// The fork shares the epoch, layout, clock, exhaustion and rate-limit settings (with its
+    /// own, full token bucket), the region registry, the audit log and the duplicate guard,
+    /// but starts with fresh state. State-file persistence and the backfill node are not
+    /// inherited, since each belongs to a single generator.
+    ///
+    /// # Errors
+    ///
+    /// - SnowflakeError::MachineIdOutOfRange if the node ID does not fit in the layout
+    /// - SnowflakeError::InvalidBackfillNode if the node ID is this generator's backfill
+    ///   node, since the fork's live IDs could collide with backfilled ones
+    pub fn fork(&self, node: u16) -> Result<Snowflake, SnowflakeError> {
+        if self.backfill_node() == Some(node) {
+            return Err(SnowflakeError::InvalidBackfillNode(node));
+        }
+        let mut builder = Snowflake::builder(node)
+            .epoch(self.epoch_ms)
+            .layout(self.layout)
+            .clock(Arc::clone(&self.clock))
+            .exhaustion_warning(self.exhaustion_horizon())
+            .on_exhaustion(self.exhaustion_strategy);
+        builder.exhaustion_hook = self.exhaustion.as_ref().and_then(ExhaustionMonitor::hook);
+        // The fork's node ID is a full node ID, which already carries its region
+        builder.regions = self.regions.clone();
+        builder.audit_log = self.audit_log.clone();
+        #[cfg(feature = "duplicate-guard")]
+        {
+            builder.duplicate_guard = self.duplicate_guard.clone();
+        }
+        if let Some(rate_limit) = self.rate_limit() {
+            builder = builder.rate_limit(rate_limit);
+        }
+        builder.build()
+    }
+
+    /// Returns the name of the generator's region, if built with `SnowflakeBuilder::region`
+    pub fn region(&self) -> Option<&str> {
+        let regions = self.regions.as_ref()?;
+        regions.name(regions.split_node(self.node).0)
+    }
+
+    /// Returns the region registry, if built with `SnowflakeBuilder::region`
+    pub fn regions(&self) -> Option<&Arc<RegionRegistry>> {
+        self.regions.as_ref()
+    }
+
+    /// Returns the node ID used for backfilled IDs, if backfill is enabled
+    pub fn backfill_node(&self) -> Option<u16> {
+        self.backfill.as_ref().map(Backfill::node)
+    }
+
+    /// Returns the state found in the state file when the generator was built
+    ///
+    /// None if the generator was not built with `persist_on_drop` or the state file did
+    /// not exist yet. `clean_shutdown` tells whether the previous process shut down cleanly.
+    pub fn previous_state(&self) -> Option<PersistedState> {
+        self.persistence.as_ref().and_then(|persistence| persistence.previous)
+    }
+
+    /// Writes the final state to the state file and marks the shutdown as clean
+    ///
+    /// Use this for explicit shutdown, e.g. in async contexts where blocking file I/O in
+    /// `Drop` is undesirable. After `close`, dropping the generator does not write the
+    /// state file again, so IDs should not be generated after calling it. Does nothing
+    /// if the generator was not built with `persist_on_drop`.
+    ///
+    /// # Errors
+    ///
+    /// Returns SnowflakeError::StateStore if the state file cannot be written
+    pub fn close(&self) -> Result<(), SnowflakeError> {
+        if let Some(persistence) = &self.persistence {
+            self.flush_state(true)?;
+            persistence.closed.store(true, Ordering::Release);
+        }
+        Ok(())
+    }
+
+    // Writes the current state to the state file, if persistence is configured
+    fn flush_state(&self, clean_shutdown: bool) -> Result<(), SnowflakeError> {
+        if let Some(persistence) = &self.persistence {
+            let state = PersistedState {
+                snapshot: self.snapshot(),
+                clean_shutdown,
+            };
+            persistence.store.store(&state).map_err(|err| SnowflakeError::StateStore(err.to_string()))?;
+        }
+        Ok(())
+    }
+
+    /// Creates a Snowflake instance that resumes from a snapshot
+    ///
+    /// The new generator never issues an ID at or before the snapshot's last ID. If the
+    /// clock is behind the snapshot's last timestamp, `generate` returns
+    /// SnowflakeError::ClockMovedBackwards until the clock catches up.
+    ///
+    /// # Errors
+    ///
+    /// - SnowflakeError::MachineIdOutOfRange if the node ID does not fit in the layout
+    /// - SnowflakeError::SequenceOutOfRange if the last sequence number does not fit in the layout
+    /// - SnowflakeError::TimestampOutOfRange if the last timestamp is negative or beyond the
+    ///   layout's range
+    pub fn from_snapshot(snapshot: &GeneratorSnapshot) -> Result<Self, SnowflakeError> {
+        if snapshot.last_sequence > snapshot.layout.max_sequence() {
+            return Err(SnowflakeError::SequenceOutOfRange);
+        }
+        let max_timestamp = snapshot.epoch.saturating_add(snapshot.layout.max_timestamp() as i64);
+        if snapshot.last_timestamp < 0 || snapshot.last_timestamp > max_timestamp {
+            return Err(SnowflakeError::TimestampOutOfRange);
+        }
+        let snowflake = Snowflake::builder(snapshot.node)
+            .epoch(snapshot.epoch)
+            .layout(snapshot.layout)
+            .build()?;
+        snowflake.last_timestamp_and_sequence.store(
+            encode_timestamp_and_sequence(snapshot.last_timestamp, snapshot.last_sequence as i64),
+            Ordering::Release,
+        );
+        Ok(snowflake)
     }
 
     /// Generates a new Snowflake ID
@@ -97,18 +824,39 @@ impl Snowflake {
     ///
     /// - SnowflakeError::ClockMovedBackwards if the system time moves backwards
     /// - SnowflakeError::SequenceOverflow if unable to generate a unique ID within 5 seconds
+    /// - SnowflakeError::Throttled if the rate limit is reached in `ThrottleMode::Error`
+    /// - SnowflakeError::TimestampExhausted if the timestamp field is exhausted (see
+    ///   `SnowflakeBuilder::on_exhaustion`)
+    /// - SnowflakeError::ClockUnavailable if the clock cannot be read
     pub fn generate(&self) -> Result<u64, SnowflakeError> {
-        let current_timestamp = self.current_time_millis();
+        self.acquire_rate_limit()?;
+        self.generate_unthrottled()
+    }
+
+    // `generate` without the rate limit
+    pub(crate) fn generate_unthrottled(&self) -> Result<u64, SnowflakeError> {
+        self.next_in_sequence(true).map(|id| id.expect("waiting for the next millisecond always issues an ID"))
+    }
+
+    // Issues the next ID. Once the sequence numbers of the current millisecond are used
+    // up, waits for the next millisecond if `wait` is true, and returns None otherwise.
+    #[inline]
+    fn next_in_sequence(&self, wait: bool) -> Result<Option<u64>, SnowflakeError> {
         let mut last_timestamp_and_sequence = self.last_timestamp_and_sequence.load(Ordering::Acquire);
 
         loop {
+            // Re-read the clock on every attempt: a competing thread may have moved the
+            // state to a newer millisecond, which must not look like a clock rollback
+            let current_timestamp = self.current_time_millis()?;
             let (last_timestamp, last_sequence) = decode_timestamp_and_sequence(last_timestamp_and_sequence);
             if current_timestamp < last_timestamp {
                 return Err(SnowflakeError::ClockMovedBackwards);
             }
             let (new_timestamp, new_sequence) = if current_timestamp == last_timestamp {
-                let new_sequence = (last_sequence + 1) & STEP_MAX as i64;
-                if new_sequence == 0 {
+                let new_sequence = (last_sequence + 1) & self.layout.max_sequence() as i64;
+                if new_sequence == 0 && !wait {
+                    return Ok(None);
+                } else if new_sequence == 0 {
                     (self.wait_next_millis(last_timestamp)?, 0)
                 } else {
                     (current_timestamp, new_sequence)
@@ -124,8 +872,11 @@ impl Snowflake {
                 Ordering::Acquire,
             ) {
                 Ok(_) => {
-                    let id = self.create_id(new_timestamp, new_sequence as u16);
-                    return Ok(id);
+                    self.check_exhaustion(new_timestamp);
+                    let id = self.create_id(new_timestamp, new_sequence as u16)?;
+                    self.check_duplicate(id);
+                    self.audit(self.node, new_timestamp, new_sequence as u16);
+                    return Ok(Some(id));
                 }
                 Err(actual) => {
                     last_timestamp_and_sequence = actual;
@@ -133,6 +884,279 @@ impl Snowflake {
             }
         }
     }
+
+    /// Generates a new Snowflake ID without clock or overflow checks
+    ///
+    /// This is a fast path for hot loops. Unlike `generate`, it never returns an error and
+    /// never waits; it panics instead on timestamp exhaustion, or if the clock cannot be read
+    /// and there is no prior timestamp to continue from (see `# Panics`). Otherwise:
+    /// - If the clock is behind the last issued timestamp, IDs keep using the last timestamp
+    ///   instead of returning SnowflakeError::ClockMovedBackwards
+    /// - If the sequence is exhausted, the timestamp is advanced by one millisecond ahead of
+    ///   the clock instead of waiting for the next millisecond
+    /// - The rate limit, if any, is not applied
+    ///
+    /// IDs stay unique and increasing for this generator, but their timestamps can run ahead
+    /// of the real clock. Only use this when the environment guarantees a monotonic clock and
+    /// the generation rate stays below the layout's sequence numbers per millisecond
+    /// (4096 by default); otherwise timestamps drift
+    /// and IDs from a restarted process may collide with IDs issued before the restart.
+    ///
+    /// Avoid mixing it with `generate` on the same instance: once a timestamp has been
+    /// borrowed from the future, `generate` returns SnowflakeError::ClockMovedBackwards
+    /// until the clock catches up.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if the timestamp field is exhausted and the exhaustion strategy does not
+    ///   allow continuing, rather than issuing a corrupt ID
+    /// - While the clock cannot be read, IDs continue from the last issued timestamp; if
+    ///   none has been issued yet, panics with the clock error, leaving the generator's
+    ///   state unchanged
+    pub fn generate_unchecked(&self) -> u64 {
+        let current_timestamp = self.current_time_millis();
+        let mut last_timestamp_and_sequence = self.last_timestamp_and_sequence.load(Ordering::Acquire);
+
+        loop {
+            let (last_timestamp, last_sequence) = decode_timestamp_and_sequence(last_timestamp_and_sequence);
+            // Without a usable reading, carry on from the last issued timestamp
+            let current_timestamp = match &current_timestamp {
+                Ok(timestamp) => *timestamp,
+                Err(err) if last_timestamp < self.epoch_ms => panic!("{}", err),
+                Err(_) => i64::MIN,
+            };
+            let (new_timestamp, new_sequence) = if current_timestamp > last_timestamp {
+                (current_timestamp, 0)
+            } else if last_sequence < self.layout.max_sequence() as i64 {
+                (last_timestamp, last_sequence + 1)
+            } else {
+                (last_timestamp + 1, 0)
+            };
+            match self.last_timestamp_and_sequence.compare_exchange_weak(
+                last_timestamp_and_sequence,
+                encode_timestamp_and_sequence(new_timestamp, new_sequence),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => {
+                    self.check_exhaustion(new_timestamp);
+                    let id = self
+                        .create_id(new_timestamp, new_sequence as u16)
+                        .unwrap_or_else(|err| panic!("{}", err));
+                    self.check_duplicate(id);
+                    self.audit(self.node, new_timestamp, new_sequence as u16);
+                    return id;
+                }
+                Err(actual) => {
+                    last_timestamp_and_sequence = actual;
+                }
+            }
+        }
+    }
+
+    /// Generates a new Snowflake ID wrapped in a `SnowflakeId`
+    ///
+    /// # Errors
+    ///
+    /// Same as `generate`
+    pub fn generate_id(&self) -> Result<SnowflakeId, SnowflakeError> {
+        self.generate().map(SnowflakeId::from)
+    }
+
+    /// Polls for a new Snowflake ID without blocking
+    ///
+    /// For custom executors and event loops that cannot block a thread or do not run a
+    /// full async runtime. Where `generate` would wait, i.e. the sequence numbers of the
+    /// current millisecond are used up or the rate limit (in `ThrottleMode::Wait`) has no
+    /// token, this returns `Poll::Pending` instead. No timer is registered: the waker in
+    /// `cx` is woken right away, so the caller is polled again as soon as the executor
+    /// gets to it, typically for less than a millisecond. Since the caller does not wait
+    /// in `poll_generate`, the 5 second SequenceOverflow limit does not apply.
+    ///
+    /// # Errors
+    ///
+    /// Same as `generate`, except for SnowflakeError::SequenceOverflow
+    ///
+    /// # Example
+    /// ```
+    /// use std::task::{Context, Poll, Waker};
+    /// use snowflake_rs_impl::snowflake::Snowflake;
+    ///
+    /// let snowflake = Snowflake::new(1, None).unwrap();
+    /// let mut cx = Context::from_waker(Waker::noop());
+    /// let id = loop {
+    ///     match snowflake.poll_generate(&mut cx) {
+    ///         Poll::Ready(id) => break id.unwrap(),
+    ///         Poll::Pending => continue, // e.g. handle other events first
+    ///     }
+    /// };
+    /// assert_eq!(id.node(), 1);
+    /// ```
+    pub fn poll_generate(&self, cx: &mut Context<'_>) -> Poll<Result<SnowflakeId, SnowflakeError>> {
+        if let Some(rate_limiter) = &self.rate_limiter {
+            if !rate_limiter.try_acquire() {
+                if rate_limiter.limit().throttle_mode() == ThrottleMode::Error {
+                    return Poll::Ready(Err(SnowflakeError::Throttled));
+                }
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+        }
+        match self.next_in_sequence(false) {
+            Ok(Some(id)) => Poll::Ready(Ok(SnowflakeId::from(id))),
+            Ok(None) => {
+                // Give the token back; the next poll takes it again
+                if let Some(rate_limiter) = &self.rate_limiter {
+                    rate_limiter.release(1);
+                }
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+            Err(err) => Poll::Ready(Err(err)),
+        }
+    }
+
+    /// Generates `n` Snowflake IDs in ascending order
+    ///
+    /// Sequence numbers are reserved in blocks, so the shared atomic state is updated once
+    /// per millisecond of IDs rather than once per ID. Within a block the timestamp and node
+    /// fields are encoded once and each ID only adds its sequence number. If the generator
+    /// has a rate limit, one token is still taken per ID, all `n` of them at once; if the
+    /// batch fails, they are given back.
+    ///
+    /// # Errors
+    ///
+    /// - SnowflakeError::BatchExceedsBurst if the generator has a rate limit whose burst
+    ///   size is less than `n`
+    /// - Otherwise, same as `generate`
+    pub fn generate_batch(&self, n: usize) -> Result<Vec<SnowflakeId>, SnowflakeError> {
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.acquire_many(n)?;
+        }
+        let ids = self.reserve_batch(n);
+        if ids.is_err() {
+            if let Some(rate_limiter) = &self.rate_limiter {
+                rate_limiter.release(n);
+            }
+        }
+        ids
+    }
+
+    // Reserves and encodes `n` IDs, block by block
+    fn reserve_batch(&self, n: usize) -> Result<Vec<SnowflakeId>, SnowflakeError> {
+        let mut ids = Vec::with_capacity(n);
+        while ids.len() < n {
+            let remaining = (n - ids.len()).min(self.layout.max_sequence() as usize + 1) as u32;
+            let (timestamp, first_sequence, count) = self.reserve_block(remaining)?;
+            let prefix = self.create_id(timestamp, 0)?;
+            self.audit(self.node, timestamp, first_sequence + (count - 1) as u16);
+            ids.extend(
+                (first_sequence as u64..first_sequence as u64 + count as u64)
+                    .map(|sequence| self.layout.with_checksum(prefix | sequence << self.layout.sequence_shift()))
+                    .map(SnowflakeId::from),
+            );
+        }
+        #[cfg(feature = "duplicate-guard")]
+        for id in &ids {
+            self.check_duplicate(id.as_u64());
+        }
+        Ok(ids)
+    }
+
+    /// Generates a Snowflake ID for a past point in time, e.g. to backfill migrated records
+    ///
+    /// The ID carries the backfill node ID (see `SnowflakeBuilder::backfill_node`) and a
+    /// sequence number from a sequence space of its own, so it never collides with live
+    /// IDs or with other IDs backfilled by this generator within this process. Timestamps
+    /// may be passed in any order; IDs for the same millisecond get increasing sequence
+    /// numbers. The rate limit does not apply.
+    ///
+    /// The backfill sequence space is kept in memory only and is not saved to the state
+    /// file, so a new generator (e.g. after a restart) starts every millisecond at sequence
+    /// 0 again. Backfill each time range from a single generator, in a single run.
+    ///
+    /// Every millisecond backfilled so far takes a few bytes of memory for the lifetime
+    /// of the generator.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp_ms` - The point in time in milliseconds since Unix epoch
+    ///
+    /// # Errors
+    ///
+    /// - SnowflakeError::BackfillNotConfigured if no backfill node is configured
+    /// - SnowflakeError::TimestampOutOfRange if the timestamp is before the epoch or not
+    ///   in the past according to the generator's clock
+    /// - SnowflakeError::SequenceOverflow if every sequence number of that millisecond has
+    ///   already been backfilled
+    /// - SnowflakeError::ClockUnavailable if the clock cannot be read
+    ///
+    /// # Example
+    /// ```
+    /// use snowflake_rs_impl::snowflake::Snowflake;
+    ///
+    /// let snowflake = Snowflake::builder(1).backfill_node(1023).build().unwrap();
+    /// let created_at = 1640995200000; // 2022-01-01T00:00:00Z
+    /// let id = snowflake.generate_at(created_at).unwrap();
+    /// let (timestamp, node, _) = Snowflake::parse_id(id);
+    /// assert_eq!(timestamp as i64 + snowflake.epoch(), created_at);
+    /// assert_eq!(node, 1023);
+    /// ```
+    pub fn generate_at(&self, timestamp_ms: i64) -> Result<u64, SnowflakeError> {
+        let backfill = self.backfill.as_ref().ok_or(SnowflakeError::BackfillNotConfigured)?;
+        if timestamp_ms < self.epoch_ms || timestamp_ms >= self.current_time_millis()? {
+            return Err(SnowflakeError::TimestampOutOfRange);
+        }
+        let sequence = backfill
+            .next_sequence(timestamp_ms, self.layout.max_sequence())
+            .ok_or(SnowflakeError::SequenceOverflow)?;
+        let id = self.create_id_for_node(backfill.node(), timestamp_ms, sequence)?;
+        self.check_duplicate(id);
+        self.audit(backfill.node(), timestamp_ms, sequence);
+        Ok(id)
+    }
+
+    // Reserves up to `max_count` consecutive sequence numbers within a single millisecond
+    // with one successful CAS. Returns the timestamp, the first sequence number and the
+    // number of sequence numbers actually reserved (at least 1).
+    fn reserve_block(&self, max_count: u32) -> Result<(i64, u16, u32), SnowflakeError> {
+        let max_sequence = self.layout.max_sequence();
+        let mut last_timestamp_and_sequence = self.last_timestamp_and_sequence.load(Ordering::Acquire);
+
+        loop {
+            let current_timestamp = self.current_time_millis()?;
+            let (last_timestamp, last_sequence) = decode_timestamp_and_sequence(last_timestamp_and_sequence);
+            if current_timestamp < last_timestamp {
+                return Err(SnowflakeError::ClockMovedBackwards);
+            }
+            let (timestamp, first_sequence) = if current_timestamp == last_timestamp {
+                if last_sequence == max_sequence as i64 {
+                    (self.wait_next_millis(last_timestamp)?, 0)
+                } else {
+                    (current_timestamp, last_sequence as u16 + 1)
+                }
+            } else {
+                (current_timestamp, 0)
+            };
+            let count = max_count.clamp(1, (max_sequence - first_sequence) as u32 + 1);
+            let last_reserved = first_sequence as i64 + (count - 1) as i64;
+            match self.last_timestamp_and_sequence.compare_exchange_weak(
+                last_timestamp_and_sequence,
+                encode_timestamp_and_sequence(timestamp, last_reserved),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => {
+                    self.check_exhaustion(timestamp);
+                    return Ok((timestamp, first_sequence, count));
+                }
+                Err(actual) => {
+                    last_timestamp_and_sequence = actual;
+                }
+            }
+        }
+    }
+
     /// Parses a Snowflake ID into its components
     /// # Arguments
     /// * `id` - The Snowflake ID to parse
@@ -140,6 +1164,8 @@ impl Snowflake {
     /// A tuple containing the timestamp, node ID, and sequence number
     /// # Example
     /// ```
+    /// use snowflake_rs_impl::snowflake::Snowflake;
+    ///
     /// let (timestamp, node, sequence) = Snowflake::parse_id(1234567890);
     /// println!("Timestamp: {}, Node: {}, Sequence: {}", timestamp, node, sequence);
     /// ```
@@ -149,11 +1175,89 @@ impl Snowflake {
         let sequence = (id & ((1 << STEP_BITS) - 1)) as u16;
         (timestamp, node, sequence)
     }
-    // Waits until the next millisecond
+
+    /// Composes a Snowflake ID from its components; the inverse of `parse_id`
+    ///
+    /// Uses the default layout; see `Layout::compose` for other layouts. Handy for
+    /// building boundary IDs, e.g. the smallest ID of a given millisecond for range queries.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp` - The timestamp offset in milliseconds since the epoch (0 to 2^41 - 1)
+    /// * `node` - The node ID (0-1023)
+    /// * `sequence` - The sequence number (0-4095)
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the composed ID or a SnowflakeError
+    ///
+    /// # Errors
+    ///
+    /// - SnowflakeError::TimestampOutOfRange if the timestamp does not fit in 41 bits
+    /// - SnowflakeError::MachineIdOutOfRange if the node ID is greater than 1023
+    /// - SnowflakeError::SequenceOutOfRange if the sequence number is greater than 4095
+    ///
+    /// # Example
+    /// ```
+    /// use snowflake_rs_impl::snowflake::Snowflake;
+    ///
+    /// let id = Snowflake::compose_id(1000, 1, 5).unwrap();
+    /// assert_eq!(Snowflake::parse_id(id.as_u64()), (1000, 1, 5));
+    /// ```
+    pub fn compose_id(timestamp: u64, node: u16, sequence: u16) -> Result<SnowflakeId, SnowflakeError> {
+        Layout::DEFAULT.compose(timestamp, node, sequence).map(SnowflakeId::from)
+    }
+
+    // Takes a token from the rate limiter, if one is configured
+    fn acquire_rate_limit(&self) -> Result<(), SnowflakeError> {
+        match &self.rate_limiter {
+            Some(rate_limiter) => rate_limiter.acquire(),
+            None => Ok(()),
+        }
+    }
+
+    // Records an issued ID in the duplicate guard, if any
+    #[cfg(feature = "duplicate-guard")]
+    #[inline]
+    fn check_duplicate(&self, id: u64) {
+        if let Some(guard) = &self.duplicate_guard {
+            guard.check(id);
+        }
+    }
+
+    #[cfg(not(feature = "duplicate-guard"))]
+    #[inline(always)]
+    fn check_duplicate(&self, _id: u64) {}
+
+    // Records issued sequence numbers in the audit log, if any
+    #[inline]
+    fn audit(&self, node: u16, timestamp: i64, sequence: u16) {
+        if let Some(audit_log) = &self.audit_log {
+            audit_log.record(node, timestamp, sequence);
+        }
+    }
+
+    // Feeds the timestamp of an issued ID to the exhaustion monitor
+    #[inline]
+    fn check_exhaustion(&self, timestamp: i64) {
+        if let Some(exhaustion) = &self.exhaustion {
+            exhaustion.check(timestamp);
+        }
+    }
+
+    // Waits until the next millisecond, counting the caller as waiting meanwhile
     fn wait_next_millis(&self, last_timestamp: i64) -> Result<i64, SnowflakeError> {
+        self.waiting.fetch_add(1, Ordering::Relaxed);
+        let result = self.spin_past(last_timestamp);
+        self.waiting.fetch_sub(1, Ordering::Relaxed);
+        result
+    }
+
+    // Spins until the clock is past `last_timestamp`, for at most 5 seconds
+    fn spin_past(&self, last_timestamp: i64) -> Result<i64, SnowflakeError> {
         let start = Instant::now();
         loop {
-            let current_timestamp = self.current_time_millis();
+            let current_timestamp = self.current_time_millis()?;
             if current_timestamp > last_timestamp {
                 return Ok(current_timestamp);
             }
@@ -165,30 +1269,102 @@ impl Snowflake {
     }
 
     // Creates the final ID by combining timestamp, node ID, and sequence
-    fn create_id(&self, timestamp: i64, sequence: u16) -> u64 {
-        (((timestamp - self.epoch_ms) as u64) << TIMESTAMP_SHIFT)
-            | ((self.node as u64) << NODE_SHIFT)
-            | sequence as u64
+    // Fails with SnowflakeError::TimestampExhausted instead of letting the timestamp
+    // overflow into the other fields, unless the strategy allows a second era
+    fn create_id(&self, timestamp: i64, sequence: u16) -> Result<u64, SnowflakeError> {
+        self.create_id_for_node(self.node, timestamp, sequence)
+    }
+
+    // Same as `create_id`, with another node ID
+    fn create_id_for_node(&self, node: u16, timestamp: i64, sequence: u16) -> Result<u64, SnowflakeError> {
+        let max_timestamp = self.layout.max_timestamp();
+        let mut offset = (timestamp - self.epoch_ms) as u64;
+        let mut era = 0;
+        if offset > max_timestamp && self.exhaustion_strategy == ExhaustionStrategy::Era {
+            offset -= max_timestamp + 1;
+            era = ERA_BIT;
+        }
+        if offset > max_timestamp {
+            return Err(SnowflakeError::TimestampExhausted);
+        }
+        Ok(self.layout.with_checksum(
+            era | (offset << self.layout.timestamp_shift())
+                | ((node as u64) << self.layout.node_shift())
+                | ((sequence as u64) << self.layout.sequence_shift()),
+        ))
     }
 
     // Returns the current timestamp in milliseconds
-    fn current_time_millis(&self) -> i64 {
-        use std::time::{SystemTime, UNIX_EPOCH};
-        SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("Time went backwards")
-            .as_millis() as i64
+    pub(crate) fn current_time_millis(&self) -> Result<i64, SnowflakeError> {
+        self.clock.try_now_millis()
+    }
+}
+
+impl Drop for Snowflake {
+    fn drop(&mut self) {
+        let Some(persistence) = &self.persistence else {
+            return;
+        };
+        if persistence.closed.load(Ordering::Acquire) {
+            return;
+        }
+        if let Err(err) = self.flush_state(true) {
+            error!("Failed to persist generator state on drop: {}", err);
+        }
+    }
+}
+
+impl Persistence {
+    // Reads the previous state from the store and checks that it belongs to this generator
+    fn open(store: StateFile, node: u16, epoch_ms: i64, layout: &Layout) -> Result<Self, SnowflakeError> {
+        let previous = store.load().map_err(|err| SnowflakeError::StateStore(err.to_string()))?;
+        if let Some(previous) = &previous {
+            if previous.snapshot.node != node || previous.snapshot.epoch != epoch_ms || previous.snapshot.layout != *layout {
+                return Err(SnowflakeError::StateStore(format!(
+                    "state file {} belongs to node {} with epoch {} and layout {}/{}",
+                    store.path().display(),
+                    previous.snapshot.node,
+                    previous.snapshot.epoch,
+                    previous.snapshot.layout.node_bits(),
+                    previous.snapshot.layout.step_bits(),
+                )));
+            }
+            if previous.snapshot.last_sequence > layout.max_sequence() || previous.snapshot.last_timestamp < 0 {
+                return Err(SnowflakeError::StateStore(format!("state file {} is corrupt", store.path().display())));
+            }
+        }
+        Ok(Persistence {
+            store,
+            previous,
+            closed: AtomicBool::new(false),
+        })
     }
 }
 
+// Checks that `now` is at or after `epoch` and fits in the timestamp field
+pub(crate) fn validate_epoch(epoch: i64, now: i64, layout: &Layout, strategy: ExhaustionStrategy) -> Result<(), SnowflakeError> {
+    let max_offset = match strategy {
+        ExhaustionStrategy::Error => layout.max_timestamp(),
+        ExhaustionStrategy::Era => layout.max_timestamp() * 2 + 1,
+    };
+    let reason = if now < epoch {
+        InvalidEpochReason::InFuture
+    } else if (now as i128 - epoch as i128) > max_offset as i128 {
+        InvalidEpochReason::BeyondLayoutRange { max_offset }
+    } else {
+        return Ok(());
+    };
+    Err(SnowflakeError::InvalidEpoch { epoch, now, reason })
+}
+
 // Encodes timestamp and sequence into a single i64 value
-fn encode_timestamp_and_sequence(timestamp: i64, sequence: i64) -> i64 {
-    (timestamp << STEP_BITS) | sequence
+pub(crate) fn encode_timestamp_and_sequence(timestamp: i64, sequence: i64) -> i64 {
+    (timestamp << STATE_SEQUENCE_BITS) | sequence
 }
 
 // Decodes timestamp and sequence from a single i64 value
-fn decode_timestamp_and_sequence(value: i64) -> (i64, i64) {
-    let timestamp = value >> STEP_BITS;
-    let sequence = value & STEP_MAX as i64;
+pub(crate) fn decode_timestamp_and_sequence(value: i64) -> (i64, i64) {
+    let timestamp = value >> STATE_SEQUENCE_BITS;
+    let sequence = value & ((1 << STATE_SEQUENCE_BITS) - 1);
     (timestamp, sequence)
 }
\ No newline at end of file
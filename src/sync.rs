@@ -0,0 +1,10 @@
+// Atomic types used by the generators.
+//
+// Under `--cfg loom` these are swapped for loom's model-checked types so the lock-free
+// paths can be exhaustively tested (see tests/loom_test.rs).
+
+#[cfg(loom)]
+pub(crate) use loom::sync::atomic::{AtomicI64, AtomicUsize, Ordering};
+
+#[cfg(not(loom))]
+pub(crate) use std::sync::atomic::{AtomicI64, AtomicUsize, Ordering};
@@ -0,0 +1,494 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use parking_lot::Mutex;
+
+use crate::clock::{Clock, SystemClock};
+use crate::generator::{IdError, IdGenerator};
+use crate::id::SnowflakeId;
+use crate::layout::Layout;
+use crate::snowflake::{Snowflake, SnowflakeBuilder, SnowflakeError, DEFAULT_EPOCH};
+
+/// A clock running a fixed offset ahead of (or, if negative, behind) another clock
+#[derive(Debug)]
+pub struct SkewedClock {
+    inner: Arc<dyn Clock>,
+    offset_ms: i64,
+}
+
+impl SkewedClock {
+    /// Creates a clock that reports `inner`'s time plus `offset_ms`
+    pub fn new(inner: Arc<dyn Clock>, offset_ms: i64) -> Self {
+        SkewedClock { inner, offset_ms }
+    }
+}
+
+impl Clock for SkewedClock {
+    fn now_millis(&self) -> i64 {
+        self.inner.now_millis() + self.offset_ms
+    }
+
+    fn try_now_millis(&self) -> Result<i64, SnowflakeError> {
+        Ok(self.inner.try_now_millis()? + self.offset_ms)
+    }
+}
+
+/// A clock that only moves when told to
+///
+/// Useful as the base clock for deterministic tests.
+#[derive(Debug, Default)]
+pub struct ManualClock {
+    now_ms: AtomicI64,
+}
+
+impl ManualClock {
+    /// Creates a clock reporting `now_ms` milliseconds since Unix epoch
+    pub fn new(now_ms: i64) -> Self {
+        ManualClock {
+            now_ms: AtomicI64::new(now_ms),
+        }
+    }
+
+    /// Sets the current time
+    pub fn set(&self, now_ms: i64) {
+        self.now_ms.store(now_ms, Ordering::SeqCst);
+    }
+
+    /// Moves the current time by `delta_ms` (backwards if negative)
+    pub fn advance(&self, delta_ms: i64) {
+        self.now_ms.fetch_add(delta_ms, Ordering::SeqCst);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now_millis(&self) -> i64 {
+        self.now_ms.load(Ordering::SeqCst)
+    }
+}
+
+/// A fault that a `FaultyClock` can inject
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClockFault {
+    /// Jump the clock backwards by the given number of milliseconds
+    StepBackward(i64),
+    /// Jump the clock forwards by the given number of milliseconds
+    StepForward(i64),
+    /// Freeze the clock at its current reading until `Resume`
+    Pause,
+    /// Unfreeze a paused clock; time continues from the inner clock
+    Resume,
+    /// Add deterministic pseudo-random noise of up to +/- the given number of milliseconds
+    /// to every reading; `Jitter(0)` turns jitter off
+    Jitter(i64),
+}
+
+// Mutable state of a FaultyClock
+#[derive(Debug)]
+struct FaultState {
+    calls: u64,
+    offset_ms: i64,
+    paused_at: Option<i64>,
+    jitter_ms: i64,
+    rng: u64,
+    schedule: BTreeMap<u64, Vec<ClockFault>>,
+}
+
+/// A clock wrapper that injects faults on a deterministic schedule
+///
+/// Faults are scheduled by reading number: `at_reading(n, fault)` applies `fault` right
+/// before the `n`-th call to `now_millis` (counting from 0) returns. Faults can also be
+/// applied immediately with `inject`. Combined with `ManualClock`, this allows fully
+/// deterministic tests of clock rollback and stalls.
+///
+/// # Example
+/// ```
+/// use std::sync::Arc;
+/// use snowflake_rs_impl::clock::Clock;
+/// use snowflake_rs_impl::test_utils::{ClockFault, FaultyClock, ManualClock};
+///
+/// let clock = FaultyClock::new(Arc::new(ManualClock::new(1_000)))
+///     .at_reading(1, ClockFault::StepBackward(10));
+/// assert_eq!(clock.now_millis(), 1_000);
+/// assert_eq!(clock.now_millis(), 990);
+/// ```
+#[derive(Debug)]
+pub struct FaultyClock {
+    inner: Arc<dyn Clock>,
+    state: Mutex<FaultState>,
+}
+
+impl FaultyClock {
+    /// Wraps `inner` without any faults
+    pub fn new(inner: Arc<dyn Clock>) -> Self {
+        FaultyClock {
+            inner,
+            state: Mutex::new(FaultState {
+                calls: 0,
+                offset_ms: 0,
+                paused_at: None,
+                jitter_ms: 0,
+                rng: 0x2545_f491_4f6c_dd1d,
+                schedule: BTreeMap::new(),
+            }),
+        }
+    }
+
+    /// Schedules `fault` to be applied at the `reading`-th call to `now_millis`
+    pub fn at_reading(self, reading: u64, fault: ClockFault) -> Self {
+        self.state.lock().schedule.entry(reading).or_default().push(fault);
+        self
+    }
+
+    /// Applies `fault` immediately
+    pub fn inject(&self, fault: ClockFault) {
+        let now = self.inner.now_millis();
+        apply_fault(&mut self.state.lock(), now, fault);
+    }
+
+    /// Returns how many times the clock has been read
+    pub fn readings(&self) -> u64 {
+        self.state.lock().calls
+    }
+
+    // Applies the faults to a reading `now` of the inner clock
+    fn reading(&self, now: i64) -> i64 {
+        let mut state = self.state.lock();
+        let reading = state.calls;
+        state.calls += 1;
+        if let Some(faults) = state.schedule.remove(&reading) {
+            for fault in faults {
+                apply_fault(&mut state, now, fault);
+            }
+        }
+
+        let base = state.paused_at.unwrap_or(now + state.offset_ms);
+        if state.jitter_ms == 0 {
+            return base;
+        }
+        // xorshift64, so jitter is the same on every run
+        state.rng ^= state.rng << 13;
+        state.rng ^= state.rng >> 7;
+        state.rng ^= state.rng << 17;
+        let span = 2 * state.jitter_ms as u64 + 1;
+        base + (state.rng % span) as i64 - state.jitter_ms
+    }
+}
+
+impl Clock for FaultyClock {
+    fn now_millis(&self) -> i64 {
+        self.reading(self.inner.now_millis())
+    }
+
+    fn try_now_millis(&self) -> Result<i64, SnowflakeError> {
+        Ok(self.reading(self.inner.try_now_millis()?))
+    }
+}
+
+// Applies a fault given the inner clock's current reading
+fn apply_fault(state: &mut FaultState, now: i64, fault: ClockFault) {
+    match fault {
+        ClockFault::StepBackward(ms) => shift(state, -ms),
+        ClockFault::StepForward(ms) => shift(state, ms),
+        ClockFault::Pause => {
+            if state.paused_at.is_none() {
+                state.paused_at = Some(now + state.offset_ms);
+            }
+        }
+        ClockFault::Resume => {
+            if let Some(paused_at) = state.paused_at.take() {
+                // Continue from the frozen reading instead of jumping ahead
+                state.offset_ms = paused_at - now;
+            }
+        }
+        ClockFault::Jitter(ms) => state.jitter_ms = ms.abs(),
+    }
+}
+
+// Moves the clock, including a paused reading
+fn shift(state: &mut FaultState, delta_ms: i64) {
+    state.offset_ms += delta_ms;
+    if let Some(paused_at) = state.paused_at.as_mut() {
+        *paused_at += delta_ms;
+    }
+}
+
+/// A generator whose IDs depend only on its settings and how many IDs it has issued
+///
+/// The `n`-th ID (counting from 0) has timestamp `start_ms + n / ids_per_millisecond` and
+/// sequence number `n % ids_per_millisecond`; no clock is read. Every run of a test
+/// therefore sees the same IDs, which keeps snapshots and golden files stable.
+///
+/// # Example
+/// ```
+/// use snowflake_rs_impl::snowflake::Snowflake;
+/// use snowflake_rs_impl::test_utils::DeterministicSnowflake;
+///
+/// let generator = DeterministicSnowflake::new(1, 1640995200000);
+/// let first = generator.generate().unwrap();
+/// let second = generator.generate().unwrap();
+/// assert_eq!(second - first, 1 << 22); // one millisecond later
+/// assert_eq!(Snowflake::parse_id(first).1, 1);
+/// ```
+#[derive(Debug)]
+pub struct DeterministicSnowflake {
+    node: u16,
+    epoch_ms: i64,
+    layout: Layout,
+    start_ms: i64,
+    ids_per_millisecond: u64,
+    issued: AtomicU64,
+}
+
+impl DeterministicSnowflake {
+    /// Creates a generator whose first ID has timestamp `start_ms`
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The node ID of every generated ID
+    /// * `start_ms` - The timestamp of the first ID in milliseconds since Unix epoch
+    pub fn new(node: u16, start_ms: i64) -> Self {
+        DeterministicSnowflake {
+            node,
+            epoch_ms: DEFAULT_EPOCH,
+            layout: Layout::DEFAULT,
+            start_ms,
+            ids_per_millisecond: 1,
+            issued: AtomicU64::new(0),
+        }
+    }
+
+    /// Sets the epoch in milliseconds. Defaults to DEFAULT_EPOCH.
+    pub fn epoch(mut self, epoch: i64) -> Self {
+        self.epoch_ms = epoch;
+        self
+    }
+
+    /// Sets the bit layout of generated IDs. Defaults to `Layout::DEFAULT`.
+    pub fn layout(mut self, layout: Layout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Sets how many consecutive IDs share a timestamp, capped at the layout's sequence
+    /// numbers per millisecond. Defaults to 1, so every ID is one millisecond after the
+    /// previous one.
+    pub fn ids_per_millisecond(mut self, ids_per_millisecond: u64) -> Self {
+        self.ids_per_millisecond = ids_per_millisecond;
+        self
+    }
+
+    /// Generates the next ID
+    ///
+    /// # Errors
+    ///
+    /// - SnowflakeError::MachineIdOutOfRange if the node ID does not fit in the layout
+    /// - SnowflakeError::TimestampOutOfRange if the timestamp is before the epoch or does
+    ///   not fit in the layout
+    pub fn generate(&self) -> Result<u64, SnowflakeError> {
+        let issued = self.issued.fetch_add(1, Ordering::Relaxed);
+        let ids_per_millisecond = self.ids_per_millisecond.clamp(1, self.layout.max_sequence() as u64 + 1);
+        let timestamp = i64::try_from(issued / ids_per_millisecond)
+            .ok()
+            .and_then(|elapsed| self.start_ms.checked_add(elapsed))
+            .and_then(|timestamp| timestamp.checked_sub(self.epoch_ms))
+            .and_then(|offset| u64::try_from(offset).ok())
+            .ok_or(SnowflakeError::TimestampOutOfRange)?;
+        self.layout.compose(timestamp, self.node, (issued % ids_per_millisecond) as u16)
+    }
+
+    /// Generates the next ID wrapped in a `SnowflakeId`
+    ///
+    /// # Errors
+    ///
+    /// Same as `generate`
+    pub fn generate_id(&self) -> Result<SnowflakeId, SnowflakeError> {
+        self.generate().map(SnowflakeId::from)
+    }
+
+    /// Returns how many IDs have been generated (or attempted) since creation or `reset`
+    pub fn issued(&self) -> u64 {
+        self.issued.load(Ordering::Relaxed)
+    }
+
+    /// Starts over, so the next ID is the first one again
+    pub fn reset(&self) {
+        self.issued.store(0, Ordering::Relaxed);
+    }
+}
+
+impl IdGenerator for DeterministicSnowflake {
+    fn next_id(&self) -> Result<SnowflakeId, IdError> {
+        self.generate_id()
+    }
+}
+
+/// Simulates a cluster of generators running concurrently
+///
+/// Each simulated node gets its own `Snowflake` (built from a shared template, with its
+/// own node ID and optionally a skewed clock) and one or more threads generating IDs.
+/// `run` then checks that IDs are unique across the whole cluster and strictly
+/// increasing per generating thread.
+///
+/// # Example
+/// ```
+/// use snowflake_rs_impl::snowflake::Snowflake;
+/// use snowflake_rs_impl::test_utils::ClusterSimulation;
+///
+/// let report = ClusterSimulation::new(Snowflake::builder(0), 0..8)
+///     .ids_per_node(10_000)
+///     .clock_skew(3, -250)
+///     .run();
+/// report.assert_ok();
+/// ```
+#[derive(Debug, Clone)]
+pub struct ClusterSimulation {
+    template: SnowflakeBuilder,
+    nodes: Vec<u16>,
+    ids_per_node: usize,
+    threads_per_node: usize,
+    base_clock: Arc<dyn Clock>,
+    skews: HashMap<u16, i64>,
+}
+
+/// Outcome of a `ClusterSimulation` run
+#[derive(Debug)]
+pub struct SimulationReport {
+    /// Number of IDs generated across all nodes
+    pub total_ids: usize,
+    /// Number of IDs generated per node
+    pub ids_per_node: HashMap<u16, usize>,
+    /// IDs that were generated more than once
+    pub duplicate_ids: Vec<u64>,
+    /// Nodes on which a thread observed an ID not greater than its previous one
+    pub non_monotonic_nodes: Vec<u16>,
+    /// Generation errors, by node
+    pub errors: Vec<(u16, SnowflakeError)>,
+}
+
+impl ClusterSimulation {
+    /// Creates a simulation with one generator per node ID
+    ///
+    /// # Arguments
+    ///
+    /// * `template` - Builder whose settings are shared by all nodes; its node ID and clock
+    ///   are replaced per node. It must not use `persist_on_drop`.
+    /// * `nodes` - The node IDs to simulate; duplicates are ignored
+    pub fn new(template: SnowflakeBuilder, nodes: impl IntoIterator<Item = u16>) -> Self {
+        let mut seen = HashSet::new();
+        ClusterSimulation {
+            template,
+            nodes: nodes.into_iter().filter(|node| seen.insert(*node)).collect(),
+            ids_per_node: 1000,
+            threads_per_node: 1,
+            base_clock: Arc::new(SystemClock),
+            skews: HashMap::new(),
+        }
+    }
+
+    /// Sets how many IDs each node generates (default 1000)
+    pub fn ids_per_node(mut self, ids_per_node: usize) -> Self {
+        self.ids_per_node = ids_per_node;
+        self
+    }
+
+    /// Sets how many threads share each node's generator (default 1)
+    pub fn threads_per_node(mut self, threads_per_node: usize) -> Self {
+        self.threads_per_node = threads_per_node.max(1);
+        self
+    }
+
+    /// Sets the clock all nodes derive their time from (default `SystemClock`)
+    pub fn base_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.base_clock = clock;
+        self
+    }
+
+    /// Runs a node's clock `offset_ms` ahead of the base clock (behind if negative)
+    pub fn clock_skew(mut self, node: u16, offset_ms: i64) -> Self {
+        self.skews.insert(node, offset_ms);
+        self
+    }
+
+    /// Runs the simulation and checks the generated IDs
+    ///
+    /// Nodes that fail to build (e.g. a node ID out of range) are reported in `errors`.
+    pub fn run(&self) -> SimulationReport {
+        let mut errors = Vec::new();
+        let mut handles = Vec::new();
+
+        for &node in &self.nodes {
+            let clock: Arc<dyn Clock> = match self.skews.get(&node) {
+                Some(&offset_ms) => Arc::new(SkewedClock::new(Arc::clone(&self.base_clock), offset_ms)),
+                None => Arc::clone(&self.base_clock),
+            };
+            let snowflake = match self.template.clone().node(node).clock(clock).build() {
+                Ok(snowflake) => Arc::new(snowflake),
+                Err(err) => {
+                    errors.push((node, err));
+                    continue;
+                }
+            };
+            for thread_index in 0..self.threads_per_node {
+                let count = self.ids_per_node / self.threads_per_node
+                    + usize::from(thread_index < self.ids_per_node % self.threads_per_node);
+                let snowflake = Arc::clone(&snowflake);
+                handles.push((node, thread::spawn(move || generate_ids(&snowflake, count))));
+            }
+        }
+
+        let mut seen = HashSet::new();
+        let mut report = SimulationReport {
+            total_ids: 0,
+            ids_per_node: HashMap::new(),
+            duplicate_ids: Vec::new(),
+            non_monotonic_nodes: Vec::new(),
+            errors,
+        };
+        for (node, handle) in handles {
+            let (ids, thread_errors) = handle.join().expect("simulation thread panicked");
+            if ids.windows(2).any(|w| w[0] >= w[1]) && !report.non_monotonic_nodes.contains(&node) {
+                report.non_monotonic_nodes.push(node);
+            }
+            report.total_ids += ids.len();
+            *report.ids_per_node.entry(node).or_insert(0) += ids.len();
+            report.duplicate_ids.extend(ids.into_iter().filter(|id| !seen.insert(*id)));
+            report.errors.extend(thread_errors.into_iter().map(|err| (node, err)));
+        }
+        report
+    }
+}
+
+impl SimulationReport {
+    /// Returns true if all IDs were unique and monotonic and no errors occurred
+    pub fn is_ok(&self) -> bool {
+        self.duplicate_ids.is_empty() && self.non_monotonic_nodes.is_empty() && self.errors.is_empty()
+    }
+
+    /// Panics with a summary of the problems if the simulation was not clean
+    pub fn assert_ok(&self) {
+        assert!(
+            self.is_ok(),
+            "cluster simulation failed: {} duplicate IDs (first: {:?}), non-monotonic nodes: {:?}, {} errors (first: {:?})",
+            self.duplicate_ids.len(),
+            self.duplicate_ids.first(),
+            self.non_monotonic_nodes,
+            self.errors.len(),
+            self.errors.first(),
+        );
+    }
+}
+
+// Generates `count` IDs and collects any errors along the way
+fn generate_ids(snowflake: &Snowflake, count: usize) -> (Vec<u64>, Vec<SnowflakeError>) {
+    let mut ids = Vec::with_capacity(count);
+    let mut errors = Vec::new();
+    for _ in 0..count {
+        match snowflake.generate() {
+            Ok(id) => ids.push(id),
+            Err(err) => errors.push(err),
+        }
+    }
+    (ids, errors)
+}
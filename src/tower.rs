@@ -0,0 +1,182 @@
+use std::fmt;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use http::header::{HeaderName, HeaderValue};
+use http::{Extensions, Request};
+use log::warn;
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::generator::IdGenerator;
+use crate::id::{IdValidator, SnowflakeId};
+
+/// Header carrying the request ID
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Default limit on how far an incoming request ID may be ahead of the system clock
+pub const DEFAULT_MAX_INCOMING_SKEW: Duration = Duration::from_secs(60);
+
+/// The Snowflake ID assigned to a request by `RequestIdLayer`
+///
+/// Stored in the request extensions, so handlers can read it with
+/// `RequestId::from_extensions` (or as `Extension<RequestId>` in axum).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RequestId(pub SnowflakeId);
+
+impl RequestId {
+    /// Returns the request ID stored in `extensions`, if any
+    pub fn from_extensions(extensions: &Extensions) -> Option<RequestId> {
+        extensions.get::<RequestId>().copied()
+    }
+
+    /// Returns the Snowflake ID
+    pub fn id(&self) -> SnowflakeId {
+        self.0
+    }
+}
+
+impl fmt::Display for RequestId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Tower layer that assigns a Snowflake ID to every request
+///
+/// For each request, the wrapped service receives a fresh ID both as a `RequestId`
+/// extension and as the `x-request-id` header (or the header set with `header`). If ID
+/// generation fails, the failure is logged and the request is passed on without an ID:
+/// an incoming header that was not kept is removed rather than passed on unchecked.
+///
+/// IDs are generated synchronously inside `Service::call`, so a generator whose rate
+/// limit uses `ThrottleMode::Wait` blocks the executor thread while it waits; prefer
+/// `ThrottleMode::Error` here.
+///
+/// # Example
+/// ```
+/// use std::sync::Arc;
+/// use snowflake_rs_impl::snowflake::Snowflake;
+/// use snowflake_rs_impl::tower::RequestIdLayer;
+///
+/// let layer = RequestIdLayer::new(Arc::new(Snowflake::new(1, None).unwrap()));
+/// // e.g. `axum::Router::new().route(...).layer(layer)`
+/// # let _ = layer;
+/// ```
+#[derive(Clone)]
+pub struct RequestIdLayer {
+    generator: Arc<dyn IdGenerator>,
+    header: HeaderName,
+    preserve_incoming: bool,
+    validator: IdValidator,
+}
+
+impl RequestIdLayer {
+    /// Creates a layer drawing request IDs from `generator`
+    pub fn new(generator: Arc<dyn IdGenerator>) -> Self {
+        RequestIdLayer {
+            generator,
+            header: REQUEST_ID_HEADER,
+            preserve_incoming: false,
+            validator: IdValidator::new().max_future_skew(DEFAULT_MAX_INCOMING_SKEW),
+        }
+    }
+
+    /// Sets the header carrying the request ID. Defaults to `x-request-id`.
+    pub fn header(mut self, header: HeaderName) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// Keeps a request ID set by an upstream service instead of generating a new one
+    ///
+    /// The incoming header is only kept if it is a decimal Snowflake ID accepted by the
+    /// validator (see `validator`); anything else is replaced. Defaults to false.
+    pub fn preserve_incoming(mut self, preserve: bool) -> Self {
+        self.preserve_incoming = preserve;
+        self
+    }
+
+    /// Sets the checks an incoming request ID must pass to be kept
+    ///
+    /// Defaults to rejecting IDs with the reserved top bit set and IDs more than
+    /// `DEFAULT_MAX_INCOMING_SKEW` ahead of the system clock, assuming DEFAULT_EPOCH and
+    /// the default layout. Set the epoch and layout of the upstream generators here if
+    /// they differ.
+    pub fn validator(mut self, validator: IdValidator) -> Self {
+        self.validator = validator;
+        self
+    }
+}
+
+impl fmt::Debug for RequestIdLayer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RequestIdLayer")
+            .field("header", &self.header)
+            .field("preserve_incoming", &self.preserve_incoming)
+            .field("validator", &self.validator)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S> Layer<S> for RequestIdLayer {
+    type Service = RequestIdService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestIdService {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+/// Service created by `RequestIdLayer`
+#[derive(Clone, Debug)]
+pub struct RequestIdService<S> {
+    inner: S,
+    layer: RequestIdLayer,
+}
+
+impl<S> RequestIdService<S> {
+    // Returns the upstream request ID, if preserving it is enabled and it passes the
+    // validator
+    fn incoming_id<B>(&self, request: &Request<B>) -> Option<SnowflakeId> {
+        if !self.layer.preserve_incoming {
+            return None;
+        }
+        let value = request.headers().get(&self.layer.header)?.to_str().ok()?;
+        self.layer.validator.validate(value.parse::<u64>().ok()?).ok()
+    }
+}
+
+impl<S, B> Service<Request<B>> for RequestIdService<S>
+where
+    S: Service<Request<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<B>) -> Self::Future {
+        let id = match self.incoming_id(&request) {
+            Some(id) => Ok(id),
+            None => self.layer.generator.next_id(),
+        };
+        match id {
+            Ok(id) => {
+                request.extensions_mut().insert(RequestId(id));
+                request.headers_mut().insert(self.layer.header.clone(), HeaderValue::from(id.as_u64()));
+            }
+            Err(err) => {
+                warn!("Failed to generate a request ID: {}", err);
+                request.headers_mut().remove(&self.layer.header);
+            }
+        }
+        self.inner.call(request)
+    }
+}
@@ -0,0 +1,209 @@
+use ::uuid::Uuid;
+
+use crate::exhaustion::ERA_BIT;
+use crate::id::SnowflakeId;
+use crate::layout::Layout;
+use crate::snowflake::{InvalidIdReason, Snowflake, SnowflakeError, DEFAULT_EPOCH};
+
+/// Tag marking a UUIDv8 that carries an embedded Snowflake ID
+const MAGIC: u128 = 0b101_0011;
+
+/// Width of the embedded epoch; covers epochs from 1970 up to 2039
+const EPOCH_BITS: u32 = 41;
+
+/// Largest epoch, in milliseconds since Unix epoch, that can be embedded
+pub const MAX_EMBEDDED_EPOCH: i64 = (1 << EPOCH_BITS) - 1;
+
+/// A Snowflake ID extracted from a UUID, with the epoch and layout it was generated with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EmbeddedSnowflake {
+    /// The Snowflake ID
+    pub id: SnowflakeId,
+    /// The epoch in milliseconds since Unix epoch
+    pub epoch: i64,
+    /// The bit layout of the ID
+    pub layout: Layout,
+}
+
+/// Embeds a Snowflake ID, with its epoch and layout, into a UUIDv8
+///
+/// The 128 bits of the UUID are laid out as follows, from most to least significant:
+///
+/// ```text
+/// | ID bits 63-16 (48) | version 8 (4) | ID bits 15-4 (12) | variant 0b10 (2) |
+/// | ID bits 3-0 (4) | tag (7) | node bits (5) | sequence bits (5) | epoch (41) |
+/// ```
+///
+/// Since the ID occupies the most significant free bits, UUIDs compare (bytewise, as
+/// databases do) in the same order as the IDs they embed. The embedding is lossless:
+/// `extract` returns exactly the ID, epoch and layout passed in.
+///
+/// # Arguments
+///
+/// * `id` - The Snowflake ID
+/// * `epoch` - The epoch in milliseconds the ID was generated with. If None, DEFAULT_EPOCH is used.
+/// * `layout` - The bit layout the ID was generated with
+///
+/// # Errors
+///
+/// - SnowflakeError::UnembeddableEpoch if the epoch is negative or greater than
+///   `MAX_EMBEDDED_EPOCH`
+/// - SnowflakeError::InvalidLayout if the layout has a check field, which the UUID
+///   cannot record
+pub fn embed(id: SnowflakeId, epoch: Option<i64>, layout: &Layout) -> Result<Uuid, SnowflakeError> {
+    if layout.check_bits() != 0 {
+        return Err(SnowflakeError::InvalidLayout);
+    }
+    let epoch = epoch.unwrap_or(DEFAULT_EPOCH);
+    if !(0..=MAX_EMBEDDED_EPOCH).contains(&epoch) {
+        return Err(SnowflakeError::UnembeddableEpoch(epoch));
+    }
+    let id = id.as_u64() as u128;
+    let bits = ((id >> 16) << 80)
+        | (0x8 << 76)
+        | (((id >> 4) & 0xfff) << 64)
+        | (0b10 << 62)
+        | ((id & 0xf) << 58)
+        | (MAGIC << 51)
+        | ((layout.node_bits() as u128) << 46)
+        | ((layout.step_bits() as u128) << 41)
+        | epoch as u128;
+    Ok(Uuid::from_u128(bits))
+}
+
+/// Extracts a Snowflake ID embedded with `embed`
+///
+/// # Errors
+///
+/// Returns SnowflakeError::InvalidUuid if the UUID is not a UUIDv8 with an embedded
+/// Snowflake ID
+pub fn extract(uuid: &Uuid) -> Result<EmbeddedSnowflake, SnowflakeError> {
+    let bits = uuid.as_u128();
+    let version = (bits >> 76) & 0xf;
+    let variant = (bits >> 62) & 0b11;
+    let magic = (bits >> 51) & 0x7f;
+    if version != 0x8 || variant != 0b10 || magic != MAGIC {
+        return Err(SnowflakeError::InvalidUuid);
+    }
+    let node_bits = ((bits >> 46) & 0x1f) as u8;
+    let step_bits = ((bits >> 41) & 0x1f) as u8;
+    let layout = Layout::new(node_bits, step_bits).map_err(|_| SnowflakeError::InvalidUuid)?;
+    let id = (((bits >> 80) as u64) << 16) | ((((bits >> 64) & 0xfff) as u64) << 4) | (((bits >> 58) & 0xf) as u64);
+    Ok(EmbeddedSnowflake {
+        id: SnowflakeId::from(id),
+        epoch: (bits & ((1 << EPOCH_BITS) - 1)) as i64,
+        layout,
+    })
+}
+
+/// Returns true if `uuid` is a UUIDv8 with an embedded Snowflake ID (see `embed`)
+pub fn is_embedded_snowflake(uuid: &Uuid) -> bool {
+    extract(uuid).is_ok()
+}
+
+/// Maps a Snowflake ID to a UUIDv7 with the same time and ordering
+///
+/// The UUIDv7 timestamp is the time the ID was generated (milliseconds since Unix epoch),
+/// followed by the node ID and sequence number, so the UUID is a valid, time-ordered
+/// UUIDv7 that sorts like the ID among UUIDs mapped with the same epoch and layout. Unlike
+/// `embed`, the epoch and layout are not recorded; `from_uuid_v7` needs them back.
+///
+/// # Errors
+///
+/// - SnowflakeError::InvalidId with `InvalidIdReason::ReservedBitSet` if the ID has
+///   `ERA_BIT` set; second-era IDs have no UUIDv7 mapping
+/// - SnowflakeError::TimestampOutOfRange if the time the ID was generated is negative or
+///   does not fit in the 48-bit UUIDv7 timestamp
+pub fn to_uuid_v7(id: SnowflakeId, epoch: Option<i64>, layout: &Layout) -> Result<Uuid, SnowflakeError> {
+    if id.as_u64() & ERA_BIT != 0 {
+        return Err(SnowflakeError::InvalidId(InvalidIdReason::ReservedBitSet));
+    }
+    let (timestamp, node, sequence) = layout.decompose(id.as_u64());
+    let unix_millis = epoch.unwrap_or(DEFAULT_EPOCH).saturating_add(timestamp as i64);
+    if !(0..1 << 48).contains(&unix_millis) {
+        return Err(SnowflakeError::TimestampOutOfRange);
+    }
+    let fields = node_and_sequence(node, sequence, layout) as u128;
+    let bits = ((unix_millis as u128) << 80)
+        | (0x7 << 76)
+        | ((fields >> 20) << 64)
+        | (0b10 << 62)
+        | ((fields & 0xfffff) << 42);
+    Ok(Uuid::from_u128(bits))
+}
+
+/// Maps a UUIDv7 created by `to_uuid_v7` back to the Snowflake ID
+///
+/// # Errors
+///
+/// - SnowflakeError::InvalidUuid if the UUID is not a UUIDv7 created by `to_uuid_v7` with
+///   this layout (e.g. it has random bits set)
+/// - SnowflakeError::TimestampOutOfRange if its time is before the epoch or past the
+///   layout's timestamp field
+pub fn from_uuid_v7(uuid: &Uuid, epoch: Option<i64>, layout: &Layout) -> Result<SnowflakeId, SnowflakeError> {
+    let bits = uuid.as_u128();
+    let version = (bits >> 76) & 0xf;
+    let variant = (bits >> 62) & 0b11;
+    let fields = (((bits >> 64) & 0xfff) << 20 | ((bits >> 42) & 0xfffff)) as u64;
+    let field_bits = (layout.node_bits() + layout.step_bits()) as u32;
+    let unused = bits & ((1 << 42) - 1);
+    if version != 0x7 || variant != 0b10 || unused != 0 || fields >> field_bits != 0 {
+        return Err(SnowflakeError::InvalidUuid);
+    }
+    let unix_millis = (bits >> 80) as i64;
+    let timestamp = u64::try_from(unix_millis - epoch.unwrap_or(DEFAULT_EPOCH))
+        .map_err(|_| SnowflakeError::TimestampOutOfRange)?;
+    let node = (fields >> layout.step_bits()) as u16;
+    let sequence = (fields & layout.max_sequence() as u64) as u16;
+    layout.compose(timestamp, node, sequence).map(SnowflakeId::from)
+}
+
+// Node ID and sequence number as one value of `node_bits + step_bits` (at most 32) bits
+fn node_and_sequence(node: u16, sequence: u16, layout: &Layout) -> u32 {
+    ((node as u32) << layout.step_bits()) | sequence as u32
+}
+
+/// Embeds the ID assuming DEFAULT_EPOCH and the default layout (see `embed`)
+impl From<SnowflakeId> for Uuid {
+    fn from(id: SnowflakeId) -> Self {
+        embed(id, None, &Layout::DEFAULT).expect("DEFAULT_EPOCH is embeddable")
+    }
+}
+
+/// Extracts an embedded ID, whatever epoch and layout it was embedded with (see `extract`)
+impl TryFrom<Uuid> for SnowflakeId {
+    type Error = SnowflakeError;
+
+    fn try_from(uuid: Uuid) -> Result<Self, Self::Error> {
+        extract(&uuid).map(|embedded| embedded.id)
+    }
+}
+
+impl TryFrom<Uuid> for EmbeddedSnowflake {
+    type Error = SnowflakeError;
+
+    fn try_from(uuid: Uuid) -> Result<Self, Self::Error> {
+        extract(&uuid)
+    }
+}
+
+/// Embeds the ID with its epoch and layout (see `embed`)
+impl TryFrom<EmbeddedSnowflake> for Uuid {
+    type Error = SnowflakeError;
+
+    fn try_from(embedded: EmbeddedSnowflake) -> Result<Self, Self::Error> {
+        embed(embedded.id, Some(embedded.epoch), &embedded.layout)
+    }
+}
+
+impl Snowflake {
+    /// Generates a new Snowflake ID embedded in a UUIDv8 (see `uuid::embed`)
+    ///
+    /// # Errors
+    ///
+    /// Same as `generate`, plus SnowflakeError::UnembeddableEpoch if the generator's epoch
+    /// cannot be embedded and SnowflakeError::InvalidLayout if its layout has a check field
+    pub fn generate_uuid(&self) -> Result<Uuid, SnowflakeError> {
+        embed(self.generate_id()?, Some(self.epoch()), &self.layout())
+    }
+}
@@ -0,0 +1,144 @@
+#![cfg(feature = "async")]
+
+use std::collections::HashSet;
+
+use snowflake_rs_impl::actor;
+#[cfg(feature = "tokio")]
+use snowflake_rs_impl::actor::IdHandle;
+#[cfg(feature = "tokio")]
+use snowflake_rs_impl::rate_limit::{RateLimit, ThrottleMode};
+use snowflake_rs_impl::snowflake::{Snowflake, SnowflakeError};
+
+/// Test that IDs from a handle increase and carry the generator's node ID
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn test_next_increases() {
+    let handle = IdHandle::spawn(Snowflake::new(7, None).unwrap());
+    let mut last = handle.next().await.unwrap();
+    for _ in 0..1000 {
+        let id = handle.next().await.unwrap();
+        assert!(id > last);
+        assert_eq!(id.node(), 7);
+        last = id;
+    }
+}
+
+/// Test that a batch comes back in ascending order with the requested size
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn test_next_batch() {
+    let handle = IdHandle::spawn(Snowflake::new(1, None).unwrap());
+    let ids = handle.next_batch(10_000).await.unwrap();
+    assert_eq!(ids.len(), 10_000);
+    assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+    assert!(handle.next_batch(0).await.unwrap().is_empty());
+}
+
+/// Test that many tasks sharing clones of one handle get unique IDs, even through a
+/// small channel
+#[cfg(feature = "tokio")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrent_handles_unique() {
+    let handle = IdHandle::spawn_with_capacity(Snowflake::new(1, None).unwrap(), 4);
+    let tasks: Vec<_> = (0..64)
+        .map(|task| {
+            let handle = handle.clone();
+            tokio::spawn(async move {
+                let mut ids = Vec::new();
+                for _ in 0..100 {
+                    if task % 2 == 0 {
+                        ids.push(handle.next().await.unwrap());
+                    } else {
+                        ids.extend(handle.next_batch(3).await.unwrap());
+                    }
+                }
+                ids
+            })
+        })
+        .collect();
+    let mut unique = HashSet::new();
+    for task in tasks {
+        for id in task.await.unwrap() {
+            assert!(unique.insert(id), "duplicate ID {}", id);
+        }
+    }
+    assert_eq!(unique.len(), 32 * 100 + 32 * 300);
+}
+
+/// Test that generator errors reach the caller of every request served in the same round
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn test_errors_are_returned() {
+    let limit = RateLimit::per_second(1).burst(1).mode(ThrottleMode::Error);
+    let snowflake = Snowflake::builder(1).rate_limit(limit).build().unwrap();
+    let handle = IdHandle::spawn(snowflake);
+    let (first, second, third) = tokio::join!(handle.next(), handle.next(), handle.next());
+    let results = [first, second, third];
+    assert_eq!(results.iter().filter(|result| result.is_ok()).count(), 1);
+    assert!(results
+        .iter()
+        .filter_map(|result| result.as_ref().err())
+        .all(|err| matches!(err, SnowflakeError::Throttled)));
+    assert!(matches!(handle.next_batch(5).await, Err(SnowflakeError::BatchExceedsBurst)));
+}
+
+/// Test that a handle reports a stopped service once its runtime has shut down
+#[cfg(feature = "tokio")]
+#[test]
+fn test_service_stopped() {
+    let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    let handle = runtime.block_on(async { IdHandle::spawn(Snowflake::new(1, None).unwrap()) });
+    assert!(!handle.is_stopped());
+    drop(runtime);
+    assert!(handle.is_stopped());
+
+    let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    assert!(matches!(runtime.block_on(handle.next()), Err(SnowflakeError::ServiceStopped)));
+}
+
+/// Test that the service runs on smol, with handles used from several smol tasks
+#[test]
+fn test_smol_runtime() {
+    let (handle, service) = actor::channel(Snowflake::new(3, None).unwrap(), 8);
+    smol::spawn(service).detach();
+    let tasks: Vec<_> = (0..16)
+        .map(|_| {
+            let handle = handle.clone();
+            smol::spawn(async move {
+                let mut ids = handle.next_batch(50).await.unwrap();
+                ids.push(handle.next().await.unwrap());
+                ids
+            })
+        })
+        .collect();
+    let ids: HashSet<_> = smol::block_on(async {
+        let mut ids = HashSet::new();
+        for task in tasks {
+            ids.extend(task.await);
+        }
+        ids
+    });
+    assert_eq!(ids.len(), 16 * 51);
+    assert!(ids.iter().all(|id| id.node() == 3));
+}
+
+/// Test that the service finishes once every handle is dropped
+#[test]
+fn test_service_finishes() {
+    let (handle, service) = actor::channel(Snowflake::new(1, None).unwrap(), 1);
+    let service = smol::spawn(service);
+    let id = smol::block_on(handle.next()).unwrap();
+    assert!(!handle.is_stopped());
+    drop(handle);
+    smol::block_on(service);
+    assert_eq!(id.node(), 1);
+}
+
+/// Test that a handle reports a stopped service once the service is dropped
+#[test]
+fn test_service_dropped() {
+    let (handle, service) = actor::channel(Snowflake::new(1, None).unwrap(), 1);
+    drop(service);
+    assert!(handle.is_stopped());
+    assert!(matches!(smol::block_on(handle.next_batch(2)), Err(SnowflakeError::ServiceStopped)));
+}
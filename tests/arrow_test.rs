@@ -0,0 +1,152 @@
+#![cfg(feature = "arrow")]
+
+use std::sync::Arc;
+
+use arrow_array::{Array, Int64Array, StringArray, UInt64Array};
+use arrow_schema::{DataType, Field, TimeUnit};
+use snowflake_rs_impl::arrow::{node_ids, sequences, timestamp_millis, SnowflakeIdBuilder, SnowflakeIdType};
+use snowflake_rs_impl::exhaustion::ERA_BIT;
+use snowflake_rs_impl::id::SnowflakeId;
+use snowflake_rs_impl::layout::Layout;
+use snowflake_rs_impl::snowflake::{Snowflake, SnowflakeError};
+
+/// Test that the extension type round-trips through field metadata
+#[test]
+fn test_extension_type_metadata() {
+    let id_type = SnowflakeIdType::new(Some(1672531200000), Layout::new(8, 14).unwrap());
+    let field = id_type.field("id", true);
+    assert_eq!(field.data_type(), &DataType::UInt64);
+    assert_eq!(
+        field.extension_type_metadata(),
+        Some(r#"{"epoch":1672531200000,"timestampBits":41,"nodeBits":8,"sequenceBits":14}"#)
+    );
+    assert_eq!(field.try_extension_type::<SnowflakeIdType>().unwrap(), id_type);
+
+    // Int64 columns are accepted too, other types are not
+    let signed = Field::new("id", DataType::Int64, false).with_metadata(field.metadata().clone());
+    assert_eq!(signed.try_extension_type::<SnowflakeIdType>().unwrap(), id_type);
+    let text = Field::new("id", DataType::Utf8, false).with_metadata(field.metadata().clone());
+    assert!(text.try_extension_type::<SnowflakeIdType>().is_err());
+}
+
+/// Test that the check field of the layout is recorded in the metadata
+#[test]
+fn test_extension_type_check_bits() {
+    let id_type = SnowflakeIdType::new(Some(0), Layout::new(6, 12).unwrap().with_check_bits(4).unwrap());
+    let field = id_type.field("id", false);
+    assert_eq!(
+        field.extension_type_metadata(),
+        Some(r#"{"epoch":0,"timestampBits":41,"nodeBits":6,"sequenceBits":12,"checkBits":4}"#)
+    );
+    assert_eq!(field.try_extension_type::<SnowflakeIdType>().unwrap(), id_type);
+
+    let id = id_type.layout().compose(1000, 33, 7).unwrap();
+    let ids = UInt64Array::from(vec![id]);
+    assert_eq!(node_ids(&ids, &id_type).unwrap().value(0), 33);
+    assert_eq!(sequences(&ids, &id_type).unwrap().value(0), 7);
+}
+
+/// Test that malformed or inconsistent metadata is rejected
+#[test]
+fn test_extension_type_invalid_metadata() {
+    let field = SnowflakeIdType::default().field("id", false);
+    for metadata in ["", "{}", r#"{"epoch":0,"timestampBits":40,"nodeBits":10,"sequenceBits":12}"#] {
+        let mut entries = field.metadata().clone();
+        entries.insert("ARROW:extension:metadata".to_string(), metadata.to_string());
+        let field = field.clone().with_metadata(entries);
+        assert!(field.try_extension_type::<SnowflakeIdType>().is_err(), "metadata {}", metadata);
+    }
+}
+
+/// Test that the builder appends IDs and nulls in order
+#[test]
+fn test_builder() {
+    let snowflake = Snowflake::new(1, None).unwrap();
+    let ids = snowflake.generate_batch(10).unwrap();
+    let mut builder = SnowflakeIdBuilder::new();
+    assert!(builder.is_empty());
+    builder.append(ids[0]);
+    builder.append_slice(&ids[1..5]);
+    builder.append_null();
+    builder.append_option(Some(ids[5]));
+    builder.extend(ids[6..].iter().copied());
+    builder.extend([None, Some(ids[0])]);
+    assert_eq!(builder.len(), 13);
+
+    let array = builder.finish();
+    assert!(builder.is_empty());
+    assert_eq!(array.null_count(), 2);
+    let values: Vec<Option<u64>> = array.iter().collect();
+    let mut expected: Vec<Option<u64>> = ids[..5].iter().map(|id| Some(id.as_u64())).collect();
+    expected.push(None);
+    expected.extend(ids[5..].iter().map(|id| Some(id.as_u64())));
+    expected.extend([None, Some(ids[0].as_u64())]);
+    assert_eq!(values, expected);
+}
+
+/// Test that the kernels decompose IDs into timestamp, node and sequence columns
+#[test]
+fn test_decompose_kernels() {
+    let epoch = 1672531200000;
+    let layout = Layout::new(8, 14).unwrap();
+    let id_type = SnowflakeIdType::new(Some(epoch), layout);
+    let raw = [
+        Some(layout.compose(5, 200, 3).unwrap()),
+        None,
+        Some(layout.compose(1 << 30, 1, 16383).unwrap()),
+    ];
+    let unsigned = UInt64Array::from(raw.to_vec());
+    let signed = Int64Array::from(raw.iter().map(|id| id.map(|id| id as i64)).collect::<Vec<_>>());
+
+    for ids in [&unsigned as &dyn Array, &signed] {
+        let timestamps = timestamp_millis(ids, &id_type).unwrap();
+        assert_eq!(timestamps.data_type(), &DataType::Timestamp(TimeUnit::Millisecond, Some(Arc::from("+00:00"))));
+        assert_eq!(timestamps.iter().collect::<Vec<_>>(), [Some(epoch + 5), None, Some(epoch + (1 << 30))]);
+        assert_eq!(node_ids(ids, &id_type).unwrap().iter().collect::<Vec<_>>(), [Some(200), None, Some(1)]);
+        assert_eq!(sequences(ids, &id_type).unwrap().iter().collect::<Vec<_>>(), [Some(3), None, Some(16383)]);
+    }
+}
+
+/// Test that the kernels agree with `SnowflakeId` on generated IDs
+#[test]
+fn test_kernels_match_id_accessors() {
+    let snowflake = Snowflake::new(7, None).unwrap();
+    let ids: Vec<SnowflakeId> = snowflake.generate_batch(100).unwrap();
+    let mut builder = SnowflakeIdBuilder::with_capacity(ids.len());
+    builder.append_slice(&ids);
+    let array = builder.finish();
+
+    let id_type = SnowflakeIdType::default();
+    let timestamps = timestamp_millis(&array, &id_type).unwrap();
+    let nodes = node_ids(&array, &id_type).unwrap();
+    for (index, id) in ids.iter().enumerate() {
+        assert_eq!(timestamps.value(index), id.timestamp() as i64 + snowflake.epoch());
+        assert_eq!(nodes.value(index), id.node());
+    }
+}
+
+/// Test that second-era IDs map to times after the end of the first era
+#[test]
+fn test_timestamp_millis_second_era() {
+    let epoch = 1672531200000;
+    let id_type = SnowflakeIdType::new(Some(epoch), Layout::DEFAULT);
+    let first = Layout::DEFAULT.compose(5, 1, 0).unwrap();
+    let ids = UInt64Array::from(vec![first, ERA_BIT | first]);
+    let timestamps = timestamp_millis(&ids, &id_type).unwrap();
+    let era_offset = Layout::DEFAULT.max_timestamp() as i64 + 1;
+    assert_eq!(timestamps.value(0), epoch + 5);
+    assert_eq!(timestamps.value(1), epoch + era_offset + 5);
+}
+
+/// Test that negative Int64 values and other array types are rejected
+#[test]
+fn test_kernels_reject_invalid_arrays() {
+    let id_type = SnowflakeIdType::default();
+    let negative = Int64Array::from(vec![1, -1]);
+    assert!(matches!(node_ids(&negative, &id_type), Err(SnowflakeError::InvalidArrowArray)));
+    // Values under a null slot are not checked
+    let masked = Int64Array::from(vec![Some(1), None]);
+    assert!(node_ids(&masked, &id_type).is_ok());
+    let text = StringArray::from(vec!["1"]);
+    assert!(matches!(timestamp_millis(&text, &id_type), Err(SnowflakeError::InvalidArrowArray)));
+}
@@ -0,0 +1,132 @@
+use std::io::{self, BufReader, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use snowflake_rs_impl::audit::{read_ranges, AuditLog, IssuedRange};
+use snowflake_rs_impl::clock::Clock;
+use snowflake_rs_impl::snowflake::Snowflake;
+
+// Clock whose reading is set by the test
+struct SetClock(AtomicI64);
+
+impl Clock for SetClock {
+    fn now_millis(&self) -> i64 {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+// In-memory sink the test can read back while the log holds it
+#[derive(Clone, Default)]
+struct SharedSink(Arc<Mutex<Vec<u8>>>);
+
+impl SharedSink {
+    fn ranges(&self) -> Vec<IssuedRange> {
+        read_ranges(self.0.lock().as_slice()).unwrap()
+    }
+}
+
+impl Write for SharedSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// Returns a fresh audit file path for a test
+fn audit_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("snowflake-{}-{}.audit", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+/// Test that a range is written once a node moves on to the next window
+#[test]
+fn test_ranges_per_window() {
+    let clock = Arc::new(SetClock(AtomicI64::new(1700000000100)));
+    let sink = SharedSink::default();
+    let audit_log = Arc::new(AuditLog::from_writer(sink.clone()));
+    let snowflake = Snowflake::builder(3).clock(clock.clone()).audit_log(audit_log.clone()).build().unwrap();
+
+    snowflake.generate_batch(10).unwrap();
+    clock.0.store(1700000000900, Ordering::SeqCst);
+    snowflake.generate().unwrap();
+    assert!(sink.ranges().is_empty());
+
+    clock.0.store(1700000001500, Ordering::SeqCst);
+    snowflake.generate_batch(4096).unwrap();
+    let expected = IssuedRange {
+        node: 3,
+        first_timestamp: 1700000000100,
+        last_timestamp: 1700000000900,
+        max_sequence: 9,
+    };
+    assert_eq!(sink.ranges(), vec![expected]);
+
+    // The open window holds one full millisecond
+    audit_log.flush().unwrap();
+    let ranges = sink.ranges();
+    assert_eq!(ranges.len(), 2);
+    assert_eq!((ranges[1].first_timestamp, ranges[1].last_timestamp), (1700000001500, 1700000001500));
+    assert_eq!(ranges[1].max_sequence, 4095);
+}
+
+/// Test that every issued ID is covered by a recorded range, including backfilled
+/// IDs under the backfill node
+#[test]
+fn test_ranges_cover_issued_ids() {
+    let sink = SharedSink::default();
+    let audit_log = Arc::new(AuditLog::from_writer(sink.clone()).window(Duration::from_millis(10)));
+    assert_eq!(audit_log.window_width(), Duration::from_millis(10));
+    let snowflake = Snowflake::builder(1).backfill_node(1023).audit_log(audit_log.clone()).build().unwrap();
+
+    let mut ids: Vec<u64> = (0..20_000).map(|_| snowflake.generate().unwrap()).collect();
+    ids.push(snowflake.generate_at(1640995200000).unwrap());
+    drop(snowflake);
+    drop(audit_log);
+
+    let ranges = sink.ranges();
+    for id in ids {
+        let (timestamp, node, sequence) = Snowflake::parse_id(id);
+        let timestamp = timestamp as i64 + 1609459200000;
+        assert!(ranges.iter().any(|range| range.contains(node, timestamp, sequence)), "ID {} not covered", id);
+    }
+    assert!(ranges.iter().any(|range| range.node == 1023));
+}
+
+/// Test that a file log only ever appends, across reopened logs
+#[test]
+fn test_file_log_appends() {
+    let path = audit_path("append");
+    for node in [1, 2] {
+        let audit_log = AuditLog::open(&path).unwrap();
+        audit_log.record(node, 1700000000000, 7);
+    }
+    let ranges = read_ranges(BufReader::new(std::fs::File::open(&path).unwrap())).unwrap();
+    assert_eq!(ranges.iter().map(|range| range.node).collect::<Vec<_>>(), vec![1, 2]);
+    assert_eq!(ranges[0].to_string(), "node=1 from=1700000000000 to=1700000000000 max_sequence=7");
+    std::fs::remove_file(&path).unwrap();
+}
+
+/// Test that malformed records are rejected and blank lines skipped
+#[test]
+fn test_read_ranges_rejects_malformed() {
+    let valid = "node=1 from=10 to=20 max_sequence=3\n\n";
+    assert_eq!(read_ranges(valid.as_bytes()).unwrap().len(), 1);
+    for invalid in [
+        "node=1 from=10 to=20",
+        "node=1 from=20 to=10 max_sequence=3",
+        "node=1 to=20 from=10 max_sequence=3",
+        "node=1 from=10 to=20 max_sequence=3 extra=1",
+        "node=70000 from=10 to=20 max_sequence=3",
+    ] {
+        let err = read_ranges(invalid.as_bytes()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
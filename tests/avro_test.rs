@@ -0,0 +1,88 @@
+#![cfg(feature = "avro")]
+
+use apache_avro::types::{Record, Value};
+use apache_avro::{Reader, Schema, Writer};
+use serde::{Deserialize, Serialize};
+use snowflake_rs_impl::avro::{from_value, schema, schema_json, to_value};
+use snowflake_rs_impl::id::SnowflakeId;
+use snowflake_rs_impl::layout::Layout;
+use snowflake_rs_impl::snowflake::Snowflake;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Event {
+    id: SnowflakeId,
+    name: String,
+}
+
+// Builds a record schema with a Snowflake ID field
+fn event_schema() -> Schema {
+    let raw = format!(
+        r#"{{"type": "record", "name": "Event", "fields": [
+            {{"name": "id", "type": {}}},
+            {{"name": "name", "type": "string"}}
+        ]}}"#,
+        schema_json(None, &Layout::DEFAULT)
+    );
+    Schema::parse_str(&raw).unwrap()
+}
+
+/// Test that the logical type schema parses as a plain long
+#[test]
+fn test_schema_is_long() {
+    assert_eq!(schema(None, &Layout::DEFAULT), Schema::Long);
+    let json = schema_json(Some(1672531200000), &Layout::DEFAULT);
+    assert!(json.contains(r#""logicalType": "snowflake-id""#));
+    assert!(json.contains(r#""epoch": 1672531200000"#));
+}
+
+/// Test round-tripping IDs through an Avro container file with the value helpers
+#[test]
+fn test_avro_value_round_trip() {
+    let schema = event_schema();
+    let snowflake = Snowflake::new(1, None).unwrap();
+    let ids: Vec<SnowflakeId> = (0..10).map(|_| snowflake.generate_id().unwrap()).collect();
+
+    let mut writer = Writer::new(&schema, Vec::new());
+    for id in &ids {
+        let mut record = Record::new(&schema).unwrap();
+        record.put("id", to_value(*id).unwrap());
+        record.put("name", "created");
+        writer.append(record).unwrap();
+    }
+    let bytes = writer.into_inner().unwrap();
+
+    let read: Vec<SnowflakeId> = Reader::new(&bytes[..])
+        .unwrap()
+        .map(|value| match value.unwrap() {
+            Value::Record(fields) => from_value(&fields[0].1).unwrap(),
+            other => panic!("unexpected value {:?}", other),
+        })
+        .collect();
+    assert_eq!(read, ids);
+}
+
+/// Test round-tripping a struct with a SnowflakeId field through serde
+#[test]
+fn test_avro_serde_round_trip() {
+    let schema = event_schema();
+    let event = Event {
+        id: Snowflake::new(2, None).unwrap().generate_id().unwrap(),
+        name: "deleted".to_string(),
+    };
+
+    let mut writer = Writer::new(&schema, Vec::new());
+    writer.append_ser(&event).unwrap();
+    let bytes = writer.into_inner().unwrap();
+
+    let value = Reader::new(&bytes[..]).unwrap().next().unwrap().unwrap();
+    let decoded: Event = apache_avro::from_value(&value).unwrap();
+    assert_eq!(decoded, event);
+}
+
+/// Test that negative longs and non-long values are rejected
+#[test]
+fn test_avro_invalid_values() {
+    assert!(from_value(&Value::Long(-1)).is_err());
+    assert!(from_value(&Value::String("1".to_string())).is_err());
+    assert!(to_value(SnowflakeId::from(u64::MAX)).is_err());
+}
@@ -0,0 +1,71 @@
+use std::collections::HashSet;
+
+use snowflake_rs_impl::snowflake::{Snowflake, SnowflakeError};
+
+const CREATED_AT: i64 = 1_640_995_200_000;
+
+/// Test that backfilled IDs carry the requested time and the backfill node
+#[test]
+fn test_generate_at() {
+    let snowflake = Snowflake::builder(1).backfill_node(1023).build().unwrap();
+    assert_eq!(snowflake.backfill_node(), Some(1023));
+
+    let first = snowflake.generate_at(CREATED_AT).unwrap();
+    let second = snowflake.generate_at(CREATED_AT).unwrap();
+    let earlier = snowflake.generate_at(CREATED_AT - 1).unwrap();
+    for (id, timestamp, sequence) in [(first, CREATED_AT, 0), (second, CREATED_AT, 1), (earlier, CREATED_AT - 1, 0)] {
+        assert_eq!(Snowflake::parse_id(id), ((timestamp - snowflake.epoch()) as u64, 1023, sequence));
+    }
+    assert!(earlier < first && first < second);
+}
+
+/// Test that backfilled IDs never collide with live IDs for the same milliseconds
+#[test]
+fn test_generate_at_does_not_collide_with_live_ids() {
+    let snowflake = Snowflake::builder(1).backfill_node(2).build().unwrap();
+    let live: Vec<u64> = (0..5000).map(|_| snowflake.generate().unwrap()).collect();
+    std::thread::sleep(std::time::Duration::from_millis(2));
+
+    let mut ids: HashSet<u64> = live.iter().copied().collect();
+    for &id in &live {
+        let (timestamp, _, _) = Snowflake::parse_id(id);
+        assert!(ids.insert(snowflake.generate_at(snowflake.epoch() + timestamp as i64).unwrap()));
+    }
+    assert_eq!(ids.len(), 10_000);
+}
+
+/// Test the backfill safeguards
+#[test]
+fn test_generate_at_errors() {
+    let snowflake = Snowflake::new(1, None).unwrap();
+    assert!(matches!(snowflake.generate_at(CREATED_AT), Err(SnowflakeError::BackfillNotConfigured)));
+
+    assert!(matches!(
+        Snowflake::builder(1).backfill_node(1).build(),
+        Err(SnowflakeError::InvalidBackfillNode(1))
+    ));
+    assert!(matches!(
+        Snowflake::builder(1).backfill_node(1024).build(),
+        Err(SnowflakeError::MachineIdOutOfRange)
+    ));
+
+    let snowflake = Snowflake::builder(1).backfill_node(2).build().unwrap();
+    assert!(matches!(snowflake.generate_at(snowflake.epoch() - 1), Err(SnowflakeError::TimestampOutOfRange)));
+    let future = 4_102_444_800_000; // 2100-01-01T00:00:00Z
+    assert!(matches!(snowflake.generate_at(future), Err(SnowflakeError::TimestampOutOfRange)));
+
+    // A millisecond runs out of backfill sequence numbers
+    for _ in 0..4096 {
+        snowflake.generate_at(CREATED_AT).unwrap();
+    }
+    assert!(matches!(snowflake.generate_at(CREATED_AT), Err(SnowflakeError::SequenceOverflow)));
+    assert!(snowflake.generate_at(CREATED_AT + 1).is_ok());
+}
+
+/// Test that forks do not inherit the backfill node, and cannot take it as their node ID
+#[test]
+fn test_fork_drops_backfill_node() {
+    let snowflake = Snowflake::builder(1).backfill_node(2).build().unwrap();
+    assert_eq!(snowflake.fork(3).unwrap().backfill_node(), None);
+    assert!(matches!(snowflake.fork(2), Err(SnowflakeError::InvalidBackfillNode(2))));
+}
@@ -0,0 +1,123 @@
+use snowflake_rs_impl::bwmarrin;
+use snowflake_rs_impl::id::SnowflakeId;
+use snowflake_rs_impl::snowflake::SnowflakeError;
+
+// One golden vector: the outputs of github.com/bwmarrin/snowflake for `ID(id)`
+struct Vector {
+    id: u64,
+    base2: &'static str,
+    base32: &'static str,
+    base36: &'static str,
+    base58: &'static str,
+    base64: &'static str,
+    time: i64,
+    node: u16,
+    step: u16,
+}
+
+const VECTORS: &[Vector] = &[
+    Vector { id: 0, base2: "0", base32: "y", base36: "0", base58: "1", base64: "MA==", time: 1288834974657, node: 0, step: 0 },
+    Vector { id: 1, base2: "1", base32: "b", base36: "1", base58: "2", base64: "MQ==", time: 1288834974657, node: 0, step: 1 },
+    Vector { id: 57, base2: "111001", base32: "b3", base36: "1l", base58: "Z", base64: "NTc=", time: 1288834974657, node: 0, step: 57 },
+    Vector { id: 58, base2: "111010", base32: "b4", base36: "1m", base58: "21", base64: "NTg=", time: 1288834974657, node: 0, step: 58 },
+    Vector { id: 4096, base2: "1000000000000", base32: "ryy", base36: "35s", base58: "2dC", base64: "NDA5Ng==", time: 1288834974657, node: 1, step: 0 },
+    Vector { id: 13587, base2: "11010100010011", base32: "peu", base36: "ahf", base58: "53g", base64: "MTM1ODc=", time: 1288834974657, node: 3, step: 1299 },
+    Vector {
+        id: 1125899906842624,
+        base2: "100000000000000000000000000000000000000000000000000",
+        base32: "byyyyyyyyyy",
+        base36: "b33j9ynrb4",
+        base58: "9MVtViEBA",
+        base64: "MTEyNTg5OTkwNjg0MjYyNA==",
+        time: 1289103410113,
+        node: 0,
+        step: 0,
+    },
+    Vector {
+        id: 1541815603606036480,
+        base2: "1010101100101101000010001111101100010000101111010000000000000",
+        base32: "bk3pbd7tbxeyy",
+        base36: "bppc0m3ju134",
+        base58: "4zzv4rZaYpj",
+        base64: "MTU0MTgxNTYwMzYwNjAzNjQ4MA==",
+        time: 1656432460105,
+        node: 378,
+        step: 0,
+    },
+    Vector {
+        id: 9223372036854775807,
+        base2: "111111111111111111111111111111111111111111111111111111111111111",
+        base32: "8999999999999",
+        base36: "1y2p0ij32e8e7",
+        base58: "npL6MjP8Qfc",
+        base64: "OTIyMzM3MjAzNjg1NDc3NTgwNw==",
+        time: 3487858230208,
+        node: 1023,
+        step: 4095,
+    },
+];
+
+/// Test that encodings and field accessors match the Go library's golden vectors
+#[test]
+fn test_bwmarrin_golden_vectors() {
+    for vector in VECTORS {
+        let id = SnowflakeId::from_u64(vector.id);
+        assert_eq!(bwmarrin::to_base2(id), vector.base2, "Base2({})", vector.id);
+        assert_eq!(bwmarrin::to_base32(id), vector.base32, "Base32({})", vector.id);
+        assert_eq!(bwmarrin::to_base36(id), vector.base36, "Base36({})", vector.id);
+        assert_eq!(bwmarrin::to_base58(id), vector.base58, "Base58({})", vector.id);
+        assert_eq!(bwmarrin::to_base64(id), vector.base64, "Base64({})", vector.id);
+        assert_eq!(bwmarrin::to_int_bytes(id), vector.id.to_be_bytes());
+        assert_eq!(bwmarrin::time_millis(id), vector.time, "Time({})", vector.id);
+        assert_eq!(bwmarrin::node(id), vector.node, "Node({})", vector.id);
+        assert_eq!(bwmarrin::step(id), vector.step, "Step({})", vector.id);
+    }
+}
+
+/// Test that the parsers invert the encodings, like the Go `Parse*` functions
+#[test]
+fn test_bwmarrin_parse_golden_vectors() {
+    for vector in VECTORS {
+        let id = SnowflakeId::from_u64(vector.id);
+        assert_eq!(bwmarrin::parse_string(&vector.id.to_string()).unwrap(), id);
+        assert_eq!(bwmarrin::parse_base2(vector.base2).unwrap(), id);
+        assert_eq!(bwmarrin::parse_base32(vector.base32).unwrap(), id);
+        assert_eq!(bwmarrin::parse_base36(vector.base36).unwrap(), id);
+        assert_eq!(bwmarrin::parse_base58(vector.base58).unwrap(), id);
+        assert_eq!(bwmarrin::parse_base64(vector.base64).unwrap(), id);
+        assert_eq!(bwmarrin::parse_int_bytes(vector.id.to_be_bytes()), id);
+    }
+}
+
+/// Test that malformed or out-of-range strings are rejected
+#[test]
+fn test_bwmarrin_parse_errors() {
+    for invalid in ["", "0", "l", "I", "O", "1 2"] {
+        assert!(matches!(bwmarrin::parse_base58(invalid), Err(SnowflakeError::InvalidEncoding)), "{:?}", invalid);
+    }
+    assert!(bwmarrin::parse_base32("yl").is_err());
+    assert!(bwmarrin::parse_base2("102").is_err());
+    // One past i64::MAX
+    assert!(bwmarrin::parse_base58("npL6MjP8Qfd").is_err());
+    assert!(bwmarrin::parse_string("-1").is_err());
+    assert!(bwmarrin::parse_base64("MA=").is_err());
+    assert!(bwmarrin::parse_base64("MA==MA==").is_err());
+    assert!(bwmarrin::parse_base64("YQ==").is_err());
+}
+
+/// Test that the compatibility builder generates IDs with the Go library's layout and epoch
+#[test]
+fn test_bwmarrin_generator() {
+    let snowflake = bwmarrin::builder(378).build().unwrap();
+    assert_eq!(snowflake.epoch(), bwmarrin::EPOCH);
+    assert_eq!(snowflake.layout(), bwmarrin::LAYOUT);
+
+    let before = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_millis() as i64;
+    let id = snowflake.generate_id().unwrap();
+    let after = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_millis() as i64;
+    assert!((before..=after).contains(&bwmarrin::time_millis(id)));
+    assert_eq!(bwmarrin::node(id), 378);
+    assert_eq!(bwmarrin::step(id), 0);
+    // The Go layout: time << 22 | node << 12 | step
+    assert_eq!(id.as_u64(), ((bwmarrin::time_millis(id) - bwmarrin::EPOCH) as u64) << 22 | 378 << 12);
+}
@@ -0,0 +1,74 @@
+use std::process::Command;
+
+use snowflake_rs_impl::layout::Layout;
+
+// Runs the `snowflake` binary with `args` and returns its exit status and stdout
+fn snowflake(args: &[&str], envs: &[(&str, &str)]) -> (bool, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_snowflake"))
+        .args(args)
+        .envs(envs.iter().copied())
+        .output()
+        .unwrap();
+    (output.status.success(), String::from_utf8(output.stdout).unwrap())
+}
+
+/// Test that doctor reports every check and picks up an explicitly assigned node ID
+#[test]
+fn test_doctor() {
+    let (success, stdout) = snowflake(&["doctor", "--duration-ms", "20"], &[("SNOWFLAKE_NODE_ID", "77")]);
+    assert!(success);
+    for section in ["Clock", "NTP synchronized", "Throughput", "Node ID candidates", "Recommended configuration"] {
+        assert!(stdout.contains(section), "missing `{}` in:\n{}", section, stdout);
+    }
+    assert!(stdout.contains("SNOWFLAKE_NODE_ID:"));
+    assert!(stdout.contains("Snowflake::builder(77)"));
+}
+
+/// Test that parse rejects IDs from nodes outside `--known-nodes`
+#[test]
+fn test_parse_known_nodes() {
+    let id = Layout::DEFAULT.compose(1000, 5, 7).unwrap().to_string();
+    assert!(snowflake(&["parse", &id, "--known-nodes", "1,5,9"], &[]).0);
+    let (success, stdout) = snowflake(&["parse", &id, "--known-nodes", "1,2"], &[]);
+    assert!(!success);
+    assert!(stdout.contains("Node:      5"), "{}", stdout);
+
+    assert!(!snowflake(&["parse", &id, "--known-nodes"], &[]).0);
+    assert!(!snowflake(&["parse", &id, "--known-nodes", "1,x"], &[]).0);
+    assert!(!snowflake(&["parse", &id, "--known-nodes", "1024"], &[]).0);
+}
+
+/// Test that unknown commands and bad options fail
+#[test]
+fn test_cli_errors() {
+    assert!(!snowflake(&["bogus"], &[]).0);
+    assert!(!snowflake(&[], &[]).0);
+    assert!(!snowflake(&["doctor", "--duration-ms", "soon"], &[]).0);
+    assert!(snowflake(&["help"], &[]).0);
+}
+
+/// Test that parse explains an ID and fails if its checksum does not match
+#[test]
+fn test_parse() {
+    let (success, stdout) = snowflake(&["parse", "1266"], &[]);
+    assert!(success);
+    assert!(stdout.contains("Sequence:  1266"), "{}", stdout);
+    assert!(snowflake(&["parse", "b62_KQ"], &[]).0);
+
+    let layout = Layout::DEFAULT.with_check_bits(4).unwrap();
+    let id = layout.compose(1000, 5, 7).unwrap();
+    let valid = id.to_string();
+    let (success, stdout) = snowflake(&["parse", &valid, "--check-bits", "4", "--epoch", "0"], &[]);
+    assert!(success);
+    assert!(stdout.contains("Checksum:  valid"), "{}", stdout);
+    assert!(stdout.contains("Node:      5"), "{}", stdout);
+
+    let corrupted = (id ^ 1 << 30).to_string();
+    let (success, stdout) = snowflake(&["parse", &corrupted, "--check-bits", "4"], &[]);
+    assert!(!success);
+    assert!(stdout.contains("Checksum:  MISMATCH"), "{}", stdout);
+
+    assert!(!snowflake(&["parse"], &[]).0);
+    assert!(!snowflake(&["parse", "abc"], &[]).0);
+    assert!(!snowflake(&["parse", "1266", "--check-bits", "9"], &[]).0);
+}
@@ -0,0 +1,208 @@
+use std::collections::HashSet;
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use snowflake_rs_impl::clock::{CachedClock, Clock, CoarseClock, SystemClock, DEFAULT_CACHE_INTERVAL};
+use snowflake_rs_impl::hlc::HlcSnowflake;
+use snowflake_rs_impl::snowflake::{Snowflake, SnowflakeError};
+
+/// Test that the coarse clock stays within a few ticks of the system clock
+#[test]
+fn test_coarse_clock_tracks_system_clock() {
+    for _ in 0..100 {
+        let before = SystemClock.now_millis();
+        let coarse = CoarseClock.now_millis();
+        let after = SystemClock.now_millis();
+        // The coarse reading lags by at most one tick (4 ms at CONFIG_HZ=250)
+        assert!(coarse <= after, "coarse clock ahead of system clock: {} > {}", coarse, after);
+        assert!(coarse >= before - 10, "coarse clock lags too far: {} < {}", coarse, before);
+    }
+}
+
+/// Test that a generator on the coarse clock produces unique, increasing IDs
+#[test]
+fn test_generate_with_coarse_clock() {
+    let snowflake = Snowflake::builder(1).clock(Arc::new(CoarseClock)).build().unwrap();
+    let ids: Vec<u64> = (0..20_000).map(|_| snowflake.generate().unwrap()).collect();
+    assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+    assert_eq!(ids.iter().collect::<HashSet<_>>().len(), ids.len());
+}
+
+// Clock whose reading is set by the test
+struct SetClock(AtomicI64);
+
+impl Clock for SetClock {
+    fn now_millis(&self) -> i64 {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+// Clock that fails while its reading is negative, like a system clock set before 1970
+struct PreEpochClock(AtomicI64);
+
+impl Clock for PreEpochClock {
+    fn now_millis(&self) -> i64 {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    fn try_now_millis(&self) -> Result<i64, SnowflakeError> {
+        match self.now_millis() {
+            now if now < 0 => Err(SnowflakeError::ClockUnavailable("before the Unix epoch".to_string())),
+            now => Ok(now),
+        }
+    }
+}
+
+// Waits until `clock` reads `expected`, for at most one second
+fn wait_for_reading(clock: &CachedClock, expected: i64) {
+    let start = Instant::now();
+    while clock.now_millis() != expected {
+        assert!(start.elapsed() < Duration::from_secs(1), "cached clock never reached {}", expected);
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+/// Test that the cached clock is initialised up front and follows the system clock
+#[test]
+fn test_cached_clock_tracks_system_clock() {
+    let before = SystemClock.now_millis();
+    let clock = CachedClock::new();
+    assert!(clock.now_millis() >= before);
+    assert_eq!(clock.interval(), DEFAULT_CACHE_INTERVAL);
+
+    thread::sleep(Duration::from_millis(20));
+    let cached = clock.now_millis();
+    let now = SystemClock.now_millis();
+    assert!(cached <= now && cached >= now - 10, "cached {} vs system {}", cached, now);
+}
+
+/// Test that the cached clock mirrors backward steps, so rollback is still detected
+#[test]
+fn test_cached_clock_rollback_detected() {
+    const START: i64 = 1_700_000_000_000;
+    let source = Arc::new(SetClock(AtomicI64::new(START)));
+    let clock = Arc::new(CachedClock::with_source(source.clone(), Duration::from_millis(1)));
+    let snowflake = Snowflake::builder(1).clock(clock.clone()).build().unwrap();
+    snowflake.generate().unwrap();
+
+    source.0.store(START - 5, Ordering::SeqCst);
+    wait_for_reading(&clock, START - 5);
+    assert!(matches!(snowflake.generate(), Err(SnowflakeError::ClockMovedBackwards)));
+
+    source.0.store(START + 1, Ordering::SeqCst);
+    wait_for_reading(&clock, START + 1);
+    assert!(snowflake.generate().is_ok());
+}
+
+/// Test that sequence exhaustion waits for the cached reading to advance
+#[test]
+fn test_cached_clock_sequence_rollover() {
+    let snowflake = Snowflake::builder(1).clock(Arc::new(CachedClock::new())).build().unwrap();
+    let ids: Vec<u64> = (0..20_000).map(|_| snowflake.generate().unwrap()).collect();
+    assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+}
+
+/// Test that the system clock reads successfully after the Unix epoch
+#[test]
+fn test_system_clock_try_now_millis() {
+    let before = SystemClock.now_millis();
+    let now = SystemClock.try_now_millis().unwrap();
+    assert!(now >= before && now <= SystemClock.now_millis());
+}
+
+/// Test that building with an unreadable clock fails instead of panicking
+#[test]
+fn test_build_with_unavailable_clock() {
+    let clock = Arc::new(PreEpochClock(AtomicI64::new(-1)));
+    let result = Snowflake::builder(1).clock(clock.clone()).build();
+    assert!(matches!(result, Err(SnowflakeError::ClockUnavailable(_))));
+    let result = HlcSnowflake::builder(1).clock(clock).build();
+    assert!(matches!(result, Err(SnowflakeError::ClockUnavailable(_))));
+}
+
+/// Test that generation fails while the clock is unreadable and recovers afterwards
+#[test]
+fn test_generate_with_unavailable_clock() {
+    const START: i64 = 1_700_000_000_000;
+    let clock = Arc::new(PreEpochClock(AtomicI64::new(START)));
+    let snowflake = Snowflake::builder(1).clock(clock.clone()).build().unwrap();
+    let first = snowflake.generate().unwrap();
+
+    clock.0.store(-1, Ordering::SeqCst);
+    assert!(matches!(snowflake.generate(), Err(SnowflakeError::ClockUnavailable(_))));
+    assert!(matches!(snowflake.generate_batch(3), Err(SnowflakeError::ClockUnavailable(_))));
+    assert!(matches!(snowflake.self_test(), Err(SnowflakeError::ClockUnavailable(_))));
+    let err = snowflake.generate().unwrap_err();
+    assert_eq!(err.to_string(), "Clock unavailable: before the Unix epoch");
+
+    clock.0.store(START + 1, Ordering::SeqCst);
+    assert!(snowflake.generate().unwrap() > first);
+}
+
+/// Test that the HLC generator and observe fail while the clock is unreadable
+#[test]
+fn test_hlc_with_unavailable_clock() {
+    const START: i64 = 1_700_000_000_000;
+    let clock = Arc::new(PreEpochClock(AtomicI64::new(START)));
+    let hlc = HlcSnowflake::builder(1).clock(clock.clone()).build().unwrap();
+    let id = hlc.generate().unwrap();
+
+    clock.0.store(-1, Ordering::SeqCst);
+    assert!(matches!(hlc.generate(), Err(SnowflakeError::ClockUnavailable(_))));
+    assert!(matches!(hlc.observe(id), Err(SnowflakeError::ClockUnavailable(_))));
+}
+
+/// Test that the unchecked path continues from the last timestamp while the clock is unreadable
+#[test]
+fn test_generate_unchecked_with_unavailable_clock() {
+    const START: i64 = 1_700_000_000_000;
+    let clock = Arc::new(PreEpochClock(AtomicI64::new(START)));
+    let snowflake = Snowflake::builder(1).clock(clock.clone()).build().unwrap();
+    let first = snowflake.generate_unchecked();
+
+    clock.0.store(-1, Ordering::SeqCst);
+    let second = snowflake.generate_unchecked();
+    assert!(second > first);
+    assert_eq!(Snowflake::parse_id(second).0, Snowflake::parse_id(first).0);
+}
+
+/// Test that the unchecked path panics with the clock error, leaving the state unchanged,
+/// when the clock is unreadable before any ID has been issued
+#[test]
+fn test_generate_unchecked_with_unavailable_clock_at_start() {
+    const START: i64 = 1_700_000_000_000;
+    let clock = Arc::new(PreEpochClock(AtomicI64::new(START)));
+    let snowflake = Snowflake::builder(1).clock(clock.clone()).build().unwrap();
+
+    clock.0.store(-1, Ordering::SeqCst);
+    let panic = std::panic::catch_unwind(AssertUnwindSafe(|| snowflake.generate_unchecked())).unwrap_err();
+    assert_eq!(panic.downcast_ref::<String>().unwrap(), "Clock unavailable: before the Unix epoch");
+
+    clock.0.store(START, Ordering::SeqCst);
+    let (timestamp, _, sequence) = Snowflake::parse_id(snowflake.generate_unchecked());
+    assert_eq!((timestamp as i64 + snowflake.epoch(), sequence), (START, 0));
+}
+
+/// Test that a cached clock fails while its source fails and recovers afterwards
+#[test]
+fn test_cached_clock_with_unavailable_source() {
+    const START: i64 = 1_700_000_000_000;
+    let source = Arc::new(PreEpochClock(AtomicI64::new(START)));
+    let clock = CachedClock::with_source(source.clone(), Duration::from_millis(1));
+    assert_eq!(clock.try_now_millis().unwrap(), START);
+
+    source.0.store(-1, Ordering::SeqCst);
+    let start = Instant::now();
+    while clock.try_now_millis().is_ok() {
+        assert!(start.elapsed() < Duration::from_secs(1), "cached clock never failed");
+        thread::sleep(Duration::from_millis(1));
+    }
+    assert!(matches!(clock.try_now_millis(), Err(SnowflakeError::ClockUnavailable(_))));
+
+    source.0.store(START + 1, Ordering::SeqCst);
+    wait_for_reading(&clock, START + 1);
+    assert_eq!(clock.try_now_millis().unwrap(), START + 1);
+}
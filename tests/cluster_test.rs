@@ -0,0 +1,39 @@
+#![cfg(feature = "test-utils")]
+
+use std::sync::Arc;
+
+use snowflake_rs_impl::clock::{Clock, SystemClock};
+use snowflake_rs_impl::snowflake::{Snowflake, SnowflakeError};
+use snowflake_rs_impl::test_utils::{ClusterSimulation, SkewedClock};
+
+/// Test a cluster with skewed clocks and several threads per node
+#[test]
+fn test_cluster_simulation_with_skew() {
+    let report = ClusterSimulation::new(Snowflake::builder(0).epoch(1672531200000), 0..16)
+        .ids_per_node(5000)
+        .threads_per_node(2)
+        .clock_skew(1, 1500)
+        .clock_skew(2, -1500)
+        .run();
+
+    report.assert_ok();
+    assert_eq!(report.total_ids, 16 * 5000);
+    assert!(report.ids_per_node.values().all(|&count| count == 5000));
+}
+
+/// Test that nodes that cannot be built are reported instead of silently skipped
+#[test]
+fn test_cluster_simulation_reports_errors() {
+    let report = ClusterSimulation::new(Snowflake::builder(0), [1, 1, 1024]).ids_per_node(10).run();
+    assert!(!report.is_ok());
+    assert_eq!(report.total_ids, 10);
+    assert!(matches!(report.errors.as_slice(), [(1024, SnowflakeError::MachineIdOutOfRange)]));
+}
+
+/// Test that a skewed clock is offset from its inner clock
+#[test]
+fn test_skewed_clock() {
+    let clock = SkewedClock::new(Arc::new(SystemClock), -60_000);
+    let diff = SystemClock.now_millis() - clock.now_millis();
+    assert!((60_000..60_100).contains(&diff));
+}
@@ -0,0 +1,87 @@
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+
+use snowflake_rs_impl::audit::AuditLog;
+use snowflake_rs_impl::config::SnowflakeConfig;
+use snowflake_rs_impl::exhaustion::{ExhaustionStrategy, DEFAULT_EXHAUSTION_HORIZON};
+use snowflake_rs_impl::layout::Layout;
+use snowflake_rs_impl::rate_limit::{RateLimit, ThrottleMode};
+use snowflake_rs_impl::region::RegionRegistry;
+use snowflake_rs_impl::snowflake::Snowflake;
+
+/// Test that a configuration survives a JSON round trip and rebuilds an equivalent generator
+#[test]
+fn test_config_round_trip() {
+    let snowflake = Snowflake::builder(12)
+        .epoch(1672531200000)
+        .layout(Layout::new(8, 14).unwrap())
+        .rate_limit(RateLimit::per_second(500).burst(50).mode(ThrottleMode::Wait))
+        .exhaustion_warning(Some(Duration::from_secs(86_400)))
+        .on_exhaustion(ExhaustionStrategy::Era)
+        .backfill_node(200)
+        .build()
+        .unwrap();
+
+    let config = snowflake.config();
+    assert_eq!(config.node, 12);
+    assert_eq!(config.epoch, 1672531200000);
+    assert_eq!(config.layout, Layout::new(8, 14).unwrap());
+    assert_eq!(config.rate_limit, snowflake.rate_limit());
+    assert_eq!(config.backfill_node, Some(200));
+
+    let json = serde_json::to_string(&config).unwrap();
+    let restored: SnowflakeConfig = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored, config);
+
+    let rebuilt = Snowflake::from_config(&restored).unwrap();
+    assert_eq!(rebuilt.config(), config);
+    let (_, node, _) = rebuilt.layout().decompose(rebuilt.generate().unwrap());
+    assert_eq!(node, 12);
+    assert_eq!(rebuilt.backfill_node(), Some(200));
+}
+
+/// Test that runtime-only settings do not survive a round trip, while the IDs do
+#[test]
+fn test_config_round_trip_drops_runtime_settings() {
+    let mut regions = RegionRegistry::new(Layout::DEFAULT, 2).unwrap();
+    regions.register("eu-west", 1).unwrap();
+    let snowflake = Snowflake::builder(200)
+        .region(Arc::new(regions), "eu-west")
+        .audit_log(Arc::new(AuditLog::from_writer(io::sink())))
+        .build()
+        .unwrap();
+
+    let config = snowflake.config();
+    assert_eq!(config.node, snowflake.node());
+    let rebuilt = Snowflake::from_config(&config).unwrap();
+    assert_eq!(rebuilt.config(), config);
+    assert_eq!(rebuilt.node(), snowflake.node());
+    assert_eq!(snowflake.region(), Some("eu-west"));
+    assert_eq!(rebuilt.region(), None);
+    assert!(snowflake.audit_log().is_some());
+    assert!(rebuilt.audit_log().is_none());
+}
+
+/// Test that optional fields fall back to the builder defaults
+#[test]
+fn test_config_defaults() {
+    let config: SnowflakeConfig = serde_json::from_str(r#"{"node": 3, "epoch": 1609459200000}"#).unwrap();
+    assert_eq!(config, Snowflake::new(3, None).unwrap().config());
+    assert_eq!(config.layout, Layout::DEFAULT);
+    assert_eq!(config.rate_limit, None);
+    assert_eq!(config.exhaustion_horizon, Some(DEFAULT_EXHAUSTION_HORIZON));
+    assert_eq!(config.exhaustion_strategy, ExhaustionStrategy::Error);
+    assert_eq!(config.backfill_node, None);
+}
+
+/// Test that an invalid configuration is rejected when building
+#[test]
+fn test_config_invalid() {
+    let mut config = Snowflake::new(1, None).unwrap().config();
+    config.node = 1024;
+    assert!(Snowflake::from_config(&config).is_err());
+
+    let json = r#"{"node": 3, "epoch": 1609459200000, "layout": {"node_bits": 60, "step_bits": 12}}"#;
+    assert!(serde_json::from_str::<SnowflakeConfig>(json).is_err());
+}
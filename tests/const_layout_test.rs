@@ -0,0 +1,48 @@
+use std::collections::HashSet;
+
+use snowflake_rs_impl::const_layout::{ConstSnowflake, DefaultConstSnowflake};
+use snowflake_rs_impl::generator::IdGenerator;
+use snowflake_rs_impl::layout::Layout;
+use snowflake_rs_impl::snowflake::{Snowflake, SnowflakeError};
+
+/// Test that the const layout matches the runtime layout with the same field widths
+#[test]
+fn test_const_layout_matches_runtime_layout() {
+    assert_eq!(DefaultConstSnowflake::LAYOUT, Layout::DEFAULT);
+    assert_eq!(ConstSnowflake::<8, 14>::LAYOUT, Layout::new(8, 14).unwrap());
+
+    let snowflake = ConstSnowflake::<8, 14>::new(255, Some(1672531200000)).unwrap();
+    let id = snowflake.generate().unwrap();
+    assert_eq!(ConstSnowflake::<8, 14>::parse_id(id), Layout::new(8, 14).unwrap().decompose(id));
+
+    let (_, node, _) = ConstSnowflake::<8, 14>::parse_id(id);
+    assert_eq!(node, 255);
+    assert!(matches!(ConstSnowflake::<8, 14>::new(256, None), Err(SnowflakeError::MachineIdOutOfRange)));
+}
+
+/// Test that the default const layout produces IDs readable by the runtime generator
+#[test]
+fn test_default_const_layout_ids() {
+    let snowflake = DefaultConstSnowflake::new(7, None).unwrap();
+    let id = snowflake.next_id().unwrap();
+    assert_eq!(id.node(), 7);
+
+    let (_, node, sequence) = Snowflake::parse_id(id.as_u64());
+    assert_eq!((node, sequence), (id.node(), id.sequence()));
+}
+
+/// Test that IDs are unique and increasing, including past one millisecond of sequence numbers
+#[test]
+fn test_const_layout_unique_ids() {
+    let snowflake = ConstSnowflake::<16, 4>::new(1, None).unwrap();
+    let ids: Vec<u64> = (0..1000).map(|_| snowflake.generate().unwrap()).collect();
+    assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+    assert_eq!(ids.iter().collect::<HashSet<_>>().len(), 1000);
+}
+
+/// Test that the epoch is validated like the runtime builder
+#[test]
+fn test_const_layout_invalid_epoch() {
+    assert!(matches!(DefaultConstSnowflake::new(1, Some(i64::MAX / 2)), Err(SnowflakeError::InvalidEpoch { .. })));
+    assert!(matches!(ConstSnowflake::<16, 16>::new(1, Some(0)), Err(SnowflakeError::InvalidEpoch { .. })));
+}
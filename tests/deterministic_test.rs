@@ -0,0 +1,61 @@
+#![cfg(feature = "test-utils")]
+
+use std::sync::Arc;
+
+use snowflake_rs_impl::generator::IdGenerator;
+use snowflake_rs_impl::id::SnowflakeId;
+use snowflake_rs_impl::layout::Layout;
+use snowflake_rs_impl::snowflake::{Snowflake, SnowflakeError};
+use snowflake_rs_impl::test_utils::DeterministicSnowflake;
+
+const START: i64 = 1_640_995_200_000;
+
+/// Test that two generators with the same settings produce the same IDs
+#[test]
+fn test_reproducible_ids() {
+    let first: Vec<u64> = {
+        let generator = DeterministicSnowflake::new(7, START);
+        (0..100).map(|_| generator.generate().unwrap()).collect()
+    };
+    let generator = DeterministicSnowflake::new(7, START);
+    let second: Vec<u64> = (0..100).map(|_| generator.generate().unwrap()).collect();
+    assert_eq!(first, second);
+
+    // Golden value: 2022-01-01T00:00:00Z relative to the default epoch, node 7
+    assert_eq!(first[0], (31_536_000_000 << 22) | (7 << 12));
+    assert_eq!(generator.issued(), 100);
+    generator.reset();
+    assert_eq!(generator.generate().unwrap(), first[0]);
+}
+
+/// Test how timestamps and sequence numbers advance with the counter
+#[test]
+fn test_ids_per_millisecond() {
+    let layout = Layout::new(8, 2).unwrap();
+    let generator = DeterministicSnowflake::new(3, START).epoch(START - 10).layout(layout).ids_per_millisecond(3);
+    let fields: Vec<(u64, u16, u16)> = (0..5).map(|_| layout.decompose(generator.generate().unwrap())).collect();
+    assert_eq!(fields, [(10, 3, 0), (10, 3, 1), (10, 3, 2), (11, 3, 0), (11, 3, 1)]);
+
+    // Capped at the layout's sequence numbers per millisecond
+    let generator = DeterministicSnowflake::new(3, START).ids_per_millisecond(u64::MAX);
+    let ids: Vec<u64> = (0..4097).map(|_| generator.generate().unwrap()).collect();
+    assert_eq!(Snowflake::parse_id(ids[4095]).2, 4095);
+    assert_eq!(Snowflake::parse_id(ids[4096]), (Snowflake::parse_id(ids[0]).0 + 1, 3, 0));
+}
+
+/// Test that invalid settings surface as generation errors
+#[test]
+fn test_errors() {
+    let generator = DeterministicSnowflake::new(1024, START);
+    assert!(matches!(generator.generate(), Err(SnowflakeError::MachineIdOutOfRange)));
+    let generator = DeterministicSnowflake::new(1, START).epoch(START + 1);
+    assert!(matches!(generator.generate(), Err(SnowflakeError::TimestampOutOfRange)));
+}
+
+/// Test that the generator can stand in for a real one behind `IdGenerator`
+#[test]
+fn test_as_id_generator() {
+    let generator: Arc<dyn IdGenerator> = Arc::new(DeterministicSnowflake::new(1, START));
+    assert_eq!(generator.next_id().unwrap().node(), 1);
+    assert!(generator.next_id().unwrap() > SnowflakeId::from(0));
+}
@@ -0,0 +1,67 @@
+#![cfg(feature = "duplicate-guard")]
+
+use std::sync::Arc;
+
+use snowflake_rs_impl::duplicate_guard::{DuplicateAction, DuplicateGuard};
+use snowflake_rs_impl::snowflake::{Snowflake, SnowflakeError};
+
+/// Test that every issued ID is recorded, including IDs from forks sharing the guard
+#[test]
+fn test_guard_records_issued_ids() {
+    let guard = Arc::new(DuplicateGuard::new(10_000, 1e-9).unwrap().action(DuplicateAction::Log));
+    let snowflake = Snowflake::builder(1).duplicate_guard(guard.clone()).build().unwrap();
+    let fork = snowflake.fork(2).unwrap();
+    assert!(Arc::ptr_eq(fork.duplicate_guard().unwrap(), &guard));
+
+    let id = snowflake.generate().unwrap();
+    let unchecked = snowflake.generate_unchecked();
+    let batch = snowflake.generate_batch(100).unwrap();
+    let forked = fork.generate().unwrap();
+
+    for id in [id, unchecked, forked].into_iter().chain(batch.iter().map(|id| id.as_u64())) {
+        assert!(guard.contains(id));
+    }
+    // An ID issued elsewhere that was already issued here is reported
+    assert!(guard.check(id));
+    assert!(!guard.check(forked + 1));
+}
+
+/// Test that a re-issued ID panics with the default action
+#[test]
+#[should_panic(expected = "may have been issued twice")]
+fn test_guard_panics_on_duplicate() {
+    let guard = Arc::new(DuplicateGuard::new(1000, 1e-9).unwrap());
+    let snowflake = Snowflake::builder(3).duplicate_guard(guard.clone()).build().unwrap();
+    let id = snowflake.generate().unwrap();
+    guard.check(id);
+}
+
+/// Test that the guard forgets old IDs after two generations and keeps false positives rare
+#[test]
+fn test_guard_bounded() {
+    let guard = DuplicateGuard::new(1000, 1e-9).unwrap().action(DuplicateAction::Log);
+    assert!((0..1000).all(|id| !guard.check(id)));
+    assert!((1000..2000).all(|id| !guard.check(id)));
+    assert!(guard.contains(0) && guard.contains(1999));
+
+    // A third generation replaces the first
+    assert!((2000..3000).all(|id| !guard.check(id)));
+    assert!((0..1000).all(|id| !guard.contains(id)));
+    assert!((1000..3000).all(|id| guard.contains(id)));
+
+    let guard = DuplicateGuard::new(10_000, 0.01).unwrap().action(DuplicateAction::Log);
+    (0..10_000).for_each(|id| {
+        guard.check(id);
+    });
+    let false_positives = (1_000_000..1_100_000).filter(|&id| guard.contains(id)).count();
+    assert!(false_positives < 2000, "{} false positives", false_positives);
+}
+
+/// Test that invalid guard parameters are rejected
+#[test]
+fn test_guard_invalid() {
+    assert!(matches!(DuplicateGuard::new(0, 0.01), Err(SnowflakeError::InvalidDuplicateGuard)));
+    assert!(matches!(DuplicateGuard::new(10, 0.0), Err(SnowflakeError::InvalidDuplicateGuard)));
+    assert!(matches!(DuplicateGuard::new(10, 1.0), Err(SnowflakeError::InvalidDuplicateGuard)));
+    assert!(matches!(DuplicateGuard::new(10, f64::NAN), Err(SnowflakeError::InvalidDuplicateGuard)));
+}
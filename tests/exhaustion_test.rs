@@ -0,0 +1,173 @@
+use std::sync::atomic::{AtomicI64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use snowflake_rs_impl::clock::{Clock, SystemClock};
+use snowflake_rs_impl::exhaustion::{era, ExhaustionStrategy, DEFAULT_EXHAUSTION_HORIZON, ERA_BIT};
+use snowflake_rs_impl::layout::Layout;
+use snowflake_rs_impl::snowflake::{Snowflake, SnowflakeError};
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+// Returns an epoch that puts the timestamp field of `layout` `days_left` days from exhaustion
+fn epoch_with_days_left(layout: &Layout, days_left: i64) -> i64 {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as i64;
+    now - (layout.max_timestamp() as i64 - days_left * DAY_MS)
+}
+
+// System clock shifted by an adjustable offset, to move a built generator past exhaustion
+struct OffsetClock(AtomicI64);
+
+impl Clock for OffsetClock {
+    fn now_millis(&self) -> i64 {
+        SystemClock.now_millis() + self.0.load(Ordering::SeqCst)
+    }
+}
+
+// Builds a generator one day from exhaustion whose clock then jumps two days ahead
+fn exhausted_generator(layout: Layout) -> Snowflake {
+    let clock = Arc::new(OffsetClock(AtomicI64::new(0)));
+    let snowflake = Snowflake::builder(1)
+        .layout(layout)
+        .epoch(epoch_with_days_left(&layout, 1))
+        .clock(clock.clone())
+        .build()
+        .unwrap();
+    clock.0.store(2 * DAY_MS, Ordering::SeqCst);
+    snowflake
+}
+
+/// Test that a generator far from exhaustion reports the remaining time and does not warn
+#[test]
+fn test_time_until_exhaustion() {
+    let warnings = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&warnings);
+    let snowflake = Snowflake::builder(1)
+        .on_exhaustion_warning(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        })
+        .build()
+        .unwrap();
+    snowflake.generate().unwrap();
+
+    let remaining = snowflake.time_until_exhaustion();
+    let expected = snowflake.max_timestamp_millis() - SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as i64;
+    assert!((remaining.as_millis() as i64 - expected).abs() < 1000);
+    assert_eq!(snowflake.exhaustion_horizon(), Some(DEFAULT_EXHAUSTION_HORIZON));
+    assert_eq!(warnings.load(Ordering::SeqCst), 0);
+}
+
+/// Test that the warning hook fires once when generating within the horizon
+#[test]
+fn test_exhaustion_warning_fires_once() {
+    let layout = Layout::new(12, 12).unwrap();
+    let remaining = Arc::new(parking_lot::Mutex::new(Vec::new()));
+    let hook_remaining = Arc::clone(&remaining);
+    let snowflake = Snowflake::builder(1)
+        .layout(layout)
+        .epoch(epoch_with_days_left(&layout, 100))
+        .on_exhaustion_warning(move |left| hook_remaining.lock().push(left))
+        .build()
+        .unwrap();
+
+    snowflake.generate().unwrap();
+    snowflake.generate_unchecked();
+    snowflake.generate_batch(10).unwrap();
+
+    let remaining = remaining.lock();
+    assert_eq!(remaining.len(), 1);
+    let days = remaining[0].as_secs() / 86_400;
+    assert!((99..=100).contains(&days), "unexpected remaining days: {}", days);
+    assert!(snowflake.time_until_exhaustion() <= Duration::from_secs(100 * 86_400));
+}
+
+/// Test that the horizon is configurable and can be disabled
+#[test]
+fn test_exhaustion_horizon_configurable() {
+    let layout = Layout::new(12, 12).unwrap();
+    let warnings = Arc::new(AtomicUsize::new(0));
+
+    let counter = Arc::clone(&warnings);
+    let outside_horizon = Snowflake::builder(1)
+        .layout(layout)
+        .epoch(epoch_with_days_left(&layout, 100))
+        .exhaustion_warning(Some(Duration::from_secs(30 * 86_400)))
+        .on_exhaustion_warning(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        })
+        .build()
+        .unwrap();
+    outside_horizon.generate().unwrap();
+    assert_eq!(warnings.load(Ordering::SeqCst), 0);
+
+    let counter = Arc::clone(&warnings);
+    let disabled = Snowflake::builder(1)
+        .layout(layout)
+        .epoch(epoch_with_days_left(&layout, 100))
+        .exhaustion_warning(None)
+        .on_exhaustion_warning(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        })
+        .build()
+        .unwrap();
+    disabled.generate().unwrap();
+    assert_eq!(disabled.exhaustion_horizon(), None);
+    assert_eq!(warnings.load(Ordering::SeqCst), 0);
+
+    let fork = outside_horizon.fork(2).unwrap();
+    assert_eq!(fork.exhaustion_horizon(), Some(Duration::from_secs(30 * 86_400)));
+}
+
+/// Test that an exhausted generator fails instead of issuing corrupt IDs by default
+#[test]
+fn test_exhausted_timestamp_errors() {
+    let snowflake = exhausted_generator(Layout::new(16, 16).unwrap());
+
+    assert_eq!(snowflake.exhaustion_strategy(), ExhaustionStrategy::Error);
+    assert!(matches!(snowflake.generate(), Err(SnowflakeError::TimestampExhausted)));
+    assert!(matches!(snowflake.generate_batch(10), Err(SnowflakeError::TimestampExhausted)));
+    assert_eq!(snowflake.time_until_exhaustion(), Duration::ZERO);
+}
+
+/// Test that generate_unchecked panics rather than issuing corrupt IDs once exhausted
+#[test]
+#[should_panic(expected = "Timestamp field is exhausted")]
+fn test_exhausted_timestamp_unchecked_panics() {
+    let snowflake = exhausted_generator(Layout::new(16, 16).unwrap());
+    snowflake.generate_unchecked();
+}
+
+/// Test that the era strategy continues past exhaustion with the era bit set
+#[test]
+fn test_exhausted_timestamp_era() {
+    let layout = Layout::new(16, 16).unwrap();
+    let epoch = epoch_with_days_left(&layout, -1);
+    let snowflake = Snowflake::builder(3)
+        .layout(layout)
+        .epoch(epoch)
+        .on_exhaustion(ExhaustionStrategy::Era)
+        .build()
+        .unwrap();
+
+    let id = snowflake.generate().unwrap();
+    assert_eq!(era(id), 1);
+    let (timestamp, node, _) = layout.decompose(id & !ERA_BIT);
+    assert_eq!(node, 3);
+    // The clock is one day past the first era, which ends at `max_timestamp + 1`
+    let into_era = timestamp as i64 - (DAY_MS - 1);
+    assert!((0..1000).contains(&into_era), "unexpected timestamp: {}", timestamp);
+
+    let batch = snowflake.generate_batch(10).unwrap();
+    assert!(batch.iter().all(|batch_id| era(batch_id.as_u64()) == 1 && batch_id.as_u64() > id));
+    assert_eq!(snowflake.fork(4).unwrap().exhaustion_strategy(), ExhaustionStrategy::Era);
+
+    let first_era = Snowflake::builder(3)
+        .layout(layout)
+        .epoch(epoch_with_days_left(&layout, 1))
+        .on_exhaustion(ExhaustionStrategy::Era)
+        .build()
+        .unwrap();
+    let first_era_id = first_era.generate().unwrap();
+    assert_eq!(era(first_era_id), 0);
+    assert!(first_era_id < id);
+}
@@ -0,0 +1,79 @@
+use snowflake_rs_impl::id::SnowflakeId;
+use snowflake_rs_impl::layout::Layout;
+use snowflake_rs_impl::snowflake::Snowflake;
+
+/// Test that explain decodes the fields and absolute time of a generated ID
+#[test]
+fn test_explain_generated_id() {
+    let snowflake = Snowflake::new(42, Some(1672531200000)).unwrap();
+    let id = snowflake.generate_id().unwrap();
+    let explanation = id.explain(Some(1672531200000));
+
+    assert_eq!(explanation.id, id.as_u64());
+    assert_eq!(explanation.layout, Layout::DEFAULT);
+    assert_eq!(explanation.era, 0);
+    assert_eq!(explanation.node, 42);
+    assert_eq!(explanation.sequence, id.sequence());
+    assert_eq!(explanation.timestamp, id.timestamp());
+    assert_eq!(explanation.unix_millis, 1672531200000 + id.timestamp() as i64);
+    assert!(explanation.utc.starts_with("20") && explanation.utc.ends_with('Z'));
+}
+
+/// Test the UTC formatting and bit segments against known values
+#[test]
+fn test_explain_known_values() {
+    // 2024-02-29T12:34:56.789Z relative to the default epoch
+    let timestamp = 1709210096789 - 1609459200000;
+    let id = SnowflakeId::from_u64(timestamp << 22 | 3 << 12 | 9);
+    let explanation = id.explain(None);
+    assert_eq!(explanation.utc, "2024-02-29T12:34:56.789Z");
+
+    let [era, timestamp_bits, node_bits, sequence_bits] = explanation.bit_segments();
+    assert_eq!(era, "0");
+    assert_eq!(timestamp_bits.len(), 41);
+    assert_eq!(u64::from_str_radix(&timestamp_bits, 2).unwrap(), timestamp);
+    assert_eq!(node_bits, "0000000011");
+    assert_eq!(sequence_bits, "000000001001");
+
+    let report = explanation.to_string();
+    assert!(report.contains("Layout:    41/10/12"));
+    assert!(report.contains("Epoch:     1609459200000 (2021-01-01T00:00:00.000Z)"));
+    assert!(report.contains("Node:      3"));
+    assert!(report.contains("Sequence:  9"));
+}
+
+/// Test explain with a custom layout and an epoch before Unix epoch
+#[test]
+fn test_explain_custom_layout() {
+    let layout = Layout::new(4, 8).unwrap();
+    let id = SnowflakeId::from_u64(layout.compose(1000, 15, 255).unwrap());
+    let explanation = id.explain_with_layout(Some(-86_400_000), &layout);
+
+    assert_eq!((explanation.timestamp, explanation.node, explanation.sequence), (1000, 15, 255));
+    assert_eq!(explanation.utc, "1969-12-31T00:00:01.000Z");
+    assert_eq!(explanation.bit_segments()[2], "1111");
+
+    let json = serde_json::to_value(&explanation).unwrap();
+    assert_eq!(json["node"], 15);
+    assert_eq!(json["utc"], "1969-12-31T00:00:01.000Z");
+}
+
+/// Test that explain reports the check field and whether it matches
+#[test]
+fn test_explain_check_bits() {
+    let layout = Layout::new(4, 8).unwrap().with_check_bits(3).unwrap();
+    let id = layout.compose(1000, 15, 255).unwrap();
+    let explanation = SnowflakeId::from_u64(id).explain_with_layout(None, &layout);
+    assert_eq!((explanation.timestamp, explanation.node, explanation.sequence), (1000, 15, 255));
+    assert!(explanation.checksum_valid);
+    let report = explanation.to_string();
+    assert!(report.contains("Layout:    48/4/8/3 (timestamp/node/sequence/check bits)"));
+    assert!(report.contains("Checksum:  valid"));
+    assert!(report.ends_with(&format!("| {:03b}", id & 0b111)));
+
+    let corrupted = SnowflakeId::from_u64(id ^ 1 << 20).explain_with_layout(None, &layout);
+    assert!(!corrupted.checksum_valid);
+    assert!(corrupted.to_string().contains("Checksum:  MISMATCH"));
+    // Layouts without a check field report no checksum
+    assert!(!SnowflakeId::from_u64(id).explain(None).to_string().contains("Checksum"));
+}
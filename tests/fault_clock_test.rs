@@ -0,0 +1,57 @@
+#![cfg(feature = "test-utils")]
+
+use std::sync::Arc;
+
+use snowflake_rs_impl::clock::Clock;
+use snowflake_rs_impl::snowflake::{Snowflake, SnowflakeError};
+use snowflake_rs_impl::test_utils::{ClockFault, FaultyClock, ManualClock};
+
+const START: i64 = 1_700_000_000_000;
+
+/// Test that a scheduled backward step makes generation report the rollback
+#[test]
+fn test_step_backward_detected() {
+    let base = Arc::new(ManualClock::new(START));
+    // Reading 0 is taken by `build` to validate the epoch
+    let clock = Arc::new(FaultyClock::new(base.clone()).at_reading(3, ClockFault::StepBackward(5)));
+    let snowflake = Snowflake::builder(1).clock(clock).build().unwrap();
+
+    snowflake.generate().unwrap();
+    base.advance(1);
+    snowflake.generate().unwrap();
+    base.advance(1);
+    assert!(matches!(snowflake.generate(), Err(SnowflakeError::ClockMovedBackwards)));
+
+    // Once real time passes the last issued timestamp, generation recovers
+    base.advance(5);
+    assert!(snowflake.generate().is_ok());
+}
+
+/// Test that a paused clock freezes readings and resumes without jumping ahead
+#[test]
+fn test_pause_and_resume() {
+    let base = Arc::new(ManualClock::new(START));
+    let clock = FaultyClock::new(base.clone());
+
+    clock.inject(ClockFault::Pause);
+    base.advance(100);
+    assert_eq!(clock.now_millis(), START);
+
+    clock.inject(ClockFault::Resume);
+    assert_eq!(clock.now_millis(), START);
+    base.advance(1);
+    assert_eq!(clock.now_millis(), START + 1);
+}
+
+/// Test that jitter stays within bounds and is the same on every run
+#[test]
+fn test_jitter_is_deterministic() {
+    fn readings() -> Vec<i64> {
+        let clock = FaultyClock::new(Arc::new(ManualClock::new(START))).at_reading(0, ClockFault::Jitter(3));
+        (0..100).map(|_| clock.now_millis()).collect()
+    }
+    let first = readings();
+    assert_eq!(first, readings());
+    assert!(first.iter().all(|t| (START - 3..=START + 3).contains(t)));
+    assert!(first.iter().any(|&t| t != START));
+}
@@ -0,0 +1,37 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use snowflake_rs_impl::generator::{IdError, IdGenerator};
+use snowflake_rs_impl::id::SnowflakeId;
+use snowflake_rs_impl::snowflake::Snowflake;
+
+// A mock generator handing out consecutive IDs
+struct CountingGenerator {
+    next: AtomicU64,
+}
+
+impl IdGenerator for CountingGenerator {
+    fn next_id(&self) -> Result<SnowflakeId, IdError> {
+        Ok(SnowflakeId::from(self.next.fetch_add(1, Ordering::SeqCst)))
+    }
+}
+
+// Application code that only depends on the trait
+fn assign_ids(generator: &dyn IdGenerator, count: usize) -> Vec<SnowflakeId> {
+    (0..count).map(|_| generator.next_id().unwrap()).collect()
+}
+
+/// Test that Snowflake and a mock can be used interchangeably through the trait
+#[test]
+fn test_id_generator_trait_objects() {
+    let generators: Vec<Arc<dyn IdGenerator>> = vec![
+        Arc::new(Snowflake::new(1, None).unwrap()),
+        Arc::new(CountingGenerator { next: AtomicU64::new(100) }),
+    ];
+
+    for generator in &generators {
+        let ids = assign_ids(generator.as_ref(), 10);
+        assert!(ids.windows(2).all(|w| w[0] < w[1]));
+    }
+    assert_eq!(assign_ids(&generators[1], 1)[0], SnowflakeId::from(110));
+}
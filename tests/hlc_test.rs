@@ -0,0 +1,125 @@
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use snowflake_rs_impl::clock::Clock;
+use snowflake_rs_impl::hlc::{HlcSnowflake, DEFAULT_MAX_DRIFT};
+use snowflake_rs_impl::layout::Layout;
+use snowflake_rs_impl::snowflake::SnowflakeError;
+
+const START: i64 = 1_700_000_000_000;
+
+// Clock that only moves when told to
+struct FixedClock(AtomicI64);
+
+impl Clock for FixedClock {
+    fn now_millis(&self) -> i64 {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+fn generator(node: u16, now: i64) -> (HlcSnowflake, Arc<FixedClock>) {
+    let clock = Arc::new(FixedClock(AtomicI64::new(now)));
+    let snowflake = HlcSnowflake::builder(node).clock(clock.clone()).build().unwrap();
+    (snowflake, clock)
+}
+
+// Returns the (timestamp, sequence) pair an ID sorts by, ignoring the node ID
+fn hlc_reading(id: u64) -> (u64, u16) {
+    let (timestamp, _, sequence) = Layout::DEFAULT.decompose(id);
+    (timestamp, sequence)
+}
+
+/// Test that IDs keep increasing while the clock stalls or steps backwards
+#[test]
+fn test_hlc_never_goes_backwards() {
+    let (snowflake, clock) = generator(1, START);
+    let mut ids = Vec::new();
+    for _ in 0..5000 {
+        ids.push(snowflake.generate().unwrap());
+    }
+    clock.0.store(START - 10_000, Ordering::SeqCst);
+    for _ in 0..100 {
+        ids.push(snowflake.generate().unwrap());
+    }
+    assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+
+    // 5000 IDs in one millisecond overflow the counter into the next millisecond
+    let (timestamp, _) = hlc_reading(*ids.last().unwrap());
+    assert_eq!(timestamp as i64 + snowflake.epoch(), START + 1);
+}
+
+/// Test that IDs generated after observing a remote ID sort after it despite clock skew
+#[test]
+fn test_hlc_observe_skewed_sender() {
+    let (sender, _) = generator(1, START + 30_000);
+    let (receiver, receiver_clock) = generator(2, START);
+
+    let sent = sender.generate().unwrap();
+    assert!(hlc_reading(receiver.generate().unwrap()) < hlc_reading(sent));
+
+    receiver.observe(sent).unwrap();
+    let reply = receiver.generate().unwrap();
+    assert!(hlc_reading(reply) > hlc_reading(sent));
+    assert_eq!(hlc_reading(reply).0, hlc_reading(sent).0);
+
+    // Once the local clock passes the observed timestamp, it follows the clock again
+    receiver_clock.0.store(START + 40_000, Ordering::SeqCst);
+    let (timestamp, sequence) = hlc_reading(receiver.generate().unwrap());
+    assert_eq!((timestamp as i64 + receiver.epoch(), sequence), (START + 40_000, 0));
+}
+
+/// Test that IDs generated after observing an ID from a higher node in the same
+/// millisecond sort after it
+#[test]
+fn test_hlc_observe_higher_node() {
+    let (sender, _) = generator(900, START);
+    let (receiver, _) = generator(1, START);
+    receiver.generate().unwrap();
+
+    let sent = sender.generate().unwrap();
+    receiver.observe(sent).unwrap();
+    let reply = receiver.generate().unwrap();
+    assert!(reply > sent);
+    assert_eq!(hlc_reading(reply), (hlc_reading(sent).0 + 1, 0));
+
+    // An ID from a lower node only needs a higher sequence number
+    let (lower, _) = generator(0, START);
+    let sent = lower.generate_id().unwrap();
+    receiver.observe(sent.as_u64()).unwrap();
+    assert!(receiver.generate_id().unwrap() > sent);
+}
+
+/// Test that observing an older ID or timestamp does not move the clock
+#[test]
+fn test_hlc_observe_older() {
+    let (snowflake, _) = generator(3, START);
+    let first = snowflake.generate().unwrap();
+    snowflake.observe(0).unwrap();
+    snowflake.observe_millis(START - 5).unwrap();
+    let second = snowflake.generate().unwrap();
+    assert_eq!(hlc_reading(second), (hlc_reading(first).0, 1));
+
+    snowflake.observe_millis(START + 5).unwrap();
+    let third = snowflake.generate().unwrap();
+    assert_eq!(hlc_reading(third).0 as i64 + snowflake.epoch(), START + 6);
+}
+
+/// Test that timestamps too far ahead are rejected without moving the clock
+#[test]
+fn test_hlc_max_drift() {
+    let (snowflake, _) = generator(1, START);
+    assert_eq!(snowflake.max_drift(), DEFAULT_MAX_DRIFT);
+    let drift = DEFAULT_MAX_DRIFT.as_millis() as i64 + 1;
+    assert!(matches!(snowflake.observe_millis(START + drift), Err(SnowflakeError::ClockDriftExceeded(d)) if d == drift));
+
+    let strict = HlcSnowflake::builder(1)
+        .clock(Arc::new(FixedClock(AtomicI64::new(START))))
+        .max_drift(Duration::from_millis(10))
+        .build()
+        .unwrap();
+    assert!(strict.observe_millis(START + 10).is_ok());
+    assert!(strict.observe_millis(START + 11).is_err());
+    let (timestamp, _) = hlc_reading(strict.generate().unwrap());
+    assert_eq!(timestamp as i64 + strict.epoch(), START + 11);
+}
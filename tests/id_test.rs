@@ -0,0 +1,210 @@
+use std::time::Duration;
+
+use snowflake_rs_impl::exhaustion::ERA_BIT;
+use snowflake_rs_impl::id::{IdValidator, SnowflakeId, SORTABLE_STRING_LEN};
+use snowflake_rs_impl::layout::Layout;
+use snowflake_rs_impl::snowflake::{InvalidIdReason, Snowflake, SnowflakeError};
+
+/// Test that successor and predecessor step by exactly one and stop at the bounds
+#[test]
+fn test_successor_predecessor() {
+    let snowflake = Snowflake::new(1, None).unwrap();
+    let id = snowflake.generate_id().unwrap();
+
+    let next = id.checked_successor().unwrap();
+    assert_eq!(next.as_u64(), id.as_u64() + 1);
+    assert_eq!(next.checked_predecessor(), Some(id));
+
+    assert_eq!(SnowflakeId::MAX.checked_successor(), None);
+    assert_eq!(SnowflakeId::MIN.checked_predecessor(), None);
+}
+
+/// Test that offsetting by a duration only touches the timestamp field
+#[test]
+fn test_offset_by_duration() {
+    let snowflake = Snowflake::new(42, None).unwrap();
+    let id = snowflake.generate_id().unwrap();
+
+    let later = id.offset_by(Duration::from_millis(1500)).unwrap();
+    assert_eq!(later.timestamp(), id.timestamp() + 1500);
+    assert_eq!(later.node(), id.node());
+    assert_eq!(later.sequence(), id.sequence());
+    assert_eq!(later.offset_back_by(Duration::from_millis(1500)), Some(id));
+
+    assert_eq!(id.offset_back_by(Duration::from_millis(id.timestamp() + 1)), None);
+    assert_eq!(SnowflakeId::MAX.offset_by(Duration::from_millis(1)), None);
+}
+
+/// Test that successors and offsets of second-era IDs stay in the second era
+#[test]
+fn test_second_era_ids_keep_era() {
+    let id = SnowflakeId::from_u64(ERA_BIT | Snowflake::new(42, None).unwrap().generate().unwrap());
+
+    let later = id.offset_by(Duration::from_millis(1500)).unwrap();
+    assert!(later > id);
+    assert_eq!(later.as_u64() & ERA_BIT, ERA_BIT);
+    assert_eq!(later.offset_back_by(Duration::from_millis(1500)), Some(id));
+    assert_eq!(id.offset_back_by(Duration::from_millis(id.timestamp() + 1)), None);
+
+    let next = id.checked_successor().unwrap();
+    assert_eq!(next.as_u64(), id.as_u64() + 1);
+    assert_eq!(next.checked_predecessor(), Some(id));
+    assert_eq!(SnowflakeId::from_u64(u64::MAX).checked_successor(), None);
+    assert_eq!(SnowflakeId::from_u64(ERA_BIT).checked_predecessor(), None);
+    assert!(SnowflakeId::from_u64(ERA_BIT) > SnowflakeId::MAX);
+}
+
+/// Test that the accessors agree with Snowflake::parse_id
+#[test]
+fn test_id_fields_match_parse_id() {
+    let snowflake = Snowflake::new(7, None).unwrap();
+    let id = snowflake.generate_id().unwrap();
+    let (timestamp, node, sequence) = Snowflake::parse_id(id.as_u64());
+    assert_eq!(id.timestamp(), timestamp);
+    assert_eq!(id.node(), node);
+    assert_eq!(id.sequence(), sequence);
+}
+
+/// Test that the validated conversions reject the reserved bit and negative values
+#[test]
+fn test_validated_conversions() {
+    let id = Snowflake::new(1, None).unwrap().generate().unwrap();
+    assert_eq!(SnowflakeId::try_from_u64(id).unwrap().as_u64(), id);
+    assert_eq!(SnowflakeId::try_from(id as i64).unwrap().as_u64(), id);
+
+    assert!(matches!(
+        SnowflakeId::try_from_u64(id | ERA_BIT),
+        Err(SnowflakeError::InvalidId(InvalidIdReason::ReservedBitSet))
+    ));
+    assert!(matches!(
+        SnowflakeId::try_from(-1i64),
+        Err(SnowflakeError::InvalidId(InvalidIdReason::Negative))
+    ));
+    assert_eq!(
+        SnowflakeError::InvalidId(InvalidIdReason::ReservedBitSet).to_string(),
+        "Invalid ID: reserved top bit is set"
+    );
+}
+
+/// Test the opt-in timestamp plausibility checks
+#[test]
+fn test_validator_timestamp_checks() {
+    let epoch = 1_609_459_200_000;
+    let validator = IdValidator::new().not_before(epoch + 1000).max_future_skew(Duration::from_secs(60));
+
+    let live = Snowflake::new(1, None).unwrap().generate().unwrap();
+    assert!(validator.validate(live).is_ok());
+
+    let old = Snowflake::compose_id(999, 1, 0).unwrap().as_u64();
+    assert!(matches!(
+        validator.validate(old),
+        Err(SnowflakeError::InvalidId(InvalidIdReason::TooOld { unix_millis, not_before }))
+            if unix_millis == epoch + 999 && not_before == epoch + 1000
+    ));
+
+    let future = SnowflakeId::MAX.as_u64();
+    assert!(matches!(validator.validate(future), Err(SnowflakeError::InvalidId(InvalidIdReason::InFuture { .. }))));
+    // Without the checks, any value without the reserved bit is accepted
+    assert!(IdValidator::new().validate(old).is_ok());
+    assert!(IdValidator::new().validate(future).is_ok());
+}
+
+/// Test that second-era IDs are accepted only when allowed, with their real time
+#[test]
+fn test_validator_allow_era() {
+    let second_era = ERA_BIT | 5 << 22;
+    assert!(IdValidator::new().validate(second_era).is_err());
+    assert!(IdValidator::new().validate_i64(second_era as i64).is_err());
+
+    let validator = IdValidator::new().allow_era(true);
+    assert_eq!(validator.validate(second_era).unwrap().as_u64(), second_era);
+    assert_eq!(validator.validate_i64(second_era as i64).unwrap().as_u64(), second_era);
+    // A second-era ID lies after every first-era ID, so a future check applies to it
+    let checked = validator.epoch(0).max_future_skew(Duration::ZERO);
+    assert!(matches!(checked.validate(second_era), Err(SnowflakeError::InvalidId(InvalidIdReason::InFuture { .. }))));
+}
+
+/// Test that base62 encoding round-trips and rejects malformed input
+#[test]
+fn test_base62() {
+    for id in [0, 61, 62, 1266, u64::MAX] {
+        let id = SnowflakeId::from_u64(id);
+        assert_eq!(SnowflakeId::from_base62(&id.to_base62()).unwrap(), id);
+    }
+    assert_eq!(SnowflakeId::from_u64(1266).to_base62(), "KQ");
+    assert_eq!(SnowflakeId::from_u64(u64::MAX).to_base62(), "LygHa16AHYF");
+    for invalid in ["", "K-Q", "LygHa16AHYG"] {
+        assert!(matches!(SnowflakeId::from_base62(invalid), Err(SnowflakeError::InvalidEncoding)));
+    }
+}
+
+/// Test that sortable strings have a fixed width, sort like the IDs and round-trip
+#[test]
+fn test_sortable_string() {
+    let mut ids: Vec<SnowflakeId> = [0, 9, 10, 1266, 1 << 40, i64::MAX as u64, u64::MAX]
+        .into_iter()
+        .map(SnowflakeId::from_u64)
+        .collect();
+    let snowflake = Snowflake::new(1, None).unwrap();
+    ids.extend((0..100).map(|_| snowflake.generate_id().unwrap()));
+    ids.sort();
+
+    let strings: Vec<String> = ids.iter().map(SnowflakeId::to_sortable_string).collect();
+    assert!(strings.iter().all(|string| string.len() == SORTABLE_STRING_LEN));
+    assert!(strings.windows(2).all(|pair| pair[0] <= pair[1]));
+    for (id, string) in ids.iter().zip(&strings) {
+        assert_eq!(SnowflakeId::from_sortable_string(string).unwrap(), *id);
+        // Sortable strings are plain decimals, so they deserialize like any decimal string
+        assert_eq!(serde_json::from_str::<SnowflakeId>(&format!("\"{}\"", string)).unwrap(), *id);
+    }
+    assert_eq!(SnowflakeId::from_u64(u64::MAX).to_sortable_string(), "18446744073709551615");
+
+    let invalid = [
+        "",
+        "1266",
+        "0000000000000000126a",
+        "+0000000000000001266",
+        "18446744073709551616",
+        "000000000000000001266",
+    ];
+    for invalid in invalid {
+        let result = SnowflakeId::from_sortable_string(invalid);
+        assert!(matches!(result, Err(SnowflakeError::InvalidEncoding)), "input {}", invalid);
+    }
+}
+
+/// Test that IDs deserialize from numbers, decimal strings and prefixed base62 strings
+#[test]
+fn test_flexible_deserialize() {
+    let id = Snowflake::new(1, None).unwrap().generate_id().unwrap();
+    let inputs = [
+        format!("{}", id),
+        format!("\"{}\"", id),
+        format!("\"b62_{}\"", id.to_base62()),
+    ];
+    for input in &inputs {
+        assert_eq!(serde_json::from_str::<SnowflakeId>(input).unwrap(), id, "input {}", input);
+    }
+    // Serialization is unchanged: a plain number
+    assert_eq!(serde_json::to_string(&id).unwrap(), id.to_string());
+
+    for invalid in ["-1", "1.5", "\"12ab\"", "\"b62_!\"", "\"\"", "null", "\"18446744073709551616\""] {
+        assert!(serde_json::from_str::<SnowflakeId>(invalid).is_err(), "input {}", invalid);
+    }
+}
+
+/// Test that the validator verifies the check field of layouts that have one
+#[test]
+fn test_validator_checksum() {
+    let layout = Layout::new(6, 12).unwrap().with_check_bits(4).unwrap();
+    let snowflake = Snowflake::builder(1).layout(layout).build().unwrap();
+    let id = snowflake.generate().unwrap();
+    let validator = IdValidator::new().epoch(snowflake.epoch()).layout(layout);
+    assert_eq!(validator.validate(id).unwrap().as_u64(), id);
+    for bit in 0..63 {
+        let result = validator.validate(id ^ 1 << bit);
+        assert!(matches!(result, Err(SnowflakeError::InvalidId(InvalidIdReason::ChecksumMismatch))), "bit {}", bit);
+    }
+    // The default layout has no check field, so the same corruption goes unnoticed
+    assert!(IdValidator::new().validate(id ^ 1).is_ok());
+}
@@ -0,0 +1,175 @@
+use snowflake_rs_impl::layout::Layout;
+use snowflake_rs_impl::snowflake::{Snowflake, SnowflakeError};
+
+/// Test that block encoding matches per-ID composition
+#[test]
+fn test_encode_block_matches_compose() {
+    let layout = Layout::new(8, 14).unwrap();
+    let sequences: Vec<u16> = (0..1000).map(|i| (i * 7) % 16384).collect();
+    let mut out = vec![0; sequences.len()];
+    layout.encode_block(123_456, 200, &sequences, &mut out).unwrap();
+    for (&id, &sequence) in out.iter().zip(&sequences) {
+        assert_eq!(id, layout.compose(123_456, 200, sequence).unwrap());
+    }
+
+    let mut range = vec![0; 100];
+    layout.encode_range(123_456, 200, 50, &mut range).unwrap();
+    assert_eq!(layout.decompose(range[0]), (123_456, 200, 50));
+    assert_eq!(layout.decompose(range[99]), (123_456, 200, 149));
+}
+
+/// Test that out-of-range fields are rejected before anything is written
+#[test]
+fn test_encode_block_out_of_range() {
+    let layout = Layout::DEFAULT;
+    let mut out = vec![0; 2];
+    let result = layout.encode_block(1, 1, &[1, 4096], &mut out);
+    assert!(matches!(result, Err(SnowflakeError::SequenceOutOfRange)));
+    assert_eq!(out, vec![0, 0]);
+
+    let result = layout.encode_block(1, 1024, &[1, 2], &mut out);
+    assert!(matches!(result, Err(SnowflakeError::MachineIdOutOfRange)));
+
+    let mut range = vec![0; 10];
+    let result = layout.encode_range(1, 1, 4090, &mut range);
+    assert!(matches!(result, Err(SnowflakeError::SequenceOutOfRange)));
+}
+
+/// Test that a generator with a custom layout encodes IDs with that layout
+#[test]
+fn test_generator_custom_layout() {
+    let layout = Layout::new(8, 14).unwrap();
+    let snowflake = Snowflake::builder(200).layout(layout).build().unwrap();
+    assert_eq!(snowflake.layout(), layout);
+    assert_eq!(snowflake.layout().max_node(), 255);
+    assert_eq!(snowflake.layout().max_sequence(), 16383);
+
+    let ids = snowflake.generate_batch(20_000).unwrap();
+    assert!(ids.windows(2).all(|w| w[0] < w[1]));
+    for id in &ids {
+        let (timestamp, node, _) = layout.decompose(id.as_u64());
+        assert_eq!(node, 200);
+        assert!(timestamp as i64 + snowflake.epoch() <= snowflake.max_timestamp_millis());
+    }
+    let (_, node, _) = layout.decompose(snowflake.generate().unwrap());
+    assert_eq!(node, 200);
+
+    let result = Snowflake::builder(256).layout(layout).build();
+    assert!(matches!(result, Err(SnowflakeError::MachineIdOutOfRange)));
+}
+
+/// Test the layout accessor for the default generator
+#[test]
+fn test_generator_default_layout() {
+    let snowflake = Snowflake::new(1, None).unwrap();
+    let layout = snowflake.layout();
+    assert_eq!(layout, Layout::DEFAULT);
+    assert_eq!((layout.timestamp_bits(), layout.node_bits(), layout.step_bits()), (41, 10, 12));
+    // 2^41 - 1 ms after 2021-01-01 is in 2090
+    assert_eq!(snowflake.max_timestamp_millis(), 1609459200000 + (1 << 41) - 1);
+}
+
+/// Test that the layout survives a snapshot round trip and a fork
+#[test]
+fn test_layout_snapshot_and_fork() {
+    let layout = Layout::new(4, 16).unwrap();
+    let snowflake = Snowflake::builder(15).layout(layout).build().unwrap();
+    let last = *snowflake.generate_batch(70_000).unwrap().last().unwrap();
+
+    let resumed = Snowflake::from_snapshot(&snowflake.snapshot()).unwrap();
+    assert_eq!(resumed.layout(), layout);
+    assert!(resumed.generate_id().unwrap() > last);
+    assert_eq!(snowflake.fork(3).unwrap().layout(), layout);
+}
+
+/// Test that check bits shorten the timestamp and round-trip through compose
+#[test]
+fn test_check_bits_compose() {
+    for check_bits in 1..=8 {
+        let layout = Layout::DEFAULT.with_check_bits(check_bits).unwrap();
+        assert_eq!(layout.check_bits(), check_bits);
+        assert_eq!(layout.timestamp_bits(), 41 - check_bits);
+        let id = layout.compose(layout.max_timestamp(), 1023, 4095).unwrap();
+        assert_eq!(layout.decompose(id), (layout.max_timestamp(), 1023, 4095));
+        assert!(layout.has_valid_checksum(id));
+        assert_eq!(layout.with_checksum(id), id);
+    }
+    assert!(matches!(Layout::DEFAULT.with_check_bits(9), Err(SnowflakeError::InvalidLayout)));
+    assert_eq!(Layout::DEFAULT.with_check_bits(0).unwrap(), Layout::DEFAULT);
+    // Without a check field every ID is valid
+    assert!(Layout::DEFAULT.has_valid_checksum(u64::MAX));
+}
+
+/// Test that every single-bit flip and short burst of flips is detected
+#[test]
+fn test_check_bits_detect_corruption() {
+    for check_bits in 1..=8 {
+        let layout = Layout::DEFAULT.with_check_bits(check_bits).unwrap();
+        let id = layout.compose(123_456_789, 513, 77).unwrap();
+        for bit in 0..64 {
+            assert!(!layout.has_valid_checksum(id ^ 1 << bit), "flip of bit {} with {} check bits", bit, check_bits);
+            for burst in (2..=check_bits as u32).filter(|burst| bit + burst <= 64) {
+                // A burst flips its first and last bit and any bits in between
+                for pattern in [(1u64 << burst) - 1, 1 | 1 << (burst - 1)] {
+                    let mask = pattern << bit;
+                    assert!(!layout.has_valid_checksum(id ^ mask), "burst {:b} at bit {}", pattern, bit);
+                }
+            }
+        }
+    }
+}
+
+/// Test that block encoding fills in the check field like compose
+#[test]
+fn test_encode_block_with_check_bits() {
+    let layout = Layout::new(8, 14).unwrap().with_check_bits(5).unwrap();
+    let sequences: Vec<u16> = (0..1000).map(|i| (i * 7) % 16384).collect();
+    let mut out = vec![0; sequences.len()];
+    layout.encode_block(123_456, 200, &sequences, &mut out).unwrap();
+    for (&id, &sequence) in out.iter().zip(&sequences) {
+        assert_eq!(id, layout.compose(123_456, 200, sequence).unwrap());
+    }
+    let mut range = vec![0; 100];
+    layout.encode_range(123_456, 200, 50, &mut range).unwrap();
+    assert!(range.iter().all(|&id| layout.has_valid_checksum(id)));
+    assert_eq!(layout.decompose(range[99]), (123_456, 200, 149));
+}
+
+/// Test that generators fill in the check field and IDs stay ordered
+#[test]
+fn test_generator_check_bits() {
+    // Taking the check bits from the node field keeps the default 41-bit timestamp
+    let layout = Layout::new(6, 12).unwrap().with_check_bits(4).unwrap();
+    assert_eq!(layout.timestamp_bits(), 41);
+    let snowflake = Snowflake::builder(9).layout(layout).build().unwrap();
+    let mut ids: Vec<u64> = (0..5000).map(|_| snowflake.generate().unwrap()).collect();
+    ids.extend(snowflake.generate_batch(5000).unwrap().iter().map(|id| id.as_u64()));
+    ids.push(snowflake.generate_unchecked());
+    assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+    assert!(ids.iter().all(|&id| layout.has_valid_checksum(id)));
+    assert!(ids.iter().all(|&id| layout.decompose(id).1 == 9));
+}
+
+/// Test that layouts without a check field serialize as before
+#[test]
+fn test_check_bits_serde() {
+    assert_eq!(serde_json::to_string(&Layout::DEFAULT).unwrap(), r#"{"node_bits":10,"step_bits":12}"#);
+    let layout = Layout::DEFAULT.with_check_bits(3).unwrap();
+    let json = serde_json::to_string(&layout).unwrap();
+    assert_eq!(json, r#"{"node_bits":10,"step_bits":12,"check_bits":3}"#);
+    assert_eq!(serde_json::from_str::<Layout>(&json).unwrap(), layout);
+    assert_eq!(serde_json::from_str::<Layout>(r#"{"node_bits":10,"step_bits":12}"#).unwrap(), Layout::DEFAULT);
+}
+
+/// Test that deserializing rejects layouts `Layout::new` and `with_check_bits` reject
+#[test]
+fn test_layout_deserialize_rejects_invalid() {
+    for json in [
+        r#"{"node_bits":60,"step_bits":12}"#,
+        r#"{"node_bits":10,"step_bits":0}"#,
+        r#"{"node_bits":10,"step_bits":12,"check_bits":9}"#,
+    ] {
+        let err = serde_json::from_str::<Layout>(json).unwrap_err();
+        assert!(err.to_string().contains("Invalid bit layout"), "{}", err);
+    }
+}
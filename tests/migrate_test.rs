@@ -0,0 +1,111 @@
+use snowflake_rs_impl::exhaustion::ERA_BIT;
+use snowflake_rs_impl::layout::Layout;
+use snowflake_rs_impl::migrate::{migrate_epoch, migrate_epoch_bulk, repack_layout, repack_layout_bulk};
+use snowflake_rs_impl::snowflake::{InvalidIdReason, Snowflake, SnowflakeError};
+
+const TWITTER_EPOCH: i64 = 1288834974657;
+const DEFAULT_EPOCH: i64 = 1609459200000;
+
+/// Test that migrating an ID preserves its absolute time, node and sequence
+#[test]
+fn test_migrate_epoch_round_trip() {
+    let snowflake = Snowflake::new(5, Some(TWITTER_EPOCH)).unwrap();
+    let id = snowflake.generate().unwrap();
+
+    let migrated = migrate_epoch(id, TWITTER_EPOCH, DEFAULT_EPOCH).unwrap();
+    let (timestamp, node, sequence) = Snowflake::parse_id(id);
+    let (migrated_timestamp, migrated_node, migrated_sequence) = Snowflake::parse_id(migrated);
+    assert_eq!(timestamp as i64 + TWITTER_EPOCH, migrated_timestamp as i64 + DEFAULT_EPOCH);
+    assert_eq!(node, migrated_node);
+    assert_eq!(sequence, migrated_sequence);
+
+    assert_eq!(migrate_epoch(migrated, DEFAULT_EPOCH, TWITTER_EPOCH).unwrap(), id);
+}
+
+/// Test that IDs before the target epoch or beyond the timestamp field are rejected
+#[test]
+fn test_migrate_epoch_out_of_range() {
+    // A 2015 ID cannot be expressed relative to a 2021 epoch
+    let early = migrate_epoch(1 << 22, TWITTER_EPOCH, DEFAULT_EPOCH);
+    assert!(matches!(early, Err(SnowflakeError::TimestampOutOfRange)));
+
+    let late = migrate_epoch(u64::MAX >> 1, DEFAULT_EPOCH, 0);
+    assert!(matches!(late, Err(SnowflakeError::TimestampOutOfRange)));
+}
+
+/// Test that the bulk variant migrates every ID and fails as a whole on a bad one
+#[test]
+fn test_migrate_epoch_bulk() {
+    let snowflake = Snowflake::new(1, Some(TWITTER_EPOCH)).unwrap();
+    let ids: Vec<u64> = (0..100).map(|_| snowflake.generate().unwrap()).collect();
+
+    let migrated = migrate_epoch_bulk(&ids, TWITTER_EPOCH, DEFAULT_EPOCH).unwrap();
+    assert_eq!(migrated.len(), ids.len());
+    assert!(migrated.windows(2).all(|w| w[0] < w[1]));
+
+    let mut with_bad = ids.clone();
+    with_bad.push(0);
+    assert!(migrate_epoch_bulk(&with_bad, TWITTER_EPOCH, DEFAULT_EPOCH).is_err());
+}
+
+/// Test re-packing from the default layout into a narrower-node layout and back
+#[test]
+fn test_repack_layout_round_trip() {
+    let narrow_nodes = Layout::new(8, 14).unwrap();
+    let snowflake = Snowflake::new(200, None).unwrap();
+    let ids: Vec<u64> = (0..100).map(|_| snowflake.generate().unwrap()).collect();
+
+    let repacked = repack_layout_bulk(&ids, &Layout::DEFAULT, &narrow_nodes).unwrap();
+    for (&id, &new_id) in ids.iter().zip(&repacked) {
+        assert_eq!(Layout::DEFAULT.decompose(id), narrow_nodes.decompose(new_id));
+        assert_eq!(repack_layout(new_id, &narrow_nodes, &Layout::DEFAULT).unwrap(), id);
+    }
+}
+
+/// Test that re-packing fails instead of truncating fields that do not fit
+#[test]
+fn test_repack_layout_information_loss() {
+    let narrow_nodes = Layout::new(8, 14).unwrap();
+
+    let wide_node = Layout::DEFAULT.compose(1000, 300, 0).unwrap();
+    let result = repack_layout(wide_node, &Layout::DEFAULT, &narrow_nodes);
+    assert!(matches!(result, Err(SnowflakeError::MachineIdOutOfRange)));
+
+    let high_sequence = narrow_nodes.compose(1000, 1, 5000).unwrap();
+    let result = repack_layout(high_sequence, &narrow_nodes, &Layout::DEFAULT);
+    assert!(matches!(result, Err(SnowflakeError::SequenceOutOfRange)));
+
+    let wide_nodes = Layout::new(12, 12).unwrap();
+    let late = Layout::DEFAULT.compose(Layout::DEFAULT.max_timestamp(), 1, 1).unwrap();
+    let result = repack_layout(late, &Layout::DEFAULT, &wide_nodes);
+    assert!(matches!(result, Err(SnowflakeError::TimestampOutOfRange)));
+}
+
+/// Test that second-era IDs and IDs with a bad checksum are rejected instead of being
+/// re-packed into valid-looking IDs
+#[test]
+fn test_repack_layout_rejects_era_and_corrupt_ids() {
+    let narrow_nodes = Layout::new(8, 14).unwrap();
+    let era_id = Layout::DEFAULT.compose(1000, 5, 7).unwrap() | ERA_BIT;
+    assert!(matches!(
+        repack_layout(era_id, &Layout::DEFAULT, &narrow_nodes),
+        Err(SnowflakeError::InvalidId(InvalidIdReason::ReservedBitSet))
+    ));
+
+    let checked = Layout::DEFAULT.with_check_bits(4).unwrap();
+    let id = checked.compose(1000, 5, 7).unwrap();
+    assert!(repack_layout(id, &checked, &narrow_nodes).is_ok());
+    assert!(matches!(
+        repack_layout_bulk(&[id, id ^ 1 << 20], &checked, &narrow_nodes),
+        Err(SnowflakeError::InvalidId(InvalidIdReason::ChecksumMismatch))
+    ));
+}
+
+/// Test that invalid field widths are rejected
+#[test]
+fn test_invalid_layout() {
+    assert!(matches!(Layout::new(17, 12), Err(SnowflakeError::InvalidLayout)));
+    assert!(matches!(Layout::new(10, 0), Err(SnowflakeError::InvalidLayout)));
+    assert_eq!(Layout::new(10, 12).unwrap(), Layout::DEFAULT);
+    assert_eq!(Layout::DEFAULT.timestamp_bits(), 41);
+}
@@ -0,0 +1,48 @@
+use std::sync::Arc;
+
+use snowflake_rs_impl::id::IdValidator;
+use snowflake_rs_impl::layout::Layout;
+use snowflake_rs_impl::origin::KnownNodes;
+use snowflake_rs_impl::snowflake::{Snowflake, SnowflakeError};
+
+/// Test that IDs from registered nodes pass and others are flagged with their node ID
+#[test]
+fn test_validate_origin() {
+    let mut known = KnownNodes::from_nodes([0, 63, 64, 1023]).unwrap();
+    assert_eq!(known.len(), 4);
+
+    for node in [0, 63, 64, 1023] {
+        let id = Snowflake::new(node, None).unwrap().generate().unwrap();
+        assert_eq!(known.validate_origin(id).unwrap(), node);
+    }
+
+    let id = Snowflake::new(5, None).unwrap().generate().unwrap();
+    assert!(matches!(known.validate_origin(id), Err(SnowflakeError::UnknownNode(5))));
+
+    assert!(known.remove(63));
+    assert!(!known.contains(63));
+    assert!(matches!(known.insert(1024), Err(SnowflakeError::MachineIdOutOfRange)));
+}
+
+/// Test that node IDs are decoded with the set's layout
+#[test]
+fn test_validate_origin_custom_layout() {
+    let layout = Layout::new(8, 14).unwrap();
+    let mut known = KnownNodes::new(layout);
+    known.insert(200).unwrap();
+
+    let id = layout.compose(1000, 200, 9000).unwrap();
+    assert_eq!(known.validate_origin(id).unwrap(), 200);
+    assert!(known.validate_origin(Layout::DEFAULT.compose(1000, 200, 1).unwrap()).is_err());
+    assert!(matches!(known.insert(256), Err(SnowflakeError::MachineIdOutOfRange)));
+}
+
+/// Test that an IdValidator with a known-node set rejects IDs from unknown nodes
+#[test]
+fn test_validator_known_nodes() {
+    let validator = IdValidator::new().known_nodes(Arc::new(KnownNodes::from_nodes([1, 2]).unwrap()));
+    let known = Snowflake::new(2, None).unwrap().generate().unwrap();
+    assert_eq!(validator.validate(known).unwrap().as_u64(), known);
+    let rogue = Snowflake::new(3, None).unwrap().generate().unwrap();
+    assert!(matches!(validator.validate(rogue), Err(SnowflakeError::UnknownNode(3))));
+}
@@ -0,0 +1,42 @@
+#![cfg(feature = "rayon")]
+
+use std::collections::HashSet;
+
+use snowflake_rs_impl::rate_limit::RateLimit;
+use snowflake_rs_impl::snowflake::{Snowflake, SnowflakeError};
+
+/// Test that parallel generation returns the requested number of unique, sorted IDs
+#[test]
+fn test_generate_parallel() {
+    let snowflake = Snowflake::new(1, None).unwrap();
+    let ids = snowflake.generate_parallel(100_000).unwrap();
+
+    assert_eq!(ids.len(), 100_000);
+    assert!(ids.windows(2).all(|w| w[0] < w[1]));
+    assert!(ids.iter().all(|id| id.node() == 1));
+}
+
+/// Test that parallel generation does not collide with regular generation
+#[test]
+fn test_generate_parallel_interleaved() {
+    let snowflake = Snowflake::new(1, None).unwrap();
+    let mut ids = HashSet::new();
+    for _ in 0..10 {
+        ids.insert(snowflake.generate_id().unwrap());
+        ids.extend(snowflake.generate_parallel(5000).unwrap());
+    }
+    assert_eq!(ids.len(), 10 * 5001);
+    assert!(snowflake.generate_parallel(0).unwrap().is_empty());
+}
+
+/// Test that chunks fit in the burst of a rate-limited generator
+#[test]
+fn test_generate_parallel_rate_limited() {
+    let snowflake = Snowflake::builder(1)
+        .rate_limit(RateLimit::per_second(1).burst(100))
+        .build()
+        .unwrap();
+    assert_eq!(snowflake.generate_parallel(10).unwrap().len(), 10);
+    assert_eq!(snowflake.generate_parallel(90).unwrap().len(), 90);
+    assert!(matches!(snowflake.generate_parallel(10), Err(SnowflakeError::Throttled)));
+}
@@ -0,0 +1,88 @@
+use std::path::PathBuf;
+
+use snowflake_rs_impl::layout::Layout;
+use snowflake_rs_impl::persist::StateFile;
+use snowflake_rs_impl::snowflake::{Snowflake, SnowflakeError};
+
+// Returns a fresh state file path for a test
+fn state_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("snowflake-{}-{}.state", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+/// Test that dropping a generator records a clean shutdown that the next startup resumes from
+#[test]
+fn test_persist_on_drop() {
+    let path = state_path("drop");
+    let last_id = {
+        let snowflake = Snowflake::builder(3).persist_on_drop(StateFile::new(&path)).build().unwrap();
+        assert!(snowflake.previous_state().is_none());
+        *snowflake.generate_batch(8000).unwrap().last().unwrap()
+    };
+
+    let snowflake = Snowflake::builder(3).persist_on_drop(StateFile::new(&path)).build().unwrap();
+    let previous = snowflake.previous_state().unwrap();
+    assert!(previous.clean_shutdown);
+    assert_eq!(previous.snapshot.node, 3);
+    assert!(snowflake.generate_id().unwrap() > last_id);
+    snowflake.close().unwrap();
+    drop(snowflake);
+    std::fs::remove_file(&path).unwrap();
+}
+
+/// Test that a generator that never shut down is reported as a crash
+#[test]
+fn test_persist_detects_crash() {
+    let path = state_path("crash");
+    let snowflake = Snowflake::builder(4).persist_on_drop(StateFile::new(&path)).build().unwrap();
+    snowflake.generate().unwrap();
+    // Simulate a crash: the generator is never dropped or closed
+    std::mem::forget(snowflake);
+
+    let state = StateFile::new(&path).load().unwrap().unwrap();
+    assert!(!state.clean_shutdown);
+
+    let restarted = Snowflake::builder(4).persist_on_drop(StateFile::new(&path)).build().unwrap();
+    assert_eq!(restarted.previous_state().map(|s| s.clean_shutdown), Some(false));
+    restarted.close().unwrap();
+    assert!(StateFile::new(&path).load().unwrap().unwrap().clean_shutdown);
+    drop(restarted);
+    std::fs::remove_file(&path).unwrap();
+}
+
+/// Test that a state file from another node is rejected
+#[test]
+fn test_persist_node_mismatch() {
+    let path = state_path("mismatch");
+    drop(Snowflake::builder(5).persist_on_drop(StateFile::new(&path)).build().unwrap());
+
+    let result = Snowflake::builder(6).persist_on_drop(StateFile::new(&path)).build();
+    assert!(matches!(result, Err(SnowflakeError::StateStore(_))));
+    std::fs::remove_file(&path).unwrap();
+}
+
+/// Test that the check field of the layout is persisted and mismatches are rejected
+#[test]
+fn test_persist_check_bits() {
+    let path = state_path("check-bits");
+    let layout = Layout::new(6, 12).unwrap().with_check_bits(4).unwrap();
+    let last_id = {
+        let snowflake = Snowflake::builder(7).layout(layout).persist_on_drop(StateFile::new(&path)).build().unwrap();
+        snowflake.generate_id().unwrap()
+    };
+    assert!(std::fs::read_to_string(&path).unwrap().contains("check_bits=4\n"));
+
+    let result = Snowflake::builder(7)
+        .layout(Layout::new(6, 12).unwrap())
+        .persist_on_drop(StateFile::new(&path))
+        .build();
+    assert!(matches!(result, Err(SnowflakeError::StateStore(_))));
+
+    let snowflake = Snowflake::builder(7).layout(layout).persist_on_drop(StateFile::new(&path)).build().unwrap();
+    assert_eq!(snowflake.previous_state().unwrap().snapshot.layout, layout);
+    assert!(snowflake.generate_id().unwrap() > last_id);
+    snowflake.close().unwrap();
+    drop(snowflake);
+    std::fs::remove_file(&path).unwrap();
+}
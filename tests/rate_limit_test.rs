@@ -0,0 +1,91 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use snowflake_rs_impl::clock::Clock;
+use snowflake_rs_impl::rate_limit::{RateLimit, ThrottleMode};
+use snowflake_rs_impl::snowflake::{Snowflake, SnowflakeError};
+
+/// Test that a generator in error mode is throttled once its burst is used up
+#[test]
+fn test_rate_limit_throttled() {
+    let snowflake = Snowflake::builder(1)
+        .rate_limit(RateLimit::per_second(10).burst(5))
+        .build()
+        .unwrap();
+
+    for _ in 0..5 {
+        snowflake.generate().unwrap();
+    }
+    assert!(matches!(snowflake.generate(), Err(SnowflakeError::Throttled)));
+
+    // One token refills after 100ms at 10 IDs per second
+    std::thread::sleep(Duration::from_millis(120));
+    assert!(snowflake.generate().is_ok());
+}
+
+/// Test that a generator in wait mode blocks instead of failing
+#[test]
+fn test_rate_limit_wait() {
+    let snowflake = Snowflake::builder(1)
+        .rate_limit(RateLimit::per_second(100).burst(1).mode(ThrottleMode::Wait))
+        .build()
+        .unwrap();
+
+    let start = Instant::now();
+    for _ in 0..11 {
+        snowflake.generate().unwrap();
+    }
+    // The first ID uses the burst token, the remaining 10 take ~10ms each
+    assert!(start.elapsed() >= Duration::from_millis(90));
+}
+
+// Clock that fails to read while `broken` is set
+struct BreakableClock {
+    broken: AtomicBool,
+}
+
+impl Clock for BreakableClock {
+    fn now_millis(&self) -> i64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as i64
+    }
+
+    fn try_now_millis(&self) -> Result<i64, SnowflakeError> {
+        if self.broken.load(Ordering::SeqCst) {
+            return Err(SnowflakeError::ClockUnavailable("broken".to_string()));
+        }
+        Ok(self.now_millis())
+    }
+}
+
+/// Test that a failed batch leaves the bucket untouched
+#[test]
+fn test_rate_limit_failed_batch() {
+    let clock = Arc::new(BreakableClock {
+        broken: AtomicBool::new(false),
+    });
+    let snowflake = Snowflake::builder(1)
+        .rate_limit(RateLimit::per_second(1).burst(5))
+        .clock(clock.clone())
+        .build()
+        .unwrap();
+
+    assert!(matches!(snowflake.generate_batch(6), Err(SnowflakeError::BatchExceedsBurst)));
+    snowflake.generate_batch(2).unwrap();
+    assert!(matches!(snowflake.generate_batch(4), Err(SnowflakeError::Throttled)));
+
+    clock.broken.store(true, Ordering::SeqCst);
+    assert!(matches!(snowflake.generate_batch(3), Err(SnowflakeError::ClockUnavailable(_))));
+    clock.broken.store(false, Ordering::SeqCst);
+
+    assert_eq!(snowflake.generate_batch(3).unwrap().len(), 3);
+    assert!(matches!(snowflake.generate(), Err(SnowflakeError::Throttled)));
+}
+
+/// Test that an invalid rate limit is rejected at build time
+#[test]
+fn test_invalid_rate_limit() {
+    let result = Snowflake::builder(1).rate_limit(RateLimit::per_second(0)).build();
+    assert!(matches!(result, Err(SnowflakeError::InvalidRateLimit)));
+    assert!(Snowflake::new(1, None).unwrap().rate_limit().is_none());
+}
@@ -0,0 +1,48 @@
+use snowflake_rs_impl::id::SnowflakeId;
+use snowflake_rs_impl::redact::{Redacted, RedactionKey};
+use snowflake_rs_impl::snowflake::{Snowflake, SnowflakeError};
+
+/// Test the digest against the SipHash-2-4 reference vector (key 00..0f, message 00..07)
+#[test]
+fn test_reference_vector() {
+    let key: [u8; 16] = std::array::from_fn(|i| i as u8);
+    let id = SnowflakeId::from_u64(u64::from_le_bytes([0, 1, 2, 3, 4, 5, 6, 7]));
+    let redacted = Redacted::new(id, &RedactionKey::new(key));
+    assert_eq!(redacted.digest(), 0x93f5_f579_9a93_2462);
+    assert_eq!(redacted.to_string(), "~93f5f5799a93");
+    assert_eq!(format!("{:?}", redacted), "~93f5f5799a93");
+}
+
+/// Test that redaction is stable per key and hides the raw ID
+#[test]
+fn test_redaction_is_keyed_and_stable() {
+    let snowflake = Snowflake::new(1, None).unwrap();
+    let id = snowflake.generate_id().unwrap();
+    let key = RedactionKey::new(*b"0123456789abcdef");
+    let other_key = RedactionKey::new(*b"fedcba9876543210");
+
+    assert_eq!(id.redacted_with(&key), id.redacted_with(&RedactionKey::new(*b"0123456789abcdef")));
+    assert_ne!(id.redacted_with(&key), id.redacted_with(&other_key));
+    let next = snowflake.generate_id().unwrap();
+    assert_ne!(id.redacted_with(&key), next.redacted_with(&key));
+
+    let rendered = id.redacted_with(&key).to_string();
+    assert_eq!(rendered.len(), 13);
+    assert!(!rendered.contains(&id.to_string()));
+    // The key itself never shows up in debug output
+    assert_eq!(format!("{:?}", key), "RedactionKey { .. }");
+}
+
+/// Test the global key: once picked it is fixed, and cannot be replaced
+#[test]
+fn test_global_key() {
+    let id = SnowflakeId::from_u64(42);
+    let first = id.redacted();
+    assert_eq!(first, id.redacted());
+    assert_eq!(first, id.redacted_with(&RedactionKey::global()));
+    assert!(matches!(
+        RedactionKey::random().install(),
+        Err(SnowflakeError::RedactionKeyAlreadySet)
+    ));
+    assert_eq!(id.redacted(), first);
+}
@@ -0,0 +1,83 @@
+use std::sync::Arc;
+
+use snowflake_rs_impl::layout::Layout;
+use snowflake_rs_impl::region::RegionRegistry;
+use snowflake_rs_impl::snowflake::{Snowflake, SnowflakeError};
+
+// Registry with 2 region bits over the default layout
+fn regions() -> Arc<RegionRegistry> {
+    let mut regions = RegionRegistry::new(Layout::DEFAULT, 2).unwrap();
+    regions.register("us-east", 0).unwrap();
+    regions.register("eu-west", 1).unwrap();
+    regions.register("ap-south", 3).unwrap();
+    Arc::new(regions)
+}
+
+/// Test that IDs carry the region bits and parse back to the region name
+#[test]
+fn test_region_in_ids() {
+    let regions = regions();
+    let snowflake = Snowflake::builder(200).region(Arc::clone(&regions), "ap-south").build().unwrap();
+    assert_eq!(snowflake.node(), (3 << 8) | 200);
+    assert_eq!(snowflake.region(), Some("ap-south"));
+
+    let id = snowflake.generate().unwrap();
+    assert_eq!(regions.region_of(id), Some("ap-south"));
+    assert_eq!(regions.region_value(id), 3);
+    assert_eq!(regions.local_node(id), 200);
+    assert_eq!(Snowflake::parse_id(id).1, snowflake.node());
+
+    // A region value without a name parses as None
+    let unnamed = Snowflake::new(2 << 8, None).unwrap().generate().unwrap();
+    assert_eq!(regions.region_of(unnamed), None);
+}
+
+/// Test the registry bookkeeping and its validation
+#[test]
+fn test_registry() {
+    let regions = regions();
+    assert_eq!(regions.max_region(), 3);
+    assert_eq!(regions.max_local_node(), 255);
+    assert_eq!(regions.value("eu-west"), Some(1));
+    assert_eq!(regions.name(0), Some("us-east"));
+    assert_eq!(regions.split_node((1 << 8) | 9), (1, 9));
+    assert_eq!(
+        regions.regions().collect::<Vec<_>>(),
+        [("ap-south", 3), ("eu-west", 1), ("us-east", 0)]
+    );
+
+    let mut regions = (*regions).clone();
+    assert!(matches!(regions.register("us-east", 2), Err(SnowflakeError::InvalidRegionConfig)));
+    assert!(matches!(regions.register("us-west", 0), Err(SnowflakeError::InvalidRegionConfig)));
+    assert!(matches!(regions.register("us-west", 4), Err(SnowflakeError::InvalidRegionConfig)));
+    assert!(matches!(RegionRegistry::new(Layout::DEFAULT, 0), Err(SnowflakeError::InvalidRegionConfig)));
+    assert!(matches!(RegionRegistry::new(Layout::DEFAULT, 10), Err(SnowflakeError::InvalidRegionConfig)));
+}
+
+/// Test the builder's region checks
+#[test]
+fn test_builder_errors() {
+    let regions = regions();
+    assert!(matches!(
+        Snowflake::builder(1).region(Arc::clone(&regions), "mars").build(),
+        Err(SnowflakeError::UnknownRegion(region)) if region == "mars"
+    ));
+    assert!(matches!(
+        Snowflake::builder(256).region(Arc::clone(&regions), "us-east").build(),
+        Err(SnowflakeError::MachineIdOutOfRange)
+    ));
+    assert!(matches!(
+        Snowflake::builder(1).layout(Layout::new(12, 10).unwrap()).region(regions, "us-east").build(),
+        Err(SnowflakeError::InvalidRegionConfig)
+    ));
+}
+
+/// Test that forks keep the registry and take a full node ID
+#[test]
+fn test_fork_keeps_regions() {
+    let snowflake = Snowflake::builder(1).region(regions(), "eu-west").build().unwrap();
+    let fork = snowflake.fork((3 << 8) | 5).unwrap();
+    assert_eq!(fork.region(), Some("ap-south"));
+    assert!(fork.regions().is_some());
+    assert_eq!(Snowflake::new(1, None).unwrap().region(), None);
+}
@@ -0,0 +1,51 @@
+use std::sync::Arc;
+use std::thread;
+
+use snowflake_rs_impl::registry::GeneratorRegistry;
+use snowflake_rs_impl::snowflake::{Snowflake, SnowflakeError};
+
+/// Test that names are created lazily with the registry defaults and reused afterwards
+#[test]
+fn test_registry_lazy_defaults() {
+    let registry = GeneratorRegistry::new(3, Some(1672531200000)).unwrap();
+    assert!(registry.is_empty());
+
+    let first = registry.get("tenant-a").unwrap();
+    let second = registry.get("tenant-a").unwrap();
+    assert!(Arc::ptr_eq(&first, &second));
+    assert_eq!(first.node(), 3);
+    assert_eq!(first.epoch(), 1672531200000);
+    assert_eq!(registry.len(), 1);
+}
+
+/// Test that per-name configuration overrides the defaults
+#[test]
+fn test_registry_configure() {
+    let registry = GeneratorRegistry::new(1, None).unwrap();
+    registry.configure("tenant-b", Some(7), None).unwrap();
+
+    let id = registry.generate("tenant-b").unwrap();
+    let (_, node, _) = Snowflake::parse_id(id);
+    assert_eq!(node, 7);
+
+    let reconfigured = registry.configure("tenant-b", Some(8), None);
+    assert!(matches!(reconfigured, Err(SnowflakeError::GeneratorAlreadyInitialized)));
+    assert!(matches!(
+        registry.configure("tenant-c", Some(1024), None),
+        Err(SnowflakeError::MachineIdOutOfRange)
+    ));
+}
+
+/// Test that concurrent first use of a name creates a single generator
+#[test]
+fn test_registry_concurrent_get() {
+    let registry = Arc::new(GeneratorRegistry::new(1, None).unwrap());
+    let handles: Vec<_> = (0..8)
+        .map(|_| {
+            let registry = Arc::clone(&registry);
+            thread::spawn(move || registry.get("shared").unwrap())
+        })
+        .collect();
+    let generators: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+    assert!(generators.iter().all(|g| Arc::ptr_eq(g, &generators[0])));
+}
@@ -0,0 +1,174 @@
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use snowflake_rs_impl::generator::IdGenerator;
+use snowflake_rs_impl::id::SnowflakeId;
+use snowflake_rs_impl::remote::{IdBlockSource, RemoteSnowflakeClient};
+use snowflake_rs_impl::snowflake::{Snowflake, SnowflakeError};
+
+// Stand-in for an ID service that can be taken down, counting the blocks it serves
+struct TestServer {
+    generator: Snowflake,
+    up: AtomicBool,
+    blocks: AtomicUsize,
+}
+
+impl TestServer {
+    fn new(node: u16) -> Arc<Self> {
+        Arc::new(TestServer {
+            generator: Snowflake::new(node, None).unwrap(),
+            up: AtomicBool::new(true),
+            blocks: AtomicUsize::new(0),
+        })
+    }
+}
+
+impl IdBlockSource for TestServer {
+    fn fetch_block(&self, count: usize) -> Result<Vec<SnowflakeId>, SnowflakeError> {
+        if !self.up.load(Ordering::SeqCst) {
+            return Err(SnowflakeError::RemoteUnavailable("connection refused".to_string()));
+        }
+        self.blocks.fetch_add(1, Ordering::SeqCst);
+        self.generator.generate_batch(count)
+    }
+}
+
+// Polls `condition` for at most 5 seconds
+fn eventually(condition: impl Fn() -> bool) -> bool {
+    let start = Instant::now();
+    while start.elapsed() < Duration::from_secs(5) {
+        if condition() {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(1));
+    }
+    false
+}
+
+/// Test that IDs come from prefetched server blocks, refilled in the background
+#[test]
+fn test_serves_prefetched_blocks() {
+    let server = TestServer::new(5);
+    let client = RemoteSnowflakeClient::builder(server.clone()).block_size(100).low_water(20).build().unwrap();
+    assert_eq!(server.blocks.load(Ordering::SeqCst), 1);
+    assert_eq!(client.buffered(), 100);
+
+    let mut ids = HashSet::new();
+    for _ in 0..1000 {
+        let id = match client.next_id() {
+            Ok(id) => id,
+            // The background refill may lag behind this tight loop
+            Err(SnowflakeError::RemoteUnavailable(_)) => {
+                assert!(eventually(|| client.buffered() > 0));
+                client.next_id().unwrap()
+            }
+            Err(err) => panic!("unexpected error {}", err),
+        };
+        assert_eq!(id.node(), 5);
+        assert!(ids.insert(id));
+    }
+    assert!(server.blocks.load(Ordering::SeqCst) >= 10);
+    assert_eq!(client.fallback_ids(), 0);
+    assert_eq!(client.last_error(), None);
+}
+
+/// Test that the fallback generator takes over while the server is down, and the
+/// client goes back to server IDs once it is reachable again
+#[test]
+fn test_falls_back_while_server_is_down() {
+    let server = TestServer::new(5);
+    server.up.store(false, Ordering::SeqCst);
+    let client = RemoteSnowflakeClient::builder(server.clone())
+        .block_size(10)
+        .low_water(2)
+        .retry_interval(Duration::from_millis(10))
+        .fallback(Snowflake::new(1023, None).unwrap())
+        .build()
+        .unwrap();
+    assert_eq!(client.next_id().unwrap().node(), 1023);
+    assert_eq!(client.fallback_ids(), 1);
+    assert!(client.last_error().unwrap().contains("connection refused"));
+
+    server.up.store(true, Ordering::SeqCst);
+    assert!(eventually(|| client.buffered() > 0));
+    assert_eq!(client.next_id().unwrap().node(), 5);
+    assert_eq!(client.last_error(), None);
+}
+
+/// Test that without a fallback, an unreachable server fails the build or the call
+#[test]
+fn test_unreachable_without_fallback() {
+    let server = TestServer::new(5);
+    server.up.store(false, Ordering::SeqCst);
+    assert!(matches!(
+        RemoteSnowflakeClient::builder(server.clone()).build(),
+        Err(SnowflakeError::RemoteUnavailable(_))
+    ));
+
+    server.up.store(true, Ordering::SeqCst);
+    let client = RemoteSnowflakeClient::builder(server.clone()).block_size(3).low_water(0).build().unwrap();
+    server.up.store(false, Ordering::SeqCst);
+    let served = (0..10).take_while(|_| client.next_id().is_ok()).count();
+    assert_eq!(served, 3);
+    match client.next_id() {
+        Err(SnowflakeError::RemoteUnavailable(reason)) => assert!(!reason.is_empty()),
+        other => panic!("expected RemoteUnavailable, got {:?}", other),
+    }
+}
+
+/// Test that blocks holding IDs of the fallback's node are rejected
+#[test]
+fn test_rejects_blocks_of_fallback_node() {
+    let server = TestServer::new(7);
+    let client = RemoteSnowflakeClient::builder(server)
+        .block_size(10)
+        .low_water(2)
+        .fallback(Snowflake::new(7, None).unwrap())
+        .build()
+        .unwrap();
+    assert_eq!(client.buffered(), 0);
+    assert!(client.last_error().unwrap().contains("fallback node 7"));
+}
+
+/// Test that an empty block counts as a failed fetch and is retried after the interval
+#[test]
+fn test_empty_block_is_retried_later() {
+    let fetches = Arc::new(AtomicUsize::new(0));
+    let source: Arc<dyn IdBlockSource> = {
+        let fetches = fetches.clone();
+        Arc::new(move |_| {
+            fetches.fetch_add(1, Ordering::SeqCst);
+            Ok(Vec::new())
+        })
+    };
+    let client = RemoteSnowflakeClient::builder(source)
+        .block_size(10)
+        .low_water(2)
+        .retry_interval(Duration::from_millis(50))
+        .fallback(Snowflake::new(1023, None).unwrap())
+        .build()
+        .unwrap();
+    assert!(client.last_error().unwrap().contains("empty block"));
+    thread::sleep(Duration::from_millis(120));
+    assert!(fetches.load(Ordering::SeqCst) <= 4);
+    assert_eq!(client.next_id().unwrap().node(), 1023);
+}
+
+/// Test that closures act as block sources and invalid prefetch settings are rejected
+#[test]
+fn test_closure_source_and_config() {
+    let server = Snowflake::new(2, None).unwrap();
+    let source: Arc<dyn IdBlockSource> = Arc::new(move |count| server.generate_batch(count));
+    let client = RemoteSnowflakeClient::builder(source.clone()).block_size(50).low_water(10).build().unwrap();
+    assert_eq!(client.next_id().unwrap().node(), 2);
+
+    for (block_size, low_water) in [(0, 0), (10, 10), (10, 20)] {
+        assert!(matches!(
+            RemoteSnowflakeClient::builder(source.clone()).block_size(block_size).low_water(low_water).build(),
+            Err(SnowflakeError::InvalidPrefetchConfig)
+        ));
+    }
+}
@@ -0,0 +1,49 @@
+#![cfg(feature = "scylla")]
+
+use scylla_cql::frame::response::cql_to_rust::{FromCqlVal, FromCqlValError};
+use scylla_cql::frame::response::result::{ColumnType, CqlValue};
+use scylla_cql::types::serialize::value::SerializeValue;
+use scylla_cql::types::serialize::writers::CellWriter;
+use snowflake_rs_impl::exhaustion::ERA_BIT;
+use snowflake_rs_impl::id::SnowflakeId;
+use snowflake_rs_impl::snowflake::Snowflake;
+
+// Serializes a value for a column of type `typ`, returning the cell (length prefix and bytes)
+fn serialize(value: &impl SerializeValue, typ: &ColumnType) -> Option<Vec<u8>> {
+    let mut cell = Vec::new();
+    value.serialize(typ, CellWriter::new(&mut cell)).ok()?;
+    Some(cell)
+}
+
+/// Test that an ID is bound exactly like the same value as an i64 bigint
+#[test]
+fn test_serialize_as_bigint() {
+    let id = Snowflake::new(1, None).unwrap().generate_id().unwrap();
+    let cell = serialize(&id, &ColumnType::BigInt).unwrap();
+    assert_eq!(cell, serialize(&(id.as_u64() as i64), &ColumnType::BigInt).unwrap());
+    assert_eq!(&cell[4..], id.as_u64().to_be_bytes());
+}
+
+/// Test that IDs with the top bit set and non-bigint columns are rejected
+#[test]
+fn test_serialize_rejects_invalid() {
+    let second_era = SnowflakeId::from_u64(ERA_BIT | 1);
+    assert!(serialize(&second_era, &ColumnType::BigInt).is_none());
+    let id = SnowflakeId::from_u64(1266);
+    assert!(serialize(&id, &ColumnType::Int).is_none());
+    assert!(serialize(&id, &ColumnType::Text).is_none());
+}
+
+/// Test that IDs read back from bigint values, including nullable columns
+#[test]
+fn test_from_cql() {
+    let id = Snowflake::new(1, None).unwrap().generate_id().unwrap();
+    let value = CqlValue::BigInt(id.as_u64() as i64);
+    assert_eq!(SnowflakeId::from_cql(value.clone()).unwrap(), id);
+    assert_eq!(<Option<SnowflakeId>>::from_cql(Some(value)).unwrap(), Some(id));
+    assert_eq!(<Option<SnowflakeId>>::from_cql(None).unwrap(), None);
+    assert_eq!(<SnowflakeId as FromCqlVal<Option<CqlValue>>>::from_cql(None), Err(FromCqlValError::ValIsNull));
+
+    assert_eq!(SnowflakeId::from_cql(CqlValue::BigInt(-1)), Err(FromCqlValError::BadVal));
+    assert_eq!(SnowflakeId::from_cql(CqlValue::Int(1)), Err(FromCqlValError::BadCqlType));
+}
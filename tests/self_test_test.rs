@@ -0,0 +1,114 @@
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use snowflake_rs_impl::clock::Clock;
+use snowflake_rs_impl::rate_limit::RateLimit;
+use snowflake_rs_impl::snowflake::Snowflake;
+
+/// Test that a healthy generator passes the self-test
+#[test]
+fn test_self_test_healthy() {
+    let snowflake = Snowflake::new(1, None).unwrap();
+    let report = snowflake.self_test().unwrap();
+    assert!(report.monotonic);
+    assert!(report.ids_checked > 1);
+    assert_eq!(report.burst_size, 4 * 4096);
+    assert!(report.throughput_per_sec > 0.0);
+    assert!(report.clock_resolution.unwrap() >= Duration::from_millis(1));
+
+    // Generation continues after the self-test
+    let id = snowflake.generate().unwrap();
+    assert!(id > 0);
+}
+
+/// Test that the self-test neither consumes nor is blocked by the rate limit
+#[test]
+fn test_self_test_ignores_rate_limit() {
+    let snowflake = Snowflake::builder(1).rate_limit(RateLimit::per_second(10)).build().unwrap();
+    let report = snowflake.self_test().unwrap();
+    assert_eq!(report.burst_size, 4 * 4096);
+    for _ in 0..10 {
+        snowflake.generate().unwrap();
+    }
+}
+
+// Clock driven by its number of readings: it ticks by `tick_ms` every 100 readings and
+// steps 10 ms back from reading `step_back_at` on
+struct ReadingClock {
+    readings: AtomicI64,
+    tick_ms: i64,
+    step_back_at: i64,
+}
+
+impl ReadingClock {
+    fn new(tick_ms: i64, step_back_at: i64) -> Self {
+        ReadingClock {
+            readings: AtomicI64::new(0),
+            tick_ms,
+            step_back_at,
+        }
+    }
+}
+
+impl Clock for ReadingClock {
+    fn now_millis(&self) -> i64 {
+        let reading = self.readings.fetch_add(1, Ordering::SeqCst);
+        let now = 1_700_000_000_000 + reading / 100 * self.tick_ms;
+        if reading >= self.step_back_at {
+            now - 10
+        } else {
+            now
+        }
+    }
+}
+
+/// Test that a coarse clock and a clock rollback are reported as warnings
+#[test]
+fn test_self_test_reports_problems() {
+    let clock = Arc::new(ReadingClock::new(5, i64::MAX));
+    let report = Snowflake::builder(1).clock(clock).build().unwrap().self_test().unwrap();
+    assert!(report.monotonic);
+    assert_eq!(report.clock_resolution, Some(Duration::from_millis(5)));
+    assert!(!report.is_ok());
+    assert!(report.warnings[0].contains("clock resolution"), "{:?}", report.warnings);
+
+    // Resolution measurement takes about 500 readings, so the step lands in the ID window
+    let clock = Arc::new(ReadingClock::new(1, 1000));
+    let report = Snowflake::builder(1).clock(clock).build().unwrap().self_test().unwrap();
+    assert!(!report.monotonic);
+    assert_eq!(report.warnings, ["IDs or clock readings went backwards"]);
+}
+
+// Clock that advances 1 ms per reading, takes 5 ms per reading for the first `slow_readings`
+// readings and steps 10 ms back from then on
+struct SlowStartClock {
+    readings: AtomicI64,
+    slow_readings: i64,
+}
+
+impl Clock for SlowStartClock {
+    fn now_millis(&self) -> i64 {
+        let reading = self.readings.fetch_add(1, Ordering::SeqCst);
+        if reading < self.slow_readings {
+            std::thread::sleep(Duration::from_millis(5));
+            1_700_000_000_000 + reading
+        } else {
+            1_700_000_000_000 + reading - 10
+        }
+    }
+}
+
+/// Test that a clock rollback during the burst is reported like one in the ID window
+#[test]
+fn test_self_test_reports_rollback_in_burst() {
+    // The slow readings take the resolution measurement and the ID window to about 20
+    // readings, so the step lands in the burst
+    let clock = Arc::new(SlowStartClock {
+        readings: AtomicI64::new(0),
+        slow_readings: 40,
+    });
+    let report = Snowflake::builder(1).clock(clock).build().unwrap().self_test().unwrap();
+    assert!(!report.monotonic);
+    assert_eq!(report.warnings, ["IDs or clock readings went backwards"]);
+}
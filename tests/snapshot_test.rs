@@ -0,0 +1,145 @@
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use snowflake_rs_impl::clock::Clock;
+use snowflake_rs_impl::layout::Layout;
+use snowflake_rs_impl::rate_limit::{RateLimit, ThrottleMode};
+use snowflake_rs_impl::snowflake::{GeneratorSnapshot, GeneratorState, GeneratorStatus, Snowflake, SnowflakeError};
+
+/// Test that a restored generator continues after the last issued ID
+#[test]
+fn test_snapshot_restore() {
+    let snowflake = Snowflake::new(9, Some(1672531200000)).unwrap();
+    let ids = snowflake.generate_batch(5000).unwrap();
+    let snapshot = snowflake.snapshot();
+    assert_eq!(snapshot.node, 9);
+    assert_eq!(snapshot.epoch, 1672531200000);
+
+    let json = serde_json::to_string(&snapshot).unwrap();
+    let restored: GeneratorSnapshot = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored, snapshot);
+
+    let resumed = Snowflake::from_snapshot(&restored).unwrap();
+    assert_eq!(resumed.snapshot(), snapshot);
+    let next = resumed.generate_id().unwrap();
+    assert!(next > *ids.last().unwrap());
+}
+
+/// Test that a snapshot from the future makes generation fail instead of reusing IDs
+#[test]
+fn test_snapshot_from_future() {
+    let snowflake = Snowflake::new(1, None).unwrap();
+    let now = snowflake.generate().unwrap() >> 22;
+    let mut snapshot = snowflake.snapshot();
+    snapshot.last_timestamp = snapshot.epoch + now as i64 + 3_600_000;
+    let resumed = Snowflake::from_snapshot(&snapshot).unwrap();
+    assert!(matches!(resumed.generate(), Err(SnowflakeError::ClockMovedBackwards)));
+
+    snapshot.last_timestamp = i64::MAX >> 13;
+    assert!(matches!(Snowflake::from_snapshot(&snapshot), Err(SnowflakeError::TimestampOutOfRange)));
+
+    snapshot.last_sequence = 4096;
+    assert!(matches!(Snowflake::from_snapshot(&snapshot), Err(SnowflakeError::SequenceOutOfRange)));
+}
+
+// Clock whose reading is set by the test
+struct SetClock(AtomicI64);
+
+impl Clock for SetClock {
+    fn now_millis(&self) -> i64 {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+// Polls `snowflake.state()` until it reports `status`, for at most 5 seconds
+fn wait_for_status(snowflake: &Snowflake, status: GeneratorStatus) -> bool {
+    let start = Instant::now();
+    while start.elapsed() < Duration::from_secs(5) {
+        if snowflake.state().status == status {
+            return true;
+        }
+        thread::yield_now();
+    }
+    false
+}
+
+/// Test that the state reports the last timestamp, sequence position and saturation
+#[test]
+fn test_state_tracks_sequence() {
+    let clock = Arc::new(SetClock(AtomicI64::new(1700000000000)));
+    let snowflake = Snowflake::builder(1).clock(clock.clone()).build().unwrap();
+    let state = snowflake.state();
+    assert_eq!(state.last_timestamp, 0);
+    assert_eq!(state.saturation(), 0.0);
+    assert_eq!(state.status, GeneratorStatus::Ready);
+
+    snowflake.generate_batch(1024).unwrap();
+    let state = snowflake.state();
+    assert_eq!(state.last_timestamp, 1700000000000);
+    assert_eq!(state.sequence, 1023);
+    assert_eq!(state.max_sequence, 4095);
+    assert_eq!(state.saturation(), 0.25);
+    assert_eq!(state.status, GeneratorStatus::Ready);
+
+    let json = serde_json::to_string(&state).unwrap();
+    assert_eq!(serde_json::from_str::<GeneratorState>(&json).unwrap(), state);
+}
+
+/// Test that a generator whose clock is behind its last timestamp is reported as suspended
+#[test]
+fn test_state_suspended() {
+    let clock = Arc::new(SetClock(AtomicI64::new(1700000000000)));
+    let snowflake = Snowflake::builder(1).clock(clock.clone()).build().unwrap();
+    snowflake.generate().unwrap();
+    clock.0.store(1699999999990, Ordering::SeqCst);
+    assert_eq!(snowflake.state().status, GeneratorStatus::Suspended);
+    assert!(matches!(snowflake.generate(), Err(SnowflakeError::ClockMovedBackwards)));
+
+    clock.0.store(1700000000000, Ordering::SeqCst);
+    assert_eq!(snowflake.state().status, GeneratorStatus::Ready);
+}
+
+/// Test that callers waiting for the next millisecond are reported while they wait
+#[test]
+fn test_state_waiting_for_next_millis() {
+    let clock = Arc::new(SetClock(AtomicI64::new(1700000000000)));
+    let snowflake = Arc::new(
+        Snowflake::builder(1)
+            .layout(Layout::new(10, 2).unwrap())
+            .clock(clock.clone())
+            .build()
+            .unwrap(),
+    );
+    snowflake.generate_batch(4).unwrap();
+    assert_eq!(snowflake.state().saturation(), 1.0);
+
+    let waiter = {
+        let snowflake = Arc::clone(&snowflake);
+        thread::spawn(move || snowflake.generate())
+    };
+    assert!(wait_for_status(&snowflake, GeneratorStatus::WaitingForNextMillis));
+    clock.0.store(1700000000001, Ordering::SeqCst);
+    waiter.join().unwrap().unwrap();
+
+    let state = snowflake.state();
+    assert_eq!(state.status, GeneratorStatus::Ready);
+    assert_eq!((state.last_timestamp, state.sequence), (1700000000001, 0));
+}
+
+/// Test that callers waiting for the rate limit are reported while they wait
+#[test]
+fn test_state_throttled() {
+    let limit = RateLimit::per_second(2).burst(1).mode(ThrottleMode::Wait);
+    let snowflake = Arc::new(Snowflake::builder(1).rate_limit(limit).build().unwrap());
+    snowflake.generate().unwrap();
+
+    let waiter = {
+        let snowflake = Arc::clone(&snowflake);
+        thread::spawn(move || snowflake.generate())
+    };
+    assert!(wait_for_status(&snowflake, GeneratorStatus::Throttled));
+    waiter.join().unwrap().unwrap();
+    assert_eq!(snowflake.state().status, GeneratorStatus::Ready);
+}
@@ -1,11 +1,16 @@
 use std::collections::HashSet;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicI64, AtomicUsize, Ordering};
+use std::task::{Context, Poll, Wake, Waker};
 use std::thread;
 
 use std::time::{Duration, Instant};
 use parking_lot::Mutex;
-use snowflake_rs_impl::snowflake::Snowflake;
+use snowflake_rs_impl::clock::Clock;
+use snowflake_rs_impl::rate_limit::{RateLimit, ThrottleMode};
+use snowflake_rs_impl::exhaustion::ExhaustionStrategy;
+use snowflake_rs_impl::layout::Layout;
+use snowflake_rs_impl::snowflake::{InvalidEpochReason, Snowflake, SnowflakeError};
 
 
 ///Test generate one id
@@ -121,4 +126,186 @@ fn test_unique_ids() {
 #[test]
 fn test_node_out_of_range() {
     assert!(Snowflake::new(1024,None).is_err());
-}
\ No newline at end of file
+}
+
+/// Test that epochs in the future or beyond the layout's range are rejected
+#[test]
+fn test_invalid_epoch() {
+    let future = Snowflake::new(1, Some(i64::MAX / 2));
+    assert!(matches!(
+        future,
+        Err(SnowflakeError::InvalidEpoch { epoch, reason: InvalidEpochReason::InFuture, .. }) if epoch == i64::MAX / 2
+    ));
+
+    let layout = Layout::new(16, 16).unwrap();
+    let too_old = Snowflake::builder(1).layout(layout).epoch(0).build();
+    match too_old {
+        Err(SnowflakeError::InvalidEpoch { epoch: 0, now, reason: InvalidEpochReason::BeyondLayoutRange { max_offset } }) => {
+            assert_eq!(max_offset, layout.max_timestamp());
+            assert!(now as u64 > max_offset);
+        }
+        other => panic!("unexpected result: {:?}", other.map(|snowflake| snowflake.epoch())),
+    }
+    assert!(Snowflake::new(1, Some(i64::MIN)).is_err());
+
+    // Unix epoch is within the default layout, and an era doubles the range
+    assert!(Snowflake::new(1, Some(0)).is_ok());
+    let recent = Snowflake::builder(1)
+        .layout(layout)
+        .epoch(std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_millis() as i64 - layout.max_timestamp() as i64 - 1000)
+        .on_exhaustion(ExhaustionStrategy::Era);
+    assert!(recent.clone().build().is_ok());
+    assert!(recent.on_exhaustion(ExhaustionStrategy::Error).build().is_err());
+}
+
+/// Test that batch generation returns unique, ascending IDs
+#[test]
+fn test_generate_batch() {
+    let snowflake = Snowflake::new(1, None).unwrap();
+    let ids = snowflake.generate_batch(10_000).unwrap();
+    assert_eq!(ids.len(), 10_000);
+    assert!(ids.windows(2).all(|w| w[0] < w[1]));
+
+    let next = snowflake.generate_id().unwrap();
+    assert!(next > *ids.last().unwrap());
+}
+
+/// Test that the unchecked fast path produces unique, increasing IDs across threads
+#[test]
+fn test_generate_unchecked() {
+    let snowflake = Arc::new(Snowflake::new(1, None).unwrap());
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let snowflake = Arc::clone(&snowflake);
+            thread::spawn(move || {
+                let ids: Vec<u64> = (0..20_000).map(|_| snowflake.generate_unchecked()).collect();
+                assert!(ids.windows(2).all(|w| w[0] < w[1]));
+                ids
+            })
+        })
+        .collect();
+
+    let mut ids = HashSet::new();
+    for handle in handles {
+        ids.extend(handle.join().unwrap());
+    }
+    assert_eq!(ids.len(), 80_000);
+}
+
+/// Test that a fork keeps the configuration but uses its own node ID and state
+#[test]
+fn test_fork() {
+    let snowflake = Snowflake::builder(1)
+        .epoch(1672531200000)
+        .rate_limit(RateLimit::per_second(1000))
+        .build()
+        .unwrap();
+    snowflake.generate_batch(100).unwrap();
+
+    let fork = snowflake.fork(2).unwrap();
+    assert_eq!(fork.node(), 2);
+    assert_eq!(fork.epoch(), 1672531200000);
+    assert_eq!(fork.rate_limit(), snowflake.rate_limit());
+
+    let (_, node, sequence) = Snowflake::parse_id(fork.generate().unwrap());
+    assert_eq!(node, 2);
+    assert_eq!(sequence, 0);
+    assert!(snowflake.fork(1024).is_err());
+}
+
+/// Test that compose_id is the inverse of parse_id and validates each field
+#[test]
+fn test_compose_id() {
+    let snowflake = Snowflake::new(5, None).unwrap();
+    let id = snowflake.generate().unwrap();
+    let (timestamp, node, sequence) = Snowflake::parse_id(id);
+    assert_eq!(Snowflake::compose_id(timestamp, node, sequence).unwrap().as_u64(), id);
+
+    // Boundary IDs bracket every ID of a millisecond
+    let first = Snowflake::compose_id(timestamp, 0, 0).unwrap();
+    let last = Snowflake::compose_id(timestamp, 1023, 4095).unwrap();
+    assert!(first.as_u64() <= id && id <= last.as_u64());
+    assert_eq!(Snowflake::compose_id((1 << 41) - 1, 1023, 4095).unwrap().as_u64(), (1 << 63) - 1);
+
+    assert!(matches!(Snowflake::compose_id(1 << 41, 0, 0), Err(SnowflakeError::TimestampOutOfRange)));
+    assert!(matches!(Snowflake::compose_id(0, 1024, 0), Err(SnowflakeError::MachineIdOutOfRange)));
+    assert!(matches!(Snowflake::compose_id(0, 0, 4096), Err(SnowflakeError::SequenceOutOfRange)));
+}
+
+// Clock frozen at the reading set by the test
+struct FrozenClock(AtomicI64);
+
+impl Clock for FrozenClock {
+    fn now_millis(&self) -> i64 {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+// Waker counting how often it is woken
+struct CountingWaker(AtomicUsize);
+
+impl Wake for CountingWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+/// Test that poll_generate issues increasing IDs without blocking
+#[test]
+fn test_poll_generate_ready() {
+    let snowflake = Snowflake::new(1, None).unwrap();
+    let mut cx = Context::from_waker(Waker::noop());
+    let mut last = snowflake.generate_id().unwrap();
+    for _ in 0..10_000 {
+        if let Poll::Ready(id) = snowflake.poll_generate(&mut cx) {
+            let id = id.unwrap();
+            assert!(id > last);
+            last = id;
+        }
+    }
+}
+
+/// Test that poll_generate returns Pending and wakes the caller once the millisecond is full
+#[test]
+fn test_poll_generate_pending_until_next_millis() {
+    let clock = Arc::new(FrozenClock(AtomicI64::new(1700000000000)));
+    let snowflake = Snowflake::builder(1)
+        .layout(Layout::new(10, 2).unwrap())
+        .clock(clock.clone())
+        .build()
+        .unwrap();
+    let waker = Arc::new(CountingWaker(AtomicUsize::new(0)));
+    let waker_handle = Waker::from(Arc::clone(&waker));
+    let mut cx = Context::from_waker(&waker_handle);
+    for _ in 0..4 {
+        assert!(matches!(snowflake.poll_generate(&mut cx), Poll::Ready(Ok(_))));
+    }
+    assert!(snowflake.poll_generate(&mut cx).is_pending());
+    assert!(snowflake.poll_generate(&mut cx).is_pending());
+    assert_eq!(waker.0.load(Ordering::SeqCst), 2);
+
+    clock.0.store(1700000000001, Ordering::SeqCst);
+    let Poll::Ready(Ok(id)) = snowflake.poll_generate(&mut cx) else {
+        panic!("expected an ID in the next millisecond");
+    };
+    let (timestamp, _, sequence) = snowflake.layout().decompose(id.as_u64());
+    assert_eq!((timestamp as i64 + snowflake.epoch(), sequence), (1700000000001, 0));
+}
+
+/// Test that poll_generate fails or returns Pending on the rate limit, by throttle mode
+#[test]
+fn test_poll_generate_rate_limit() {
+    let mut cx = Context::from_waker(Waker::noop());
+    let limit = RateLimit::per_second(10).burst(1);
+    let snowflake = Snowflake::builder(1).rate_limit(limit.mode(ThrottleMode::Error)).build().unwrap();
+    assert!(snowflake.poll_generate(&mut cx).is_ready());
+    assert!(matches!(snowflake.poll_generate(&mut cx), Poll::Ready(Err(SnowflakeError::Throttled))));
+
+    let snowflake = Snowflake::builder(1).rate_limit(limit.mode(ThrottleMode::Wait)).build().unwrap();
+    assert!(snowflake.poll_generate(&mut cx).is_ready());
+    let start = Instant::now();
+    while snowflake.poll_generate(&mut cx).is_pending() {
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+    assert!(start.elapsed() >= Duration::from_millis(50));
+}
@@ -0,0 +1,116 @@
+#![cfg(feature = "tower")]
+
+use std::convert::Infallible;
+use std::future::Future;
+use std::pin::pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+
+use http::header::HeaderName;
+use http::Request;
+use snowflake_rs_impl::generator::{IdError, IdGenerator};
+use snowflake_rs_impl::id::{IdValidator, SnowflakeId};
+use snowflake_rs_impl::layout::Layout;
+use snowflake_rs_impl::snowflake::{Snowflake, SnowflakeError};
+use snowflake_rs_impl::tower::{RequestId, RequestIdLayer, REQUEST_ID_HEADER};
+use tower::{service_fn, Layer, Service, ServiceExt};
+
+// Polls a future that completes without waiting on I/O
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+// Returns the request ID extension and `header` value seen by the inner service
+fn call(layer: &RequestIdLayer, header: HeaderName, request: Request<()>) -> (Option<RequestId>, Option<String>) {
+    let service = layer.layer(service_fn(move |request: Request<()>| {
+        let value = request.headers().get(&header).map(|value| value.to_str().unwrap().to_string());
+        async move { Ok::<_, Infallible>((RequestId::from_extensions(request.extensions()), value)) }
+    }));
+    block_on(service.oneshot(request)).unwrap()
+}
+
+/// Test that every request gets a fresh ID in its extensions and header
+#[test]
+fn test_request_id_layer() {
+    let layer = RequestIdLayer::new(Arc::new(Snowflake::new(5, None).unwrap()));
+    let (first, header) = call(&layer, REQUEST_ID_HEADER, Request::new(()));
+    let first = first.unwrap();
+    assert_eq!(first.id().node(), 5);
+    assert_eq!(header.unwrap(), first.to_string());
+
+    let (second, _) = call(&layer, REQUEST_ID_HEADER, Request::new(()));
+    assert!(second.unwrap() > first);
+}
+
+/// Test a custom header and preserving an incoming request ID
+#[test]
+fn test_request_id_layer_options() {
+    let header = HeaderName::from_static("x-correlation-id");
+    let layer = RequestIdLayer::new(Arc::new(Snowflake::new(1, None).unwrap()))
+        .header(header.clone())
+        .preserve_incoming(true);
+
+    let request = Request::builder().header(&header, "123456789").body(()).unwrap();
+    let (id, value) = call(&layer, header.clone(), request);
+    assert_eq!(id, Some(RequestId(SnowflakeId::from_u64(123456789))));
+    assert_eq!(value.as_deref(), Some("123456789"));
+
+    // Values that are not IDs are replaced
+    let request = Request::builder().header(&header, "not-an-id").body(()).unwrap();
+    let (id, value) = call(&layer, header.clone(), request);
+    assert_eq!(value.unwrap(), id.unwrap().to_string());
+}
+
+/// Test that incoming IDs failing validation are replaced by fresh ones
+#[test]
+fn test_request_id_layer_rejects_invalid_incoming() {
+    let snowflake = Snowflake::new(1, None).unwrap();
+    let far_future = Layout::DEFAULT.compose(Layout::DEFAULT.max_timestamp(), 3, 0).unwrap();
+    let layer = RequestIdLayer::new(Arc::new(snowflake)).preserve_incoming(true);
+    for incoming in [u64::MAX.to_string(), (1u64 << 63 | 42).to_string(), far_future.to_string()] {
+        let request = Request::builder().header(REQUEST_ID_HEADER, &incoming).body(()).unwrap();
+        let (id, value) = call(&layer, REQUEST_ID_HEADER, request);
+        let id = id.unwrap();
+        assert_eq!(id.id().node(), 1);
+        assert_eq!(value.unwrap(), id.to_string());
+        assert_ne!(id.to_string(), incoming);
+    }
+
+    // A stricter validator also rejects IDs that are otherwise well-formed
+    let stale = Layout::DEFAULT.compose(1000, 3, 0).unwrap();
+    let layer = RequestIdLayer::new(Arc::new(Snowflake::new(1, None).unwrap()))
+        .preserve_incoming(true)
+        .validator(IdValidator::new().not_before(1640995200000).max_future_skew(Duration::from_secs(1)));
+    let request = Request::builder().header(REQUEST_ID_HEADER, stale.to_string()).body(()).unwrap();
+    let (id, _) = call(&layer, REQUEST_ID_HEADER, request);
+    assert_ne!(id.unwrap().id().as_u64(), stale);
+}
+
+/// Test that a failing generator passes the request on without an ID
+#[test]
+fn test_request_id_layer_generator_error() {
+    struct Failing;
+    impl IdGenerator for Failing {
+        fn next_id(&self) -> Result<SnowflakeId, IdError> {
+            Err(SnowflakeError::ClockMovedBackwards)
+        }
+    }
+
+    let mut service = RequestIdLayer::new(Arc::new(Failing)).layer(service_fn(|request: Request<()>| async move {
+        Ok::<_, Infallible>(RequestId::from_extensions(request.extensions()).is_none() && request.headers().is_empty())
+    }));
+    block_on(service.ready()).unwrap();
+    assert!(block_on(service.call(Request::new(()))).unwrap());
+
+    // An incoming header that fails validation is not passed on either
+    let layer = RequestIdLayer::new(Arc::new(Failing)).preserve_incoming(true);
+    let request = Request::builder().header(REQUEST_ID_HEADER, u64::MAX.to_string()).body(()).unwrap();
+    assert_eq!(call(&layer, REQUEST_ID_HEADER, request), (None, None));
+}
@@ -0,0 +1,162 @@
+#![cfg(feature = "uuid")]
+
+use snowflake_rs_impl::exhaustion::ERA_BIT;
+use snowflake_rs_impl::id::SnowflakeId;
+use snowflake_rs_impl::layout::Layout;
+use snowflake_rs_impl::snowflake::{InvalidIdReason, Snowflake, SnowflakeError};
+use snowflake_rs_impl::uuid::{
+    embed, extract, from_uuid_v7, is_embedded_snowflake, to_uuid_v7, EmbeddedSnowflake, MAX_EMBEDDED_EPOCH,
+};
+use uuid::{Uuid, Variant};
+
+/// Test that embedding is lossless and produces an RFC 9562 UUIDv8
+#[test]
+fn test_embed_round_trip() {
+    let layout = Layout::new(8, 14).unwrap();
+    for raw in [0, 1, 0x0123_4567_89ab_cdef, u64::MAX >> 1, u64::MAX] {
+        let id = SnowflakeId::from_u64(raw);
+        let uuid = embed(id, Some(1672531200123), &layout).unwrap();
+        assert_eq!(uuid.get_version_num(), 8);
+        assert_eq!(uuid.get_variant(), Variant::RFC4122);
+
+        let embedded = extract(&uuid).unwrap();
+        assert_eq!(embedded.id, id);
+        assert_eq!(embedded.epoch, 1672531200123);
+        assert_eq!(embedded.layout, layout);
+    }
+}
+
+/// Test that UUIDs sort in the same order as the IDs they embed
+#[test]
+fn test_embed_preserves_order() {
+    let snowflake = Snowflake::new(1, None).unwrap();
+    let mut ids: Vec<u64> = (0..1000).map(|_| snowflake.generate().unwrap()).collect();
+    ids.extend([1, 2, 15, 16, 17, 0xffff, 0x1_0000, u64::MAX >> 1]);
+    ids.sort_unstable();
+
+    let uuids: Vec<Uuid> = ids.iter().map(|&id| embed(SnowflakeId::from(id), None, &Layout::DEFAULT).unwrap()).collect();
+    assert!(uuids.windows(2).all(|pair| pair[0] < pair[1]));
+    assert!(uuids.windows(2).all(|pair| pair[0].as_bytes() < pair[1].as_bytes()));
+}
+
+/// Test that a generator's UUIDs carry its epoch and layout
+#[test]
+fn test_generate_uuid() {
+    let layout = Layout::new(12, 10).unwrap();
+    let snowflake = Snowflake::builder(4000).epoch(1672531200000).layout(layout).build().unwrap();
+    let embedded = extract(&snowflake.generate_uuid().unwrap()).unwrap();
+    assert_eq!(embedded.epoch, snowflake.epoch());
+    assert_eq!(embedded.layout, layout);
+    assert_eq!(layout.decompose(embedded.id.as_u64()).1, 4000);
+}
+
+/// Test that other UUIDs and unembeddable epochs are rejected
+#[test]
+fn test_embed_errors() {
+    assert!(matches!(extract(&Uuid::nil()), Err(SnowflakeError::InvalidUuid)));
+    assert!(matches!(extract(&Uuid::max()), Err(SnowflakeError::InvalidUuid)));
+    let v4 = Uuid::parse_str("f47ac10b-58cc-4372-a567-0e02b2c3d479").unwrap();
+    assert!(matches!(extract(&v4), Err(SnowflakeError::InvalidUuid)));
+
+    // A UUIDv8 from another scheme is not mistaken for an embedded ID
+    let other_v8 = Uuid::from_u128(0x0123_4567_89ab_8def_8000_0000_0000_0000);
+    assert!(matches!(extract(&other_v8), Err(SnowflakeError::InvalidUuid)));
+
+    let id = SnowflakeId::from_u64(1);
+    assert!(matches!(embed(id, Some(-1), &Layout::DEFAULT), Err(SnowflakeError::UnembeddableEpoch(-1))));
+    assert!(embed(id, Some(MAX_EMBEDDED_EPOCH), &Layout::DEFAULT).is_ok());
+    assert!(embed(id, Some(MAX_EMBEDDED_EPOCH + 1), &Layout::DEFAULT).is_err());
+    // The UUID has no room to record a check field
+    let checked = Layout::DEFAULT.with_check_bits(4).unwrap();
+    assert!(matches!(embed(id, None, &checked), Err(SnowflakeError::InvalidLayout)));
+}
+
+/// Test that the UUIDv7 mapping drops the check field and restores it on the way back
+#[test]
+fn test_uuid_v7_check_bits() {
+    let layout = Layout::new(6, 12).unwrap().with_check_bits(4).unwrap();
+    let id = SnowflakeId::from(layout.compose(123_456, 33, 4095).unwrap());
+    let uuid = to_uuid_v7(id, None, &layout).unwrap();
+    assert_eq!(uuid.get_version_num(), 7);
+    assert_eq!(from_uuid_v7(&uuid, None, &layout).unwrap(), id);
+}
+
+/// Test the From/TryFrom conversions and embedded-ID detection
+#[test]
+fn test_conversions() {
+    let snowflake = Snowflake::new(9, None).unwrap();
+    let id = snowflake.generate_id().unwrap();
+
+    let uuid = Uuid::from(id);
+    assert_eq!(uuid, embed(id, None, &Layout::DEFAULT).unwrap());
+    assert!(is_embedded_snowflake(&uuid));
+    assert_eq!(SnowflakeId::try_from(uuid).unwrap(), id);
+
+    let layout = Layout::new(6, 16).unwrap();
+    let uuid = embed(id, Some(1672531200000), &layout).unwrap();
+    let embedded = EmbeddedSnowflake::try_from(uuid).unwrap();
+    assert_eq!(embedded.layout, layout);
+    assert_eq!(Uuid::try_from(embedded).unwrap(), uuid);
+
+    let unembeddable = EmbeddedSnowflake {
+        epoch: -1,
+        ..embedded
+    };
+    assert!(matches!(Uuid::try_from(unembeddable), Err(SnowflakeError::UnembeddableEpoch(-1))));
+    let checked = EmbeddedSnowflake {
+        layout: Layout::DEFAULT.with_check_bits(4).unwrap(),
+        ..embedded
+    };
+    assert!(matches!(Uuid::try_from(checked), Err(SnowflakeError::InvalidLayout)));
+
+    let v4 = Uuid::parse_str("f47ac10b-58cc-4372-a567-0e02b2c3d479").unwrap();
+    assert!(!is_embedded_snowflake(&v4));
+    assert!(matches!(SnowflakeId::try_from(v4), Err(SnowflakeError::InvalidUuid)));
+}
+
+/// Test that the UUIDv7 mapping is a valid, time-ordered UUIDv7 and round-trips
+#[test]
+fn test_uuid_v7_round_trip() {
+    let layout = Layout::new(12, 10).unwrap();
+    let snowflake = Snowflake::builder(4000).layout(layout).build().unwrap();
+    let mut ids: Vec<SnowflakeId> = (0..1000).map(|_| snowflake.generate_id().unwrap()).collect();
+    ids.push(SnowflakeId::from(layout.compose(1, layout.max_node(), layout.max_sequence()).unwrap()));
+    ids.push(SnowflakeId::from(layout.compose(2, 0, 0).unwrap()));
+    ids.sort_unstable();
+
+    let uuids: Vec<Uuid> = ids.iter().map(|&id| to_uuid_v7(id, None, &layout).unwrap()).collect();
+    assert!(uuids.windows(2).all(|pair| pair[0].as_bytes() < pair[1].as_bytes()));
+    for (&id, uuid) in ids.iter().zip(&uuids) {
+        assert_eq!(uuid.get_version_num(), 7);
+        assert_eq!(uuid.get_variant(), Variant::RFC4122);
+        let (seconds, nanos) = uuid.get_timestamp().unwrap().to_unix();
+        let (timestamp, _, _) = layout.decompose(id.as_u64());
+        assert_eq!(seconds * 1000 + nanos as u64 / 1_000_000, snowflake.epoch() as u64 + timestamp);
+        assert_eq!(from_uuid_v7(uuid, None, &layout).unwrap(), id);
+    }
+}
+
+/// Test that foreign UUIDv7s and out-of-range times are rejected
+#[test]
+fn test_uuid_v7_errors() {
+    let foreign = Uuid::parse_str("01890a5d-ac96-774b-bcce-b302099a8057").unwrap();
+    assert!(matches!(from_uuid_v7(&foreign, None, &Layout::DEFAULT), Err(SnowflakeError::InvalidUuid)));
+    let embedded = embed(SnowflakeId::from_u64(1), None, &Layout::DEFAULT).unwrap();
+    assert!(matches!(from_uuid_v7(&embedded, None, &Layout::DEFAULT), Err(SnowflakeError::InvalidUuid)));
+
+    // Fields wider than the layout are rejected
+    let wide = to_uuid_v7(SnowflakeId::from_u64(u64::MAX >> 1), None, &Layout::new(14, 12).unwrap()).unwrap();
+    assert!(matches!(from_uuid_v7(&wide, None, &Layout::DEFAULT), Err(SnowflakeError::InvalidUuid)));
+
+    let id = SnowflakeId::from_u64(1 << 22);
+    assert!(matches!(to_uuid_v7(id, Some(-10), &Layout::DEFAULT), Err(SnowflakeError::TimestampOutOfRange)));
+    let uuid = to_uuid_v7(id, Some(0), &Layout::DEFAULT).unwrap();
+    assert!(matches!(from_uuid_v7(&uuid, Some(5), &Layout::DEFAULT), Err(SnowflakeError::TimestampOutOfRange)));
+
+    // Second-era IDs have no UUIDv7 mapping
+    let era = SnowflakeId::from_u64(ERA_BIT | id.as_u64());
+    assert!(matches!(
+        to_uuid_v7(era, None, &Layout::DEFAULT),
+        Err(SnowflakeError::InvalidId(InvalidIdReason::ReservedBitSet))
+    ));
+}